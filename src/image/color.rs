@@ -1,6 +1,6 @@
 use crate::math::la::vector4::Vector4;
 
-#[derive(Copy, Clone, Default, Debug)]
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
 #[repr(C)]
 pub struct ColorRGBA {
     pub r: u8,
@@ -17,6 +17,60 @@ pub struct ColorRGB {
     pub b: u8,
 }
 
+impl From<[u8; 4]> for ColorRGBA {
+    fn from([r, g, b, a]: [u8; 4]) -> ColorRGBA {
+        ColorRGBA { r, g, b, a }
+    }
+}
+
+impl From<ColorRGBA> for [u8; 4] {
+    fn from(c: ColorRGBA) -> [u8; 4] {
+        [c.r, c.g, c.b, c.a]
+    }
+}
+
+impl From<[u8; 3]> for ColorRGB {
+    fn from([r, g, b]: [u8; 3]) -> ColorRGB {
+        ColorRGB { r, g, b }
+    }
+}
+
+impl From<ColorRGB> for [u8; 3] {
+    fn from(c: ColorRGB) -> [u8; 3] {
+        [c.r, c.g, c.b]
+    }
+}
+
+// Serialized as [r, g, b, a]
+#[cfg(feature = "serde")]
+impl serde::Serialize for ColorRGBA {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        <[u8; 4]>::from(*self).serialize(s)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ColorRGBA {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<ColorRGBA, D::Error> {
+        <[u8; 4]>::deserialize(d).map(ColorRGBA::from)
+    }
+}
+
+// Serialized as [r, g, b]
+#[cfg(feature = "serde")]
+impl serde::Serialize for ColorRGB {
+    fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+        <[u8; 3]>::from(*self).serialize(s)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ColorRGB {
+    fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<ColorRGB, D::Error> {
+        <[u8; 3]>::deserialize(d).map(ColorRGB::from)
+    }
+}
+
 impl ColorRGBA {
     pub const fn new() -> Self {
         ColorRGBA {
@@ -43,7 +97,7 @@ impl ColorRGBA {
         }
     }
     pub fn to_u32(self) -> u32 {
-        return u32::from_le_bytes([self.r, self.g, self.b, self.a]);
+        u32::from_le_bytes([self.r, self.g, self.b, self.a])
     }
     pub fn from_vector4(self, v: Vector4) -> ColorRGBA {
         ColorRGBA {
@@ -22,5 +22,5 @@ pub fn is_down(key: usize) -> bool {
 }
 
 pub fn get_position() -> (f32, f32) {
-    return (POSITION[0], POSITION[1]);
+    (POSITION[0], POSITION[1])
 }
@@ -0,0 +1,435 @@
+use crate::image::color::ColorRGBA;
+use crate::math::geom::bounding_box::BoundingBox;
+use crate::math::geom::sphere::Sphere;
+use crate::math::la::dual_quaternion::DualQuaternion;
+use crate::math::la::matrix2::Matrix2x2;
+use crate::math::la::matrix3::Matrix3x3;
+use crate::math::la::matrix4::Matrix4x4;
+use crate::math::la::quaternion::Quaternion;
+use crate::math::la::vector2::Vector2;
+use crate::math::la::vector3::Vector3;
+use crate::math::la::vector4::Vector4;
+use std::ops;
+
+// Floating point comparison with three modes:
+// abs_diff_eq  - |a - b| <= epsilon, good near zero
+// relative_eq  - |a - b| <= max(|a|, |b|) * max_relative, good for big values
+// ulps_eq      - a and b are at most max_ulps representable floats apart
+// relative_eq and ulps_eq also accept anything within epsilon so values
+// around zero compare equal. NaN is never equal to anything.
+pub trait ApproxEq {
+    type Epsilon: Copy;
+
+    fn default_epsilon() -> Self::Epsilon;
+    fn default_max_relative() -> Self::Epsilon;
+    fn default_max_ulps() -> u32;
+
+    fn abs_diff_eq(&self, other: &Self, epsilon: Self::Epsilon) -> bool;
+    fn relative_eq(
+        &self,
+        other: &Self,
+        epsilon: Self::Epsilon,
+        max_relative: Self::Epsilon,
+    ) -> bool;
+    fn ulps_eq(&self, other: &Self, epsilon: Self::Epsilon, max_ulps: u32) -> bool;
+
+    // relative_eq with the default tolerances
+    fn approx_eq(&self, other: &Self) -> bool {
+        self.relative_eq(other, Self::default_epsilon(), Self::default_max_relative())
+    }
+}
+
+macro_rules! impl_approx_float {
+    ($t:ident, $bits:ty) => {
+        impl ApproxEq for $t {
+            type Epsilon = $t;
+
+            fn default_epsilon() -> $t {
+                $t::EPSILON
+            }
+
+            fn default_max_relative() -> $t {
+                $t::EPSILON
+            }
+
+            fn default_max_ulps() -> u32 {
+                4
+            }
+
+            fn abs_diff_eq(&self, other: &$t, epsilon: $t) -> bool {
+                (self - other).abs() <= epsilon
+            }
+
+            fn relative_eq(&self, other: &$t, epsilon: $t, max_relative: $t) -> bool {
+                // Covers infinities of the same sign
+                if self == other {
+                    return true;
+                }
+                if self.is_infinite() || other.is_infinite() {
+                    return false;
+                }
+                let diff = (self - other).abs();
+                if diff <= epsilon {
+                    return true;
+                }
+                diff <= self.abs().max(other.abs()) * max_relative
+            }
+
+            fn ulps_eq(&self, other: &$t, epsilon: $t, max_ulps: u32) -> bool {
+                if self.abs_diff_eq(other, epsilon) {
+                    return true;
+                }
+                if self.is_nan()
+                    || other.is_nan()
+                    || self.is_sign_negative() != other.is_sign_negative()
+                {
+                    return false;
+                }
+                // Same sign floats are ordered like their bit patterns
+                self.to_bits().abs_diff(other.to_bits()) <= max_ulps as $bits
+            }
+        }
+    };
+}
+
+impl_approx_float!(f32, u32);
+impl_approx_float!(f64, u64);
+
+impl<T: ApproxEq, const N: usize> ApproxEq for [T; N] {
+    type Epsilon = T::Epsilon;
+
+    fn default_epsilon() -> T::Epsilon {
+        T::default_epsilon()
+    }
+
+    fn default_max_relative() -> T::Epsilon {
+        T::default_max_relative()
+    }
+
+    fn default_max_ulps() -> u32 {
+        T::default_max_ulps()
+    }
+
+    fn abs_diff_eq(&self, other: &[T; N], epsilon: T::Epsilon) -> bool {
+        self.iter()
+            .zip(other.iter())
+            .all(|(a, b)| a.abs_diff_eq(b, epsilon))
+    }
+
+    fn relative_eq(&self, other: &[T; N], epsilon: T::Epsilon, max_relative: T::Epsilon) -> bool {
+        self.iter()
+            .zip(other.iter())
+            .all(|(a, b)| a.relative_eq(b, epsilon, max_relative))
+    }
+
+    fn ulps_eq(&self, other: &[T; N], epsilon: T::Epsilon, max_ulps: u32) -> bool {
+        self.iter()
+            .zip(other.iter())
+            .all(|(a, b)| a.ulps_eq(b, epsilon, max_ulps))
+    }
+}
+
+// Compare a type as the array returned by $to_array
+macro_rules! impl_approx_as_array {
+    ($V:ty, $T:ident, $to_array:expr) => {
+        impl<$T: ApproxEq + Copy> ApproxEq for $V {
+            type Epsilon = $T::Epsilon;
+
+            fn default_epsilon() -> $T::Epsilon {
+                $T::default_epsilon()
+            }
+
+            fn default_max_relative() -> $T::Epsilon {
+                $T::default_max_relative()
+            }
+
+            fn default_max_ulps() -> u32 {
+                $T::default_max_ulps()
+            }
+
+            fn abs_diff_eq(&self, other: &$V, epsilon: $T::Epsilon) -> bool {
+                $to_array(self).abs_diff_eq(&$to_array(other), epsilon)
+            }
+
+            fn relative_eq(
+                &self,
+                other: &$V,
+                epsilon: $T::Epsilon,
+                max_relative: $T::Epsilon,
+            ) -> bool {
+                $to_array(self).relative_eq(&$to_array(other), epsilon, max_relative)
+            }
+
+            fn ulps_eq(&self, other: &$V, epsilon: $T::Epsilon, max_ulps: u32) -> bool {
+                $to_array(self).ulps_eq(&$to_array(other), epsilon, max_ulps)
+            }
+        }
+    };
+}
+
+impl_approx_as_array!(Vector2<T>, T, |v: &Vector2<T>| [v.x, v.y]);
+impl_approx_as_array!(Vector3<T>, T, |v: &Vector3<T>| [v.x, v.y, v.z]);
+impl_approx_as_array!(Vector4<T>, T, |v: &Vector4<T>| [v.x, v.y, v.z, v.w]);
+impl_approx_as_array!(Matrix2x2<T>, T, |m: &Matrix2x2<T>| m.raw);
+impl_approx_as_array!(Matrix3x3<T>, T, |m: &Matrix3x3<T>| m.raw);
+impl_approx_as_array!(Matrix4x4<T>, T, |m: &Matrix4x4<T>| m.raw);
+
+// Compare a type as the array returned by $to_array, also accepting the
+// negated array
+macro_rules! impl_approx_signed {
+    ($V:ty, $T:ident, $to_array:expr) => {
+        impl<$T: ApproxEq + Copy + ops::Neg<Output = $T>> ApproxEq for $V {
+            type Epsilon = $T::Epsilon;
+
+            fn default_epsilon() -> $T::Epsilon {
+                $T::default_epsilon()
+            }
+
+            fn default_max_relative() -> $T::Epsilon {
+                $T::default_max_relative()
+            }
+
+            fn default_max_ulps() -> u32 {
+                $T::default_max_ulps()
+            }
+
+            fn abs_diff_eq(&self, other: &$V, epsilon: $T::Epsilon) -> bool {
+                let (a, b) = ($to_array(self), $to_array(other));
+                a.abs_diff_eq(&b, epsilon) || a.abs_diff_eq(&b.map(|v| -v), epsilon)
+            }
+
+            fn relative_eq(
+                &self,
+                other: &$V,
+                epsilon: $T::Epsilon,
+                max_relative: $T::Epsilon,
+            ) -> bool {
+                let (a, b) = ($to_array(self), $to_array(other));
+                a.relative_eq(&b, epsilon, max_relative)
+                    || a.relative_eq(&b.map(|v| -v), epsilon, max_relative)
+            }
+
+            fn ulps_eq(&self, other: &$V, epsilon: $T::Epsilon, max_ulps: u32) -> bool {
+                let (a, b) = ($to_array(self), $to_array(other));
+                a.ulps_eq(&b, epsilon, max_ulps) || a.ulps_eq(&b.map(|v| -v), epsilon, max_ulps)
+            }
+        }
+    };
+}
+
+// q and -q are the same rotation
+impl_approx_signed!(Quaternion<T>, T, |q: &Quaternion<T>| [q.x, q.y, q.z, q.w]);
+
+// Both parts flip sign together, (-real, -dual) is the same transform
+impl_approx_signed!(DualQuaternion<T>, T, |d: &DualQuaternion<T>| [
+    d.real.x, d.real.y, d.real.z, d.real.w, d.dual.x, d.dual.y, d.dual.z, d.dual.w
+]);
+
+// Plain f32 structs compared field by field
+macro_rules! impl_approx_f32_fields {
+    ($Type:ty, $to_array:expr) => {
+        impl ApproxEq for $Type {
+            type Epsilon = f32;
+
+            fn default_epsilon() -> f32 {
+                f32::EPSILON
+            }
+
+            fn default_max_relative() -> f32 {
+                f32::EPSILON
+            }
+
+            fn default_max_ulps() -> u32 {
+                4
+            }
+
+            fn abs_diff_eq(&self, other: &$Type, epsilon: f32) -> bool {
+                let f = $to_array;
+                f(self).abs_diff_eq(&f(other), epsilon)
+            }
+
+            fn relative_eq(&self, other: &$Type, epsilon: f32, max_relative: f32) -> bool {
+                let f = $to_array;
+                f(self).relative_eq(&f(other), epsilon, max_relative)
+            }
+
+            fn ulps_eq(&self, other: &$Type, epsilon: f32, max_ulps: u32) -> bool {
+                let f = $to_array;
+                f(self).ulps_eq(&f(other), epsilon, max_ulps)
+            }
+        }
+    };
+}
+
+impl_approx_f32_fields!(BoundingBox, |b: &BoundingBox| [
+    b.min_x, b.max_x, b.min_y, b.max_y, b.min_z, b.max_z
+]);
+
+impl_approx_f32_fields!(Sphere, |s: &Sphere| [
+    s.center.x, s.center.y, s.center.z, s.radius
+]);
+
+// Channels are compared as floats in 0..1, epsilon of 1.0 / 255.0 allows one
+// step of difference
+impl ApproxEq for ColorRGBA {
+    type Epsilon = f32;
+
+    fn default_epsilon() -> f32 {
+        f32::EPSILON
+    }
+
+    fn default_max_relative() -> f32 {
+        f32::EPSILON
+    }
+
+    fn default_max_ulps() -> u32 {
+        4
+    }
+
+    fn abs_diff_eq(&self, other: &ColorRGBA, epsilon: f32) -> bool {
+        self.to_vector4().abs_diff_eq(&other.to_vector4(), epsilon)
+    }
+
+    fn relative_eq(&self, other: &ColorRGBA, epsilon: f32, max_relative: f32) -> bool {
+        self.to_vector4()
+            .relative_eq(&other.to_vector4(), epsilon, max_relative)
+    }
+
+    fn ulps_eq(&self, other: &ColorRGBA, epsilon: f32, max_ulps: u32) -> bool {
+        self.to_vector4()
+            .ulps_eq(&other.to_vector4(), epsilon, max_ulps)
+    }
+}
+
+// Defaults of the type behind a reference, used by the assert macros
+pub fn default_epsilon<T: ApproxEq>(_: &T) -> T::Epsilon {
+    T::default_epsilon()
+}
+
+pub fn default_max_relative<T: ApproxEq>(_: &T) -> T::Epsilon {
+    T::default_max_relative()
+}
+
+pub fn default_max_ulps<T: ApproxEq>(_: &T) -> u32 {
+    T::default_max_ulps()
+}
+
+// Shared body of the assert macros, $ok sees both sides as $x and $y
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __approx_check {
+    ($name:literal, $a:expr, $b:expr, |$x:ident, $y:ident| $ok:expr) => {
+        match (&$a, &$b) {
+            ($x, $y) => {
+                if !$ok {
+                    panic!(
+                        concat!($name, "!({}, {}) failed\n  left: {:?}\n right: {:?}"),
+                        stringify!($a),
+                        stringify!($b),
+                        $x,
+                        $y
+                    );
+                }
+            }
+        }
+    };
+}
+
+// assert_approx_eq!(a, b) or assert_approx_eq!(a, b, epsilon = 1e-4)
+#[macro_export]
+macro_rules! assert_approx_eq {
+    ($a:expr, $b:expr $(,)?) => {
+        $crate::__approx_check!("assert_approx_eq", $a, $b, |a, b| {
+            $crate::math::approx::ApproxEq::abs_diff_eq(
+                a,
+                b,
+                $crate::math::approx::default_epsilon(a),
+            )
+        })
+    };
+    ($a:expr, $b:expr, epsilon = $eps:expr $(,)?) => {
+        $crate::__approx_check!("assert_approx_eq", $a, $b, |a, b| {
+            $crate::math::approx::ApproxEq::abs_diff_eq(a, b, $eps)
+        })
+    };
+}
+
+// assert_relative_eq!(a, b), optionally with epsilon = 1e-6 and/or max_relative = 1e-4
+#[macro_export]
+macro_rules! assert_relative_eq {
+    ($a:expr, $b:expr $(,)?) => {
+        $crate::__approx_check!("assert_relative_eq", $a, $b, |a, b| {
+            $crate::math::approx::ApproxEq::relative_eq(
+                a,
+                b,
+                $crate::math::approx::default_epsilon(a),
+                $crate::math::approx::default_max_relative(a),
+            )
+        })
+    };
+    ($a:expr, $b:expr, epsilon = $eps:expr $(,)?) => {
+        $crate::__approx_check!("assert_relative_eq", $a, $b, |a, b| {
+            $crate::math::approx::ApproxEq::relative_eq(
+                a,
+                b,
+                $eps,
+                $crate::math::approx::default_max_relative(a),
+            )
+        })
+    };
+    ($a:expr, $b:expr, max_relative = $rel:expr $(,)?) => {
+        $crate::__approx_check!("assert_relative_eq", $a, $b, |a, b| {
+            $crate::math::approx::ApproxEq::relative_eq(
+                a,
+                b,
+                $crate::math::approx::default_epsilon(a),
+                $rel,
+            )
+        })
+    };
+    ($a:expr, $b:expr, epsilon = $eps:expr, max_relative = $rel:expr $(,)?) => {
+        $crate::__approx_check!("assert_relative_eq", $a, $b, |a, b| {
+            $crate::math::approx::ApproxEq::relative_eq(a, b, $eps, $rel)
+        })
+    };
+}
+
+// assert_ulps_eq!(a, b), optionally with epsilon = 1e-6 and/or max_ulps = 8
+#[macro_export]
+macro_rules! assert_ulps_eq {
+    ($a:expr, $b:expr $(,)?) => {
+        $crate::__approx_check!("assert_ulps_eq", $a, $b, |a, b| {
+            $crate::math::approx::ApproxEq::ulps_eq(
+                a,
+                b,
+                $crate::math::approx::default_epsilon(a),
+                $crate::math::approx::default_max_ulps(a),
+            )
+        })
+    };
+    ($a:expr, $b:expr, epsilon = $eps:expr $(,)?) => {
+        $crate::__approx_check!("assert_ulps_eq", $a, $b, |a, b| {
+            $crate::math::approx::ApproxEq::ulps_eq(
+                a,
+                b,
+                $eps,
+                $crate::math::approx::default_max_ulps(a),
+            )
+        })
+    };
+    ($a:expr, $b:expr, max_ulps = $ulps:expr $(,)?) => {
+        $crate::__approx_check!("assert_ulps_eq", $a, $b, |a, b| {
+            $crate::math::approx::ApproxEq::ulps_eq(
+                a,
+                b,
+                $crate::math::approx::default_epsilon(a),
+                $ulps,
+            )
+        })
+    };
+    ($a:expr, $b:expr, epsilon = $eps:expr, max_ulps = $ulps:expr $(,)?) => {
+        $crate::__approx_check!("assert_ulps_eq", $a, $b, |a, b| {
+            $crate::math::approx::ApproxEq::ulps_eq(a, b, $eps, $ulps)
+        })
+    };
+}
@@ -1,3 +1,11 @@
+use crate::math::la::matrix4::Matrix4x4;
+use crate::math::la::vector3::Vector3;
+use crate::render::mesh::mesh::MeshData;
+
+// Axis aligned box. Default is the zero sized box at the origin, empty() is
+// the box with min above max that any union or add_point replaces
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BoundingBox {
     pub min_x: f32,
     pub max_x: f32,
@@ -6,3 +14,201 @@ pub struct BoundingBox {
     pub min_z: f32,
     pub max_z: f32,
 }
+
+#[allow(dead_code)]
+impl BoundingBox {
+    pub const fn new(min: Vector3, max: Vector3) -> BoundingBox {
+        BoundingBox {
+            min_x: min.x,
+            max_x: max.x,
+            min_y: min.y,
+            max_y: max.y,
+            min_z: min.z,
+            max_z: max.z,
+        }
+    }
+
+    pub const fn empty() -> BoundingBox {
+        BoundingBox {
+            min_x: f32::INFINITY,
+            max_x: f32::NEG_INFINITY,
+            min_y: f32::INFINITY,
+            max_y: f32::NEG_INFINITY,
+            min_z: f32::INFINITY,
+            max_z: f32::NEG_INFINITY,
+        }
+    }
+
+    pub fn from_center_extents(center: Vector3, extents: Vector3) -> BoundingBox {
+        BoundingBox::new(center - extents, center + extents)
+    }
+
+    // Empty for no points
+    pub fn from_points(points: &[Vector3]) -> BoundingBox {
+        let mut b = BoundingBox::empty();
+        for p in points.iter() {
+            b.add_point(*p);
+        }
+        b
+    }
+
+    pub fn from_mesh(mesh: &MeshData) -> BoundingBox {
+        BoundingBox::from_points(&mesh.vertex)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min_x > self.max_x || self.min_y > self.max_y || self.min_z > self.max_z
+    }
+
+    pub fn min(&self) -> Vector3 {
+        Vector3::new(self.min_x, self.min_y, self.min_z)
+    }
+
+    pub fn max(&self) -> Vector3 {
+        Vector3::new(self.max_x, self.max_y, self.max_z)
+    }
+
+    pub fn center(&self) -> Vector3 {
+        (self.min() + self.max()) * 0.5
+    }
+
+    pub fn size(&self) -> Vector3 {
+        self.max() - self.min()
+    }
+
+    // Half of the size
+    pub fn extents(&self) -> Vector3 {
+        self.size() * 0.5
+    }
+
+    pub fn volume(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let s = self.size();
+        s.x * s.y * s.z
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        if self.is_empty() {
+            return 0.0;
+        }
+        let s = self.size();
+        2.0 * (s.x * s.y + s.y * s.z + s.z * s.x)
+    }
+
+    // Grow the box so it contains p
+    pub fn add_point(&mut self, p: Vector3) {
+        self.min_x = self.min_x.min(p.x);
+        self.max_x = self.max_x.max(p.x);
+        self.min_y = self.min_y.min(p.y);
+        self.max_y = self.max_y.max(p.y);
+        self.min_z = self.min_z.min(p.z);
+        self.max_z = self.max_z.max(p.z);
+    }
+
+    // Move every side out by amount, negative amount shrinks
+    pub fn expand(&self, amount: f32) -> BoundingBox {
+        let v = Vector3::new(amount, amount, amount);
+        BoundingBox::new(self.min() - v, self.max() + v)
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min_x: self.min_x.min(other.min_x),
+            max_x: self.max_x.max(other.max_x),
+            min_y: self.min_y.min(other.min_y),
+            max_y: self.max_y.max(other.max_y),
+            min_z: self.min_z.min(other.min_z),
+            max_z: self.max_z.max(other.max_z),
+        }
+    }
+
+    // None when the boxes don't overlap, touching boxes give a flat box
+    pub fn intersection(&self, other: &BoundingBox) -> Option<BoundingBox> {
+        let b = BoundingBox {
+            min_x: self.min_x.max(other.min_x),
+            max_x: self.max_x.min(other.max_x),
+            min_y: self.min_y.max(other.min_y),
+            max_y: self.max_y.min(other.max_y),
+            min_z: self.min_z.max(other.min_z),
+            max_z: self.max_z.min(other.max_z),
+        };
+        if b.is_empty() {
+            return None;
+        }
+        Some(b)
+    }
+
+    // Points on the surface are inside
+    pub fn contains_point(&self, p: Vector3) -> bool {
+        p.x >= self.min_x
+            && p.x <= self.max_x
+            && p.y >= self.min_y
+            && p.y <= self.max_y
+            && p.z >= self.min_z
+            && p.z <= self.max_z
+    }
+
+    pub fn contains(&self, other: &BoundingBox) -> bool {
+        other.min_x >= self.min_x
+            && other.max_x <= self.max_x
+            && other.min_y >= self.min_y
+            && other.max_y <= self.max_y
+            && other.min_z >= self.min_z
+            && other.max_z <= self.max_z
+    }
+
+    // Touching boxes intersect
+    pub fn intersects(&self, other: &BoundingBox) -> bool {
+        self.min_x <= other.max_x
+            && self.max_x >= other.min_x
+            && self.min_y <= other.max_y
+            && self.max_y >= other.min_y
+            && self.min_z <= other.max_z
+            && self.max_z >= other.min_z
+    }
+
+    pub fn closest_point(&self, p: Vector3) -> Vector3 {
+        p.clamp(self.min(), self.max())
+    }
+
+    pub fn corners(&self) -> [Vector3; 8] {
+        [
+            Vector3::new(self.min_x, self.min_y, self.min_z),
+            Vector3::new(self.max_x, self.min_y, self.min_z),
+            Vector3::new(self.min_x, self.max_y, self.min_z),
+            Vector3::new(self.max_x, self.max_y, self.min_z),
+            Vector3::new(self.min_x, self.min_y, self.max_z),
+            Vector3::new(self.max_x, self.min_y, self.max_z),
+            Vector3::new(self.min_x, self.max_y, self.max_z),
+            Vector3::new(self.max_x, self.max_y, self.max_z),
+        ]
+    }
+
+    // Box around the transformed box (Arvo's method), for affine matrices.
+    // Cheaper than transforming the 8 corners and gives the same result
+    pub fn transform(&self, mx: &Matrix4x4) -> BoundingBox {
+        if self.is_empty() {
+            return *self;
+        }
+
+        let src_min = [self.min_x, self.min_y, self.min_z];
+        let src_max = [self.max_x, self.max_y, self.max_z];
+        let mut min = [mx.raw[12], mx.raw[13], mx.raw[14]];
+        let mut max = min;
+
+        for i in 0..3 {
+            for j in 0..3 {
+                // Row i, column j
+                let m = mx.raw[j * 4 + i];
+                let a = m * src_min[j];
+                let b = m * src_max[j];
+                min[i] += a.min(b);
+                max[i] += a.max(b);
+            }
+        }
+
+        BoundingBox::new(min.into(), max.into())
+    }
+}
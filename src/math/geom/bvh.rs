@@ -0,0 +1,531 @@
+use crate::math::geom::bounding_box::BoundingBox;
+use crate::math::geom::ray::{MeshHit, Ray};
+use crate::math::la::vector3::Vector3;
+use crate::render::mesh::mesh::MeshData;
+
+const BINS: usize = 16;
+// Cost of visiting a node relative to testing one triangle
+const TRAVERSAL_COST: f32 = 1.0;
+const MAX_LEAF_SIZE: usize = 16;
+
+// Leaves have count > 0 and own triangles[first..first + count], inner nodes
+// have count 0 and their children at first and first + 1
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BvhNode {
+    pub bounds: BoundingBox,
+    pub first: u32,
+    pub count: u32,
+}
+
+impl BvhNode {
+    pub fn is_leaf(&self) -> bool {
+        self.count > 0
+    }
+}
+
+// Bounding volume hierarchy over triangles. Keeps its own copy of the
+// positions so queries don't need the mesh, the whole tree can be serialized
+// and cached
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "BvhData"))]
+pub struct Bvh {
+    pub nodes: Vec<BvhNode>,
+    // Triangle numbers in leaf order
+    pub triangles: Vec<u32>,
+    // Vertex indices of every triangle
+    pub corners: Vec<[u32; 3]>,
+    pub positions: Vec<Vector3>,
+}
+
+// Unchecked serialized form. A stale or broken cache must not get indices
+// into the queries that point past the lists
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct BvhData {
+    nodes: Vec<BvhNode>,
+    triangles: Vec<u32>,
+    corners: Vec<[u32; 3]>,
+    positions: Vec<Vector3>,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<BvhData> for Bvh {
+    type Error = String;
+
+    fn try_from(b: BvhData) -> Result<Bvh, String> {
+        // Triangles left out by the build may still point past the vertices
+        for t in b.triangles.iter() {
+            let corners = match b.corners.get(*t as usize) {
+                Some(c) => c,
+                None => return Err(format!("Bvh triangle {} out of {}", t, b.corners.len())),
+            };
+            if corners.iter().any(|i| *i as usize >= b.positions.len()) {
+                return Err(format!("Bvh triangle {} has corners out of range", t));
+            }
+        }
+        for (i, n) in b.nodes.iter().enumerate() {
+            let (first, count) = (n.first as usize, n.count as usize);
+            let valid = if n.is_leaf() {
+                first + count <= b.triangles.len()
+            } else {
+                // Children come after their parent, refit relies on it
+                first > i && first + 1 < b.nodes.len()
+            };
+            if !valid {
+                return Err(format!("Bvh node {} out of range", i));
+            }
+        }
+        Ok(Bvh {
+            nodes: b.nodes,
+            triangles: b.triangles,
+            corners: b.corners,
+            positions: b.positions,
+        })
+    }
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct NearestPoint {
+    pub triangle: usize,
+    pub point: Vector3,
+    pub distance: f32,
+}
+
+#[allow(dead_code)]
+impl Bvh {
+    // Triangles from index, or from consecutive vertices if there is no
+    // index, same as MeshData::raycast
+    pub fn from_mesh(mesh: &MeshData) -> Bvh {
+        let corners = if mesh.index.is_empty() {
+            (0..mesh.vertex.len() as u32 / 3)
+                .map(|t| [t * 3, t * 3 + 1, t * 3 + 2])
+                .collect()
+        } else {
+            mesh.index
+                .chunks_exact(3)
+                .map(|c| [c[0], c[1], c[2]])
+                .collect()
+        };
+        Bvh::new(mesh.vertex.clone(), corners)
+    }
+
+    // Binned surface area heuristic build
+    pub fn new(positions: Vec<Vector3>, corners: Vec<[u32; 3]>) -> Bvh {
+        let mut bvh = Bvh {
+            nodes: vec![],
+            triangles: vec![],
+            corners,
+            positions,
+        };
+        // Triangles pointing past the vertices are left out, like in
+        // MeshData::raycast
+        let count = bvh.positions.len() as u32;
+        bvh.triangles = (0..bvh.corners.len() as u32)
+            .filter(|t| bvh.corners[*t as usize].iter().all(|i| *i < count))
+            .collect();
+        if bvh.triangles.is_empty() {
+            return bvh;
+        }
+
+        let mut bounds = vec![BoundingBox::empty(); bvh.corners.len()];
+        for t in bvh.triangles.iter() {
+            bounds[*t as usize] = bvh.triangle_bounds(*t as usize);
+        }
+        let centers: Vec<Vector3> = bounds.iter().map(|b| b.center()).collect();
+
+        bvh.nodes.push(BvhNode::default());
+        bvh.build_node(0, 0, bvh.triangles.len(), &bounds, &centers);
+        bvh
+    }
+
+    fn build_node(
+        &mut self,
+        node: usize,
+        start: usize,
+        end: usize,
+        bounds: &[BoundingBox],
+        centers: &[Vector3],
+    ) {
+        let mut node_bounds = BoundingBox::empty();
+        let mut center_bounds = BoundingBox::empty();
+        for t in self.triangles[start..end].iter() {
+            node_bounds = node_bounds.union(&bounds[*t as usize]);
+            center_bounds.add_point(centers[*t as usize]);
+        }
+        let count = end - start;
+        self.nodes[node] = BvhNode {
+            bounds: node_bounds,
+            first: start as u32,
+            count: count as u32,
+        };
+        if count <= 2 {
+            return;
+        }
+
+        // Cheapest split between bins over all axes
+        let leaf_cost = count as f32;
+        let area = node_bounds.surface_area();
+        let mut best: Option<(usize, usize, f32)> = None;
+        for axis in 0..3 {
+            let (lo, hi) = (center_bounds.min()[axis], center_bounds.max()[axis]);
+            if hi <= lo {
+                continue;
+            }
+            let bin_of =
+                |c: Vector3| (((c[axis] - lo) / (hi - lo) * BINS as f32) as usize).min(BINS - 1);
+
+            let mut bin_bounds = [BoundingBox::empty(); BINS];
+            let mut bin_count = [0usize; BINS];
+            for t in self.triangles[start..end].iter() {
+                let b = bin_of(centers[*t as usize]);
+                bin_bounds[b] = bin_bounds[b].union(&bounds[*t as usize]);
+                bin_count[b] += 1;
+            }
+
+            // Right side areas from the back, then sweep from the front
+            let mut right_area = [0.0; BINS];
+            let mut right_count = [0usize; BINS];
+            let mut acc = BoundingBox::empty();
+            let mut n = 0;
+            for i in (1..BINS).rev() {
+                acc = acc.union(&bin_bounds[i]);
+                n += bin_count[i];
+                right_area[i] = acc.surface_area();
+                right_count[i] = n;
+            }
+            let mut acc = BoundingBox::empty();
+            let mut n = 0;
+            for i in 0..BINS - 1 {
+                acc = acc.union(&bin_bounds[i]);
+                n += bin_count[i];
+                if n == 0 || right_count[i + 1] == 0 {
+                    continue;
+                }
+                let cost = TRAVERSAL_COST
+                    + (acc.surface_area() * n as f32
+                        + right_area[i + 1] * right_count[i + 1] as f32)
+                        / area.max(f32::MIN_POSITIVE);
+                if best.is_none_or(|b| cost < b.2) {
+                    best = Some((axis, i + 1, cost));
+                }
+            }
+        }
+
+        let (axis, split, cost) = match best {
+            Some(b) => b,
+            None => return,
+        };
+        if cost >= leaf_cost && count <= MAX_LEAF_SIZE {
+            return;
+        }
+
+        // Partition triangles by bin
+        let (lo, hi) = (center_bounds.min()[axis], center_bounds.max()[axis]);
+        let bin_of =
+            |c: Vector3| (((c[axis] - lo) / (hi - lo) * BINS as f32) as usize).min(BINS - 1);
+        let mut mid = start;
+        for i in start..end {
+            if bin_of(centers[self.triangles[i] as usize]) < split {
+                self.triangles.swap(i, mid);
+                mid += 1;
+            }
+        }
+
+        let left = self.nodes.len();
+        self.nodes.push(BvhNode::default());
+        self.nodes.push(BvhNode::default());
+        self.nodes[node].first = left as u32;
+        self.nodes[node].count = 0;
+        self.build_node(left, start, mid, bounds, centers);
+        self.build_node(left + 1, mid, end, bounds, centers);
+    }
+
+    fn triangle(&self, t: usize) -> (Vector3, Vector3, Vector3) {
+        let c = self.corners[t];
+        (
+            self.positions[c[0] as usize],
+            self.positions[c[1] as usize],
+            self.positions[c[2] as usize],
+        )
+    }
+
+    fn triangle_bounds(&self, t: usize) -> BoundingBox {
+        let (a, b, c) = self.triangle(t);
+        BoundingBox::from_points(&[a, b, c])
+    }
+
+    pub fn bounds(&self) -> BoundingBox {
+        match self.nodes.first() {
+            Some(n) => n.bounds,
+            None => BoundingBox::empty(),
+        }
+    }
+
+    // Update the boxes for moved vertices, e.g. from
+    // MeshData::skinned_vertices. The tree shape stays, so queries get slower
+    // when the mesh deforms a lot, rebuild then
+    pub fn refit(&mut self, positions: &[Vector3]) {
+        assert_eq!(
+            positions.len(),
+            self.positions.len(),
+            "vertex count changed"
+        );
+        self.positions.copy_from_slice(positions);
+
+        // Children are always stored after their parent
+        for i in (0..self.nodes.len()).rev() {
+            let n = self.nodes[i];
+            self.nodes[i].bounds = if n.is_leaf() {
+                let (first, count) = (n.first as usize, n.count as usize);
+                self.triangles[first..first + count]
+                    .iter()
+                    .fold(BoundingBox::empty(), |b, t| {
+                        b.union(&self.triangle_bounds(*t as usize))
+                    })
+            } else {
+                let l = n.first as usize;
+                self.nodes[l].bounds.union(&self.nodes[l + 1].bounds)
+            };
+        }
+    }
+
+    // Closest hit, uv is left None, MeshData::triangle_uv fills it in
+    pub fn raycast(&self, ray: &Ray, cull_backface: bool) -> Option<MeshHit> {
+        let mut best: Option<MeshHit> = None;
+        self.traverse_ray(ray, f32::INFINITY, |t, max| {
+            let (a, b, c) = self.triangle(t);
+            match ray.intersect_triangle(a, b, c, cull_backface) {
+                Some(h) if h.hit.distance < max => {
+                    best = Some(MeshHit {
+                        hit: h.hit,
+                        triangle: t,
+                        barycentric: h.barycentric,
+                        uv: None,
+                    });
+                    Some(h.hit.distance)
+                }
+                _ => None,
+            }
+        });
+        best
+    }
+
+    // True if anything is hit closer than max_distance, stops at the first
+    // hit. For shadow and line of sight rays
+    pub fn any_hit(&self, ray: &Ray, max_distance: f32, cull_backface: bool) -> bool {
+        let mut found = false;
+        self.traverse_ray(ray, max_distance, |t, max| {
+            let (a, b, c) = self.triangle(t);
+            match ray.intersect_triangle(a, b, c, cull_backface) {
+                Some(h) if h.hit.distance < max => {
+                    found = true;
+                    // Closing the range ends the traversal
+                    Some(f32::NEG_INFINITY)
+                }
+                _ => None,
+            }
+        });
+        found
+    }
+
+    // Visit leaves along the ray near to far. test returns the distance of a
+    // hit, later nodes beyond it are skipped
+    fn traverse_ray<F: FnMut(usize, f32) -> Option<f32>>(&self, ray: &Ray, max: f32, mut test: F) {
+        if self.nodes.is_empty() {
+            return;
+        }
+        let inv = Vector3::new(
+            1.0 / ray.direction.x,
+            1.0 / ray.direction.y,
+            1.0 / ray.direction.z,
+        );
+        let mut max = max;
+        let mut stack = vec![0usize];
+
+        while let Some(i) = stack.pop() {
+            let n = &self.nodes[i];
+            match slab(ray.origin, inv, &n.bounds) {
+                Some(t) if t <= max => {}
+                _ => continue,
+            }
+
+            if n.is_leaf() {
+                let (first, count) = (n.first as usize, n.count as usize);
+                for t in self.triangles[first..first + count].iter() {
+                    if let Some(d) = test(*t as usize, max) {
+                        max = d;
+                        if max < 0.0 {
+                            return;
+                        }
+                    }
+                }
+                continue;
+            }
+
+            // Push the far child first so the near one is popped next
+            let (l, r) = (n.first as usize, n.first as usize + 1);
+            let dl = slab(ray.origin, inv, &self.nodes[l].bounds);
+            let dr = slab(ray.origin, inv, &self.nodes[r].bounds);
+            match (dl, dr) {
+                (Some(a), Some(b)) if a <= b => stack.extend([r, l]),
+                (Some(_), Some(_)) => stack.extend([l, r]),
+                (Some(_), None) => stack.push(l),
+                (None, Some(_)) => stack.push(r),
+                (None, None) => {}
+            }
+        }
+    }
+
+    // Triangles whose bounds overlap the box
+    pub fn query_box(&self, b: &BoundingBox) -> Vec<usize> {
+        let mut out = vec![];
+        if self.nodes.is_empty() {
+            return out;
+        }
+        let mut stack = vec![0usize];
+        while let Some(i) = stack.pop() {
+            let n = &self.nodes[i];
+            if !n.bounds.intersects(b) {
+                continue;
+            }
+            if n.is_leaf() {
+                let (first, count) = (n.first as usize, n.count as usize);
+                for t in self.triangles[first..first + count].iter() {
+                    if self.triangle_bounds(*t as usize).intersects(b) {
+                        out.push(*t as usize);
+                    }
+                }
+            } else {
+                stack.extend([n.first as usize, n.first as usize + 1]);
+            }
+        }
+        out
+    }
+
+    // Closest point on the surface, None for an empty tree
+    pub fn nearest_point(&self, p: Vector3) -> Option<NearestPoint> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let box_distance2 = |b: &BoundingBox| {
+            let d = b.closest_point(p) - p;
+            d.dot(d)
+        };
+        let mut best: Option<NearestPoint> = None;
+        let mut best2 = f32::INFINITY;
+        let mut stack = vec![0usize];
+
+        while let Some(i) = stack.pop() {
+            let n = &self.nodes[i];
+            if box_distance2(&n.bounds) > best2 {
+                continue;
+            }
+
+            if n.is_leaf() {
+                let (first, count) = (n.first as usize, n.count as usize);
+                for t in self.triangles[first..first + count].iter() {
+                    let (a, b, c) = self.triangle(*t as usize);
+                    let q = closest_point_on_triangle(p, a, b, c);
+                    let d2 = (q - p).dot(q - p);
+                    if d2 < best2 {
+                        best2 = d2;
+                        best = Some(NearestPoint {
+                            triangle: *t as usize,
+                            point: q,
+                            distance: d2.sqrt(),
+                        });
+                    }
+                }
+                continue;
+            }
+
+            // Closer child last so it is searched first
+            let (l, r) = (n.first as usize, n.first as usize + 1);
+            if box_distance2(&self.nodes[l].bounds) <= box_distance2(&self.nodes[r].bounds) {
+                stack.extend([r, l]);
+            } else {
+                stack.extend([l, r]);
+            }
+        }
+        best
+    }
+}
+
+// Entry distance of the ray into the box, 0 if the origin is inside
+fn slab(origin: Vector3, inv: Vector3, b: &BoundingBox) -> Option<f32> {
+    let (min, max) = (b.min(), b.max());
+    let mut t_near = 0.0f32;
+    let mut t_far = f32::INFINITY;
+    for i in 0..3 {
+        // Parallel to the slab, same as Ray::intersect_box. An origin on a
+        // face would give 0 * inf = NaN below
+        if inv[i].is_infinite() {
+            if origin[i] < min[i] || origin[i] > max[i] {
+                return None;
+            }
+            continue;
+        }
+        let t0 = (min[i] - origin[i]) * inv[i];
+        let t1 = (max[i] - origin[i]) * inv[i];
+        let (lo, hi) = if t0 < t1 { (t0, t1) } else { (t1, t0) };
+        if lo > t_near {
+            t_near = lo;
+        }
+        if hi < t_far {
+            t_far = hi;
+        }
+    }
+    if t_near <= t_far {
+        Some(t_near)
+    } else {
+        None
+    }
+}
+
+// Closest point on triangle abc to p (Ericson, Real-Time Collision Detection)
+pub fn closest_point_on_triangle(p: Vector3, a: Vector3, b: Vector3, c: Vector3) -> Vector3 {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+
+    let bp = p - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+
+    let cp = p - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && (d4 - d3) >= 0.0 && (d5 - d6) >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+
+    // Inside the face
+    let denom = 1.0 / (va + vb + vc);
+    a + ab * (vb * denom) + ac * (vc * denom)
+}
@@ -0,0 +1,113 @@
+use crate::math::geom::bounding_box::BoundingBox;
+use crate::math::geom::plane::Plane;
+use crate::math::geom::sphere::Sphere;
+use crate::math::la::matrix4::{ClipDepth, Matrix4x4};
+use crate::math::la::vector3::Vector3;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Intersection {
+    Outside,
+    Intersecting,
+    Inside,
+}
+
+// Six planes with normals pointing inside, in the order left, right, bottom,
+// top, near, far
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Frustum {
+    pub planes: [Plane; 6],
+}
+
+#[allow(dead_code)]
+impl Frustum {
+    // Planes of projection * view (Gribb and Hartmann), clip_depth must match
+    // the one the projection was built with
+    pub fn from_matrix4x4(mx: &Matrix4x4, clip_depth: ClipDepth) -> Frustum {
+        let m = mx.raw;
+        let row = |i: usize| [m[i], m[4 + i], m[8 + i], m[12 + i]];
+        let (r0, r1, r2, r3) = (row(0), row(1), row(2), row(3));
+        let plane = |a: [f32; 4], b: [f32; 4], s: f32| {
+            Plane::new(
+                Vector3::new(a[0] + s * b[0], a[1] + s * b[1], a[2] + s * b[2]),
+                a[3] + s * b[3],
+            )
+        };
+
+        let near = match clip_depth {
+            ClipDepth::NegativeOneToOne => plane(r3, r2, 1.0),
+            ClipDepth::ZeroToOne => plane(r2, r2, 0.0),
+        };
+        Frustum {
+            planes: [
+                plane(r3, r0, 1.0),
+                plane(r3, r0, -1.0),
+                plane(r3, r1, 1.0),
+                plane(r3, r1, -1.0),
+                near,
+                plane(r3, r2, -1.0),
+            ],
+        }
+    }
+
+    pub fn contains_point(&self, p: Vector3) -> bool {
+        self.planes
+            .iter()
+            .all(|plane| plane.signed_distance(p) >= 0.0)
+    }
+
+    pub fn test_sphere(&self, sphere: &Sphere) -> Intersection {
+        if sphere.is_empty() {
+            return Intersection::Outside;
+        }
+        let mut out = Intersection::Inside;
+        for plane in self.planes.iter() {
+            let d = plane.signed_distance(sphere.center);
+            if d < -sphere.radius {
+                return Intersection::Outside;
+            }
+            if d < sphere.radius {
+                out = Intersection::Intersecting;
+            }
+        }
+        out
+    }
+
+    // Conservative, boxes outside near an edge of the frustum can be reported
+    // as intersecting but visible boxes are never outside
+    pub fn test_box(&self, b: &BoundingBox) -> Intersection {
+        if b.is_empty() {
+            return Intersection::Outside;
+        }
+        let mut out = Intersection::Inside;
+        for plane in self.planes.iter() {
+            // Corners farthest along and against the normal
+            let n = plane.normal;
+            let positive = Vector3::new(
+                if n.x >= 0.0 { b.max_x } else { b.min_x },
+                if n.y >= 0.0 { b.max_y } else { b.min_y },
+                if n.z >= 0.0 { b.max_z } else { b.min_z },
+            );
+            let negative = Vector3::new(
+                if n.x >= 0.0 { b.min_x } else { b.max_x },
+                if n.y >= 0.0 { b.min_y } else { b.max_y },
+                if n.z >= 0.0 { b.min_z } else { b.max_z },
+            );
+            if plane.signed_distance(positive) < 0.0 {
+                return Intersection::Outside;
+            }
+            if plane.signed_distance(negative) < 0.0 {
+                out = Intersection::Intersecting;
+            }
+        }
+        out
+    }
+
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        self.test_sphere(sphere) != Intersection::Outside
+    }
+
+    pub fn intersects_box(&self, b: &BoundingBox) -> bool {
+        self.test_box(b) != Intersection::Outside
+    }
+}
@@ -0,0 +1,84 @@
+use crate::math::geom::bounding_box::BoundingBox;
+use crate::math::la::vector3::Vector3;
+use std::collections::HashSet;
+
+// Convex hull as triangles of point indices, wound counter clockwise seen from
+// outside. None if the points are flat or fewer than 4. Incremental, fine for
+// tool sized inputs of a few thousand points
+pub fn convex_hull(points: &[Vector3]) -> Option<Vec<[usize; 3]>> {
+    if points.len() < 4 {
+        return None;
+    }
+
+    let size = BoundingBox::from_points(points).size();
+    let eps = size.x.max(size.y).max(size.z) * 1e-5;
+    if eps == 0.0 {
+        return None;
+    }
+
+    // Start with a big tetrahedron
+    let farthest = |d: &dyn Fn(Vector3) -> f32| {
+        let mut best = 0;
+        for i in 1..points.len() {
+            if d(points[i]) > d(points[best]) {
+                best = i;
+            }
+        }
+        best
+    };
+    let i0 = farthest(&|p| -p.x);
+    let i1 = farthest(&|p| p.distance_to(points[i0]));
+    let line = (points[i1] - points[i0]).normalize();
+    let i2 = farthest(&|p| (p - points[i0]).cross(line).length());
+    let n = (points[i1] - points[i0])
+        .cross(points[i2] - points[i0])
+        .normalize();
+    let i3 = farthest(&|p| (p - points[i0]).dot(n).abs());
+    if (points[i2] - points[i0]).cross(line).length() <= eps
+        || (points[i3] - points[i0]).dot(n).abs() <= eps
+    {
+        return None;
+    }
+
+    let mut faces = vec![[i0, i1, i2], [i0, i2, i3], [i0, i3, i1], [i1, i3, i2]];
+    // Facing away from the fourth point means facing out
+    if (points[i3] - points[i0]).dot(n) > 0.0 {
+        for f in faces.iter_mut() {
+            f.swap(1, 2);
+        }
+    }
+
+    let normal = |f: &[usize; 3]| {
+        (points[f[1]] - points[f[0]])
+            .cross(points[f[2]] - points[f[0]])
+            .normalize()
+    };
+
+    for (i, p) in points.iter().enumerate() {
+        if i == i0 || i == i1 || i == i2 || i == i3 {
+            continue;
+        }
+
+        let (visible, kept): (Vec<[usize; 3]>, Vec<[usize; 3]>) = faces
+            .iter()
+            .partition(|f| normal(f).dot(*p - points[f[0]]) > eps);
+        if visible.is_empty() {
+            continue;
+        }
+
+        // Edges of the visible region without a visible twin form the
+        // horizon, connect each one to the new point
+        let edges: HashSet<(usize, usize)> = visible
+            .iter()
+            .flat_map(|f| [(f[0], f[1]), (f[1], f[2]), (f[2], f[0])])
+            .collect();
+        faces = kept;
+        for (a, b) in edges.iter() {
+            if !edges.contains(&(*b, *a)) {
+                faces.push([*a, *b, i]);
+            }
+        }
+    }
+
+    Some(faces)
+}
@@ -1,2 +1,10 @@
 pub mod bounding_box;
+pub mod bvh;
+pub mod frustum;
+pub mod hull;
 pub mod line;
+pub mod octree;
+pub mod oriented_box;
+pub mod plane;
+pub mod ray;
+pub mod sphere;
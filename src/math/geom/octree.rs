@@ -0,0 +1,394 @@
+use crate::math::geom::bounding_box::BoundingBox;
+use crate::math::geom::frustum::{Frustum, Intersection};
+use crate::math::geom::ray::Ray;
+use crate::math::geom::sphere::Sphere;
+use crate::math::la::vector3::Vector3;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// Nodes hold objects up to this times their own size, centered anywhere in
+// the node, so an object never has to sit higher than its size needs
+const LOOSENESS: f32 = 2.0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Bounds {
+    Box(BoundingBox),
+    Sphere(Sphere),
+}
+
+impl From<BoundingBox> for Bounds {
+    fn from(b: BoundingBox) -> Bounds {
+        Bounds::Box(b)
+    }
+}
+
+impl From<Sphere> for Bounds {
+    fn from(s: Sphere) -> Bounds {
+        Bounds::Sphere(s)
+    }
+}
+
+#[allow(dead_code)]
+impl Bounds {
+    pub fn to_bounding_box(&self) -> BoundingBox {
+        match self {
+            Bounds::Box(b) => *b,
+            Bounds::Sphere(s) => BoundingBox::from_center_extents(
+                s.center,
+                Vector3::new(s.radius, s.radius, s.radius),
+            ),
+        }
+    }
+
+    pub fn intersects_box(&self, other: &BoundingBox) -> bool {
+        match self {
+            Bounds::Box(b) => b.intersects(other),
+            Bounds::Sphere(s) => sphere_intersects_box(s, other),
+        }
+    }
+
+    pub fn intersects_sphere(&self, other: &Sphere) -> bool {
+        match self {
+            Bounds::Box(b) => sphere_intersects_box(other, b),
+            Bounds::Sphere(s) => s.intersects(other),
+        }
+    }
+
+    pub fn intersects_frustum(&self, frustum: &Frustum) -> bool {
+        match self {
+            Bounds::Box(b) => frustum.intersects_box(b),
+            Bounds::Sphere(s) => frustum.intersects_sphere(s),
+        }
+    }
+
+    // Distance along the ray to the first hit, 0 when the origin is inside
+    pub fn ray_distance(&self, ray: &Ray) -> Option<f32> {
+        match self {
+            Bounds::Box(b) if b.contains_point(ray.origin) => Some(0.0),
+            Bounds::Box(b) => ray.intersect_box(b).map(|h| h.distance),
+            Bounds::Sphere(s) if s.contains_point(ray.origin) => Some(0.0),
+            Bounds::Sphere(s) => ray.intersect_sphere(s).map(|h| h.distance),
+        }
+    }
+}
+
+fn sphere_intersects_box(s: &Sphere, b: &BoundingBox) -> bool {
+    if s.is_empty() || b.is_empty() {
+        return false;
+    }
+    let d = b.closest_point(s.center) - s.center;
+    d.dot(d) <= s.radius * s.radius
+}
+
+#[derive(Clone, Debug)]
+struct OctreeNode<K> {
+    center: Vector3,
+    half: Vector3,
+    parent: u32,
+    // Index of the first of 8 children, 0 for none since the root is no child
+    children: u32,
+    items: Vec<K>,
+}
+
+impl<K> OctreeNode<K> {
+    fn loose_bounds(&self) -> BoundingBox {
+        BoundingBox::from_center_extents(self.center, self.half * LOOSENESS)
+    }
+
+    fn is_empty_leaf(&self) -> bool {
+        self.children == 0 && self.items.is_empty()
+    }
+}
+
+// Loose octree over objects keyed by id. Objects centered outside the world
+// bounds are kept in the root, which is always searched. Children that run
+// empty are pruned on remove and move, their slots are reused
+#[derive(Clone, Debug)]
+pub struct Octree<K = u32> {
+    nodes: Vec<OctreeNode<K>>,
+    // First index of pruned groups of 8 nodes
+    free: Vec<u32>,
+    entries: HashMap<K, (Bounds, u32)>,
+    max_depth: u32,
+}
+
+#[allow(dead_code)]
+impl<K: Copy + Eq + Hash> Octree<K> {
+    pub fn new(world: BoundingBox, max_depth: u32) -> Octree<K> {
+        Octree {
+            nodes: vec![OctreeNode {
+                center: world.center(),
+                half: world.extents(),
+                parent: 0,
+                children: 0,
+                items: vec![],
+            }],
+            free: vec![],
+            entries: HashMap::new(),
+            max_depth,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    // Nodes in use, including the root
+    pub fn node_count(&self) -> usize {
+        self.nodes.len() - self.free.len() * 8
+    }
+
+    pub fn contains(&self, key: K) -> bool {
+        self.entries.contains_key(&key)
+    }
+
+    pub fn get(&self, key: K) -> Option<Bounds> {
+        self.entries.get(&key).map(|e| e.0)
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.truncate(1);
+        self.nodes[0].children = 0;
+        self.nodes[0].items.clear();
+        self.free.clear();
+        self.entries.clear();
+    }
+
+    // Returns the previous bounds if the key was already in the tree
+    pub fn insert<B: Into<Bounds>>(&mut self, key: K, bounds: B) -> Option<Bounds> {
+        let previous = self.remove(key);
+        let bounds = bounds.into();
+        let node = self.target(&bounds.to_bounding_box());
+        self.nodes[node].items.push(key);
+        self.entries.insert(key, (bounds, node as u32));
+        previous
+    }
+
+    pub fn remove(&mut self, key: K) -> Option<Bounds> {
+        let (bounds, node) = self.entries.remove(&key)?;
+        let items = &mut self.nodes[node as usize].items;
+        if let Some(i) = items.iter().position(|k| *k == key) {
+            items.swap_remove(i);
+        }
+        self.prune(node as usize);
+        Some(bounds)
+    }
+
+    // Move an object, cheap while it stays in the same node. False if the key
+    // is not in the tree
+    pub fn update<B: Into<Bounds>>(&mut self, key: K, bounds: B) -> bool {
+        let current = match self.entries.get(&key) {
+            Some(e) => e.1,
+            None => return false,
+        };
+        let bounds = bounds.into();
+        if self.find_target(&bounds.to_bounding_box()) == Some(current as usize) {
+            self.entries.insert(key, (bounds, current));
+        } else {
+            self.insert(key, bounds);
+        }
+        true
+    }
+
+    // Octant of the child the box goes into, None if it stays in node. The
+    // child's loose bounds hold every box up to its own size centered in it
+    fn child_octant(&self, node: usize, b: &BoundingBox) -> Option<u32> {
+        let n = &self.nodes[node];
+        let (c, e) = (b.center(), b.extents());
+        let half = n.half * 0.5;
+        let fits = e.x <= half.x * (LOOSENESS - 1.0)
+            && e.y <= half.y * (LOOSENESS - 1.0)
+            && e.z <= half.z * (LOOSENESS - 1.0);
+        let inside = BoundingBox::from_center_extents(n.center, n.half).contains_point(c);
+        if !fits || !inside {
+            return None;
+        }
+        Some(
+            (c.x >= n.center.x) as u32
+                | ((c.y >= n.center.y) as u32) << 1
+                | ((c.z >= n.center.z) as u32) << 2,
+        )
+    }
+
+    // Node the box belongs in, None if that needs new children
+    fn find_target(&self, b: &BoundingBox) -> Option<usize> {
+        let mut node = 0;
+        for _ in 0..self.max_depth {
+            let octant = match self.child_octant(node, b) {
+                Some(o) => o,
+                None => break,
+            };
+            match self.nodes[node].children {
+                0 => return None,
+                first => node = (first + octant) as usize,
+            }
+        }
+        Some(node)
+    }
+
+    // Same as find_target but creates the missing children
+    fn target(&mut self, b: &BoundingBox) -> usize {
+        let mut node = 0;
+        for _ in 0..self.max_depth {
+            let octant = match self.child_octant(node, b) {
+                Some(o) => o,
+                None => break,
+            };
+            if self.nodes[node].children == 0 {
+                self.split(node);
+            }
+            node = (self.nodes[node].children + octant) as usize;
+        }
+        node
+    }
+
+    fn split(&mut self, node: usize) {
+        let (center, half) = (self.nodes[node].center, self.nodes[node].half * 0.5);
+        let first = match self.free.pop() {
+            Some(first) => first,
+            None => {
+                let first = self.nodes.len() as u32;
+                self.nodes.resize_with(self.nodes.len() + 8, || OctreeNode {
+                    center,
+                    half,
+                    parent: 0,
+                    children: 0,
+                    items: vec![],
+                });
+                first
+            }
+        };
+        for octant in 0..8 {
+            let sign = |bit: usize| if octant & bit != 0 { 1.0 } else { -1.0 };
+            let child = &mut self.nodes[first as usize + octant];
+            child.center = center + Vector3::new(sign(1), sign(2), sign(4)) * half;
+            child.half = half;
+            child.parent = node as u32;
+        }
+        self.nodes[node].children = first;
+    }
+
+    // Free groups of 8 empty leaves, walking up from node
+    fn prune(&mut self, mut node: usize) {
+        while node != 0 {
+            let parent = self.nodes[node].parent as usize;
+            let first = self.nodes[parent].children as usize;
+            if !self.nodes[first..first + 8]
+                .iter()
+                .all(|n| n.is_empty_leaf())
+            {
+                break;
+            }
+            self.nodes[parent].children = 0;
+            self.free.push(first as u32);
+            node = parent;
+        }
+    }
+
+    // Walk nodes by their loose bounds. Everything below an Inside node is
+    // taken without testing the objects
+    fn collect<N, I>(&self, node_test: N, item_test: I) -> Vec<K>
+    where
+        N: Fn(&BoundingBox) -> Intersection,
+        I: Fn(&Bounds) -> bool,
+    {
+        let mut out = vec![];
+        let mut stack = vec![(0usize, false)];
+        while let Some((i, inside)) = stack.pop() {
+            let n = &self.nodes[i];
+            let inside = inside
+                || match i {
+                    0 => false,
+                    _ => match node_test(&n.loose_bounds()) {
+                        Intersection::Outside => continue,
+                        Intersection::Intersecting => false,
+                        Intersection::Inside => true,
+                    },
+                };
+
+            for key in n.items.iter() {
+                if inside || item_test(&self.entries[key].0) {
+                    out.push(*key);
+                }
+            }
+            if n.children != 0 {
+                stack.extend((0..8).map(|c| ((n.children + c) as usize, inside)));
+            }
+        }
+        out
+    }
+
+    pub fn query_box(&self, b: &BoundingBox) -> Vec<K> {
+        self.collect(
+            |node| {
+                if b.contains(node) {
+                    Intersection::Inside
+                } else if b.intersects(node) {
+                    Intersection::Intersecting
+                } else {
+                    Intersection::Outside
+                }
+            },
+            |bounds| bounds.intersects_box(b),
+        )
+    }
+
+    pub fn query_sphere(&self, s: &Sphere) -> Vec<K> {
+        self.collect(
+            |node| {
+                if !sphere_intersects_box(s, node) {
+                    Intersection::Outside
+                } else if node.corners().iter().all(|p| s.contains_point(*p)) {
+                    Intersection::Inside
+                } else {
+                    Intersection::Intersecting
+                }
+            },
+            |bounds| bounds.intersects_sphere(s),
+        )
+    }
+
+    pub fn query_frustum(&self, frustum: &Frustum) -> Vec<K> {
+        self.collect(
+            |node| frustum.test_box(node),
+            |bounds| bounds.intersects_frustum(frustum),
+        )
+    }
+
+    // Objects hit within max_distance, nearest first, see
+    // Bounds::ray_distance
+    pub fn query_ray(&self, ray: &Ray, max_distance: f32) -> Vec<(K, f32)> {
+        let mut out = vec![];
+        let mut stack = vec![0usize];
+        while let Some(i) = stack.pop() {
+            let n = &self.nodes[i];
+            if i != 0 {
+                let loose = n.loose_bounds();
+                let reached = loose.contains_point(ray.origin)
+                    || ray
+                        .intersect_box(&loose)
+                        .is_some_and(|h| h.distance <= max_distance);
+                if !reached {
+                    continue;
+                }
+            }
+
+            for key in n.items.iter() {
+                match self.entries[key].0.ray_distance(ray) {
+                    Some(d) if d <= max_distance => out.push((*key, d)),
+                    _ => {}
+                }
+            }
+            if n.children != 0 {
+                stack.extend((0..8).map(|c| (n.children + c) as usize));
+            }
+        }
+        out.sort_by(|a, b| a.1.total_cmp(&b.1));
+        out
+    }
+}
@@ -0,0 +1,224 @@
+use crate::math::geom::bounding_box::BoundingBox;
+use crate::math::geom::hull::convex_hull;
+use crate::math::la::matrix3::Matrix3x3;
+use crate::math::la::matrix4::Matrix4x4;
+use crate::math::la::vector3::Vector3;
+
+// Box rotated into its own frame. Columns of axes are unit length and
+// orthogonal, extents are the half sizes along them
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrientedBox {
+    pub center: Vector3,
+    pub axes: Matrix3x3,
+    pub extents: Vector3,
+}
+
+#[allow(dead_code)]
+impl OrientedBox {
+    pub const fn new(center: Vector3, axes: Matrix3x3, extents: Vector3) -> OrientedBox {
+        OrientedBox {
+            center,
+            axes,
+            extents,
+        }
+    }
+
+    pub fn from_bounding_box(b: &BoundingBox) -> OrientedBox {
+        OrientedBox::new(b.center(), Matrix3x3::new(), b.extents())
+    }
+
+    // Axes from the principal components of the points. Dense areas pull the
+    // axes towards them, from_points_hull doesn't have that problem
+    pub fn from_points(points: &[Vector3]) -> OrientedBox {
+        let (_, axes) = Matrix3x3::covariance(points).symmetric_eigen();
+        OrientedBox::fit(points, axes)
+    }
+
+    // Principal components of the convex hull surface, so only the shape
+    // counts and not how the vertices are spread. Flat point sets use
+    // from_points
+    pub fn from_points_hull(points: &[Vector3]) -> OrientedBox {
+        let faces = match convex_hull(points) {
+            Some(faces) => faces,
+            None => return OrientedBox::from_points(points),
+        };
+
+        // Area weighted second moments of the triangles
+        let mut area = 0.0;
+        let mut mean = Vector3::zero();
+        let mut moment = [[0.0f32; 3]; 3];
+        for f in faces.iter() {
+            let (p, q, r) = (points[f[0]], points[f[1]], points[f[2]]);
+            let a = (q - p).cross(r - p).length() * 0.5;
+            let m = (p + q + r) / 3.0;
+            area += a;
+            mean += m * a;
+            for (i, row) in moment.iter_mut().enumerate() {
+                for (j, v) in row.iter_mut().enumerate() {
+                    *v += a / 12.0 * (9.0 * m[i] * m[j] + p[i] * p[j] + q[i] * q[j] + r[i] * r[j]);
+                }
+            }
+        }
+        if area == 0.0 {
+            return OrientedBox::from_points(points);
+        }
+
+        mean /= area;
+        let mut cov = Matrix3x3::new();
+        for i in 0..3 {
+            for j in 0..3 {
+                cov.raw[j * 3 + i] = moment[i][j] / area - mean[i] * mean[j];
+            }
+        }
+        let (_, axes) = cov.symmetric_eigen();
+        OrientedBox::fit(points, axes)
+    }
+
+    // Tightest box with the given axes
+    fn fit(points: &[Vector3], axes: Matrix3x3) -> OrientedBox {
+        if points.is_empty() {
+            return OrientedBox::new(Vector3::zero(), axes, Vector3::zero());
+        }
+
+        let local: Vec<Vector3> = points.iter().map(|p| *p * axes.transpose()).collect();
+        let b = BoundingBox::from_points(&local);
+        OrientedBox::new(b.center() * axes, axes, b.extents())
+    }
+
+    pub fn axis(&self, i: usize) -> Vector3 {
+        self.axes.column(i)
+    }
+
+    pub fn volume(&self) -> f32 {
+        8.0 * self.extents.x * self.extents.y * self.extents.z
+    }
+
+    // Point in the box frame, origin at the center
+    pub fn to_local(&self, p: Vector3) -> Vector3 {
+        (p - self.center) * self.axes.transpose()
+    }
+
+    // Points on the surface are inside
+    pub fn contains_point(&self, p: Vector3) -> bool {
+        let l = self.to_local(p);
+        let eps = 1e-5 * self.extents.x.max(self.extents.y).max(self.extents.z);
+        l.x.abs() <= self.extents.x + eps
+            && l.y.abs() <= self.extents.y + eps
+            && l.z.abs() <= self.extents.z + eps
+    }
+
+    pub fn closest_point(&self, p: Vector3) -> Vector3 {
+        let l = self.to_local(p).clamp(-self.extents, self.extents);
+        self.center + l * self.axes
+    }
+
+    pub fn corners(&self) -> [Vector3; 8] {
+        let (x, y, z) = (
+            self.axis(0) * self.extents.x,
+            self.axis(1) * self.extents.y,
+            self.axis(2) * self.extents.z,
+        );
+        let c = self.center;
+        [
+            c - x - y - z,
+            c + x - y - z,
+            c - x + y - z,
+            c + x + y - z,
+            c - x - y + z,
+            c + x - y + z,
+            c - x + y + z,
+            c + x + y + z,
+        ]
+    }
+
+    // Axis aligned box around this one
+    pub fn to_bounding_box(&self) -> BoundingBox {
+        BoundingBox::from_points(&self.corners())
+    }
+
+    // Separating axis test on the 15 candidate axes. Touching boxes intersect
+    pub fn intersects(&self, other: &OrientedBox) -> bool {
+        let a = [self.axis(0), self.axis(1), self.axis(2)];
+        let b = [other.axis(0), other.axis(1), other.axis(2)];
+        let ea = self.extents;
+        let eb = other.extents;
+
+        // b's axes in a's frame, the epsilon keeps parallel edges from giving
+        // a zero cross product axis that separates by rounding
+        let mut r = [[0.0f32; 3]; 3];
+        let mut abs_r = [[0.0f32; 3]; 3];
+        for i in 0..3 {
+            for j in 0..3 {
+                r[i][j] = a[i].dot(b[j]);
+                abs_r[i][j] = r[i][j].abs() + 1e-6;
+            }
+        }
+        let d = other.center - self.center;
+        let t = [d.dot(a[0]), d.dot(a[1]), d.dot(a[2])];
+
+        for i in 0..3 {
+            let rb = eb[0] * abs_r[i][0] + eb[1] * abs_r[i][1] + eb[2] * abs_r[i][2];
+            if t[i].abs() > ea[i] + rb {
+                return false;
+            }
+        }
+
+        for j in 0..3 {
+            let ra = ea[0] * abs_r[0][j] + ea[1] * abs_r[1][j] + ea[2] * abs_r[2][j];
+            let tj = t[0] * r[0][j] + t[1] * r[1][j] + t[2] * r[2][j];
+            if tj.abs() > ra + eb[j] {
+                return false;
+            }
+        }
+
+        // Cross products of edge pairs, a[i] x b[j]
+        for i in 0..3 {
+            let (i1, i2) = ((i + 1) % 3, (i + 2) % 3);
+            for j in 0..3 {
+                let (j1, j2) = ((j + 1) % 3, (j + 2) % 3);
+                let ra = ea[i1] * abs_r[i2][j] + ea[i2] * abs_r[i1][j];
+                let rb = eb[j1] * abs_r[i][j2] + eb[j2] * abs_r[i][j1];
+                let tl = t[i2] * r[i1][j] - t[i1] * r[i2][j];
+                if tl.abs() > ra + rb {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+
+    pub fn intersects_box(&self, other: &BoundingBox) -> bool {
+        !other.is_empty() && self.intersects(&OrientedBox::from_bounding_box(other))
+    }
+
+    // Box around the transformed box. Exact for rotation, translation and
+    // uniform scale, otherwise the box grows to hold the sheared shape
+    pub fn transform(&self, mx: &Matrix4x4) -> OrientedBox {
+        let m = Matrix3x3::from_matrix4x4(*mx);
+        let edges = [
+            self.axis(0) * self.extents.x * m,
+            self.axis(1) * self.extents.y * m,
+            self.axis(2) * self.extents.z * m,
+        ];
+
+        // Orthonormal frame that follows the first two edges. A matrix that
+        // flattens them gets an axis aligned box around the corners instead
+        let x = edges[0].normalize();
+        let y = (edges[1] - x * edges[1].dot(x)).normalize();
+        if x.length() == 0.0 || y.length() == 0.0 {
+            let corners = self.corners().map(|c| c * *mx);
+            return OrientedBox::from_bounding_box(&BoundingBox::from_points(&corners));
+        }
+        let z = x.cross(y);
+
+        let axes = Matrix3x3::from_columns(x, y, z);
+        let extent = |axis: Vector3| edges.iter().map(|e| e.dot(axis).abs()).sum::<f32>();
+        OrientedBox::new(
+            self.center * *mx,
+            axes,
+            Vector3::new(extent(x), extent(y), extent(z)),
+        )
+    }
+}
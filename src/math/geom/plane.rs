@@ -0,0 +1,60 @@
+use crate::math::geom::ray::{Ray, RayHit};
+use crate::math::la::vector3::Vector3;
+
+// Points p with normal.dot(p) + d = 0, normal is unit length and points to
+// the positive side
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Plane {
+    pub normal: Vector3,
+    pub d: f32,
+}
+
+#[allow(dead_code)]
+impl Plane {
+    // Normal doesn't need to be unit length, both parts are scaled
+    pub fn new(normal: Vector3, d: f32) -> Plane {
+        let l = normal.length();
+        if l == 0.0 {
+            return Plane { normal, d };
+        }
+        Plane {
+            normal: normal / l,
+            d: d / l,
+        }
+    }
+
+    pub fn from_point_normal(point: Vector3, normal: Vector3) -> Plane {
+        let n = normal.normalize();
+        Plane {
+            normal: n,
+            d: -n.dot(point),
+        }
+    }
+
+    // Counter clockwise points look at the positive side
+    pub fn from_points(a: Vector3, b: Vector3, c: Vector3) -> Plane {
+        Plane::from_point_normal(a, (b - a).cross(c - a))
+    }
+
+    // Positive in front of the plane
+    pub fn signed_distance(&self, p: Vector3) -> f32 {
+        self.normal.dot(p) + self.d
+    }
+
+    // Closest point on the plane
+    pub fn project_point(&self, p: Vector3) -> Vector3 {
+        p - self.normal * self.signed_distance(p)
+    }
+
+    pub fn flip(&self) -> Plane {
+        Plane {
+            normal: -self.normal,
+            d: -self.d,
+        }
+    }
+
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<RayHit> {
+        ray.intersect_plane(self.normal, self.d)
+    }
+}
@@ -0,0 +1,250 @@
+use crate::math::geom::bounding_box::BoundingBox;
+use crate::math::geom::sphere::Sphere;
+use crate::math::la::matrix4::{ClipDepth, Matrix4x4};
+use crate::math::la::vector2::Vector2;
+use crate::math::la::vector3::Vector3;
+use crate::math::la::vector4::Vector4;
+
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Ray {
+    pub origin: Vector3,
+    // Unit length, distances of hits are in world units
+    pub direction: Vector3,
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct RayHit {
+    pub distance: f32,
+    pub point: Vector3,
+    pub normal: Vector3,
+}
+
+// Barycentric weights of the triangle corners a, b and c
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct TriangleHit {
+    pub hit: RayHit,
+    pub barycentric: Vector3,
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct MeshHit {
+    pub hit: RayHit,
+    // Index of the triangle, its corners are index[triangle * 3..]
+    pub triangle: usize,
+    pub barycentric: Vector3,
+    // Interpolated from uv0, None if the mesh has no uvs
+    pub uv: Option<Vector2>,
+}
+
+#[allow(dead_code)]
+impl Ray {
+    // Direction gets normalized
+    pub fn new(origin: Vector3, direction: Vector3) -> Ray {
+        Ray {
+            origin,
+            direction: direction.normalize(),
+        }
+    }
+
+    pub fn from_points(from: Vector3, to: Vector3) -> Ray {
+        Ray::new(from, to - from)
+    }
+
+    // Ray through a point in normalized device coordinates (-1..1, +Y up),
+    // inverse_view_projection is (projection * view).invert(). Works for
+    // standard, reversed and infinite projections
+    pub fn from_ndc(
+        ndc: Vector2,
+        inverse_view_projection: &Matrix4x4,
+        clip_depth: ClipDepth,
+    ) -> Ray {
+        let m = &inverse_view_projection.raw;
+        let unproject = |z: f32| {
+            let v = [ndc.x, ndc.y, z, 1.0];
+            let row = |r: usize| (0..4).map(|c| m[c * 4 + r] * v[c]).sum::<f32>();
+            Vector4::new(row(0), row(1), row(2), row(3))
+        };
+        let low_z = match clip_depth {
+            ClipDepth::NegativeOneToOne => -1.0,
+            ClipDepth::ZeroToOne => 0.0,
+        };
+
+        // View depth is 1 / w, so the end with the larger w is the near
+        // plane, at 1 for reversed projections. Orthographic w is 1 at every
+        // depth and keeps the standard order
+        let (mut near, mut far) = (unproject(low_z), unproject(1.0));
+        if far.w > near.w * 1.001 {
+            std::mem::swap(&mut near, &mut far);
+        }
+
+        // The far end stays homogeneous, w is 0 for a far plane at infinity
+        let origin = Vector3::new(near.x, near.y, near.z) / near.w;
+        let far_xyz = Vector3::new(far.x, far.y, far.z);
+        Ray::new(origin, far_xyz - origin * far.w)
+    }
+
+    pub fn at(&self, distance: f32) -> Vector3 {
+        self.origin + self.direction * distance
+    }
+
+    pub fn transform(&self, mx: &Matrix4x4) -> Ray {
+        Ray::from_points(self.origin * *mx, self.at(1.0) * *mx)
+    }
+
+    // Plane of points p with normal.dot(p) + d = 0. The normal of the hit
+    // faces the ray
+    pub fn intersect_plane(&self, normal: Vector3, d: f32) -> Option<RayHit> {
+        let denom = normal.dot(self.direction);
+        if denom.abs() < 1e-8 {
+            return None;
+        }
+        let t = -(normal.dot(self.origin) + d) / denom;
+        if t < 0.0 {
+            return None;
+        }
+        let n = normal.normalize();
+        Some(RayHit {
+            distance: t,
+            point: self.at(t),
+            normal: if denom > 0.0 { -n } else { n },
+        })
+    }
+
+    // First hit in front of the origin, the exit point when the origin is
+    // inside. Normal points out of the sphere
+    pub fn intersect_sphere(&self, sphere: &Sphere) -> Option<RayHit> {
+        if sphere.is_empty() {
+            return None;
+        }
+        let oc = self.origin - sphere.center;
+        let b = oc.dot(self.direction);
+        let c = oc.dot(oc) - sphere.radius * sphere.radius;
+        let disc = b * b - c;
+        if disc < 0.0 {
+            return None;
+        }
+
+        let s = disc.sqrt();
+        let t = if -b - s >= 0.0 { -b - s } else { -b + s };
+        if t < 0.0 {
+            return None;
+        }
+        let point = self.at(t);
+        let normal = if sphere.radius > 0.0 {
+            (point - sphere.center) / sphere.radius
+        } else {
+            -self.direction
+        };
+        Some(RayHit {
+            distance: t,
+            point,
+            normal,
+        })
+    }
+
+    // Slab method. First hit in front of the origin, the exit point when the
+    // origin is inside. Normal is the outward normal of the face hit
+    pub fn intersect_box(&self, b: &BoundingBox) -> Option<RayHit> {
+        if b.is_empty() {
+            return None;
+        }
+
+        let (min, max) = (b.min(), b.max());
+        let mut t_near = f32::NEG_INFINITY;
+        let mut t_far = f32::INFINITY;
+        let (mut near_axis, mut far_axis) = (0, 0);
+
+        for i in 0..3 {
+            let o = self.origin[i];
+            let d = self.direction[i];
+            if d == 0.0 {
+                // Parallel to the slab, must be between its sides
+                if o < min[i] || o > max[i] {
+                    return None;
+                }
+                continue;
+            }
+            let mut t0 = (min[i] - o) / d;
+            let mut t1 = (max[i] - o) / d;
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+            if t0 > t_near {
+                t_near = t0;
+                near_axis = i;
+            }
+            if t1 < t_far {
+                t_far = t1;
+                far_axis = i;
+            }
+            if t_near > t_far || t_far < 0.0 {
+                return None;
+            }
+        }
+
+        let (t, axis, entering) = if t_near >= 0.0 {
+            (t_near, near_axis, true)
+        } else {
+            (t_far, far_axis, false)
+        };
+
+        // Entering faces look against the ray, exit faces along it
+        let mut normal = Vector3::zero();
+        let d = self.direction[axis];
+        normal[axis] = if (d > 0.0) == entering { -1.0 } else { 1.0 };
+        Some(RayHit {
+            distance: t,
+            point: self.at(t),
+            normal,
+        })
+    }
+
+    // Möller–Trumbore. Counter clockwise triangles face the viewer, with
+    // cull_backface the back side is ignored. Normal follows the winding
+    pub fn intersect_triangle(
+        &self,
+        a: Vector3,
+        b: Vector3,
+        c: Vector3,
+        cull_backface: bool,
+    ) -> Option<TriangleHit> {
+        let e1 = b - a;
+        let e2 = c - a;
+        let p = self.direction.cross(e2);
+        let det = e1.dot(p);
+
+        if cull_backface {
+            if det < 1e-12 {
+                return None;
+            }
+        } else if det.abs() < 1e-12 {
+            return None;
+        }
+
+        let inv = 1.0 / det;
+        let s = self.origin - a;
+        let u = s.dot(p) * inv;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(e1);
+        let v = self.direction.dot(q) * inv;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        let t = e2.dot(q) * inv;
+        if t < 0.0 {
+            return None;
+        }
+
+        Some(TriangleHit {
+            hit: RayHit {
+                distance: t,
+                point: self.at(t),
+                normal: e1.cross(e2).normalize(),
+            },
+            barycentric: Vector3::new(1.0 - u - v, u, v),
+        })
+    }
+}
@@ -0,0 +1,224 @@
+use crate::math::la::matrix4::Matrix4x4;
+use crate::math::la::vector3::Vector3;
+use crate::math::random::Random;
+
+// Negative radius is the empty sphere, contains nothing
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Sphere {
+    pub center: Vector3,
+    pub radius: f32,
+}
+
+#[allow(dead_code)]
+impl Sphere {
+    pub const fn new(center: Vector3, radius: f32) -> Sphere {
+        Sphere { center, radius }
+    }
+
+    pub const fn empty() -> Sphere {
+        Sphere {
+            center: Vector3::zero(),
+            radius: -1.0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.radius < 0.0
+    }
+
+    // Fast approximation, up to about 5% larger than the minimum. Start from
+    // two far apart points and grow the sphere for every point left outside
+    pub fn ritter(points: &[Vector3]) -> Sphere {
+        if points.is_empty() {
+            return Sphere::empty();
+        }
+
+        let farthest = |from: Vector3| {
+            let mut best = from;
+            let mut best_d = -1.0;
+            for p in points.iter() {
+                let d = (*p - from).dot(*p - from);
+                if d > best_d {
+                    best = *p;
+                    best_d = d;
+                }
+            }
+            best
+        };
+        let a = farthest(points[0]);
+        let b = farthest(a);
+        let mut s = Sphere::from_two(a, b);
+
+        for p in points.iter() {
+            s = s.add_point(*p);
+        }
+        s
+    }
+
+    // Minimum enclosing sphere (Welzl), expected linear time. The points are
+    // shuffled with a fixed seed so the result is repeatable
+    pub fn welzl(points: &[Vector3]) -> Sphere {
+        let mut p = points.to_vec();
+        let mut r = Random::new(0x5EED);
+        for i in (1..p.len()).rev() {
+            let j = (r.int() as u32 as usize) % (i + 1);
+            p.swap(i, j);
+        }
+
+        // Move to front without recursion, each level pins one more point to
+        // the surface
+        let mut s = Sphere::empty();
+        for i in 0..p.len() {
+            if s.contains_loose(p[i]) {
+                continue;
+            }
+            s = Sphere::new(p[i], 0.0);
+            for j in 0..i {
+                if s.contains_loose(p[j]) {
+                    continue;
+                }
+                s = Sphere::from_two(p[i], p[j]);
+                for k in 0..j {
+                    if s.contains_loose(p[k]) {
+                        continue;
+                    }
+                    s = Sphere::from_three(p[i], p[j], p[k]);
+                    for l in 0..k {
+                        if s.contains_loose(p[l]) {
+                            continue;
+                        }
+                        s = Sphere::from_four(p[i], p[j], p[k], p[l]);
+                    }
+                }
+            }
+        }
+        s
+    }
+
+    // Smallest sphere with both points on the surface
+    pub fn from_two(a: Vector3, b: Vector3) -> Sphere {
+        Sphere::new((a + b) * 0.5, a.distance_to(b) * 0.5)
+    }
+
+    // Circumsphere of the triangle, the sphere of the longest edge if the
+    // points are on a line
+    pub fn from_three(a: Vector3, b: Vector3, c: Vector3) -> Sphere {
+        let ab = b - a;
+        let ac = c - a;
+        let n = ab.cross(ac);
+        let n2 = n.dot(n);
+        if n2 <= f32::EPSILON * ab.dot(ab) * ac.dot(ac) {
+            return Sphere::largest_pair(&[a, b, c]);
+        }
+
+        let o = (n.cross(ab) * ac.dot(ac) + ac.cross(n) * ab.dot(ab)) / (2.0 * n2);
+        Sphere::new(a + o, o.length())
+    }
+
+    // Circumsphere of the tetrahedron. Flat tetrahedrons fall back to the
+    // smallest triangle sphere that holds all four points
+    pub fn from_four(a: Vector3, b: Vector3, c: Vector3, d: Vector3) -> Sphere {
+        let (r1, r2, r3) = (b - a, c - a, d - a);
+        let det = r1.dot(r2.cross(r3));
+        let scale = r1.length() * r2.length() * r3.length();
+
+        if det.abs() > f32::EPSILON * 16.0 * scale {
+            let o =
+                (r2.cross(r3) * r1.dot(r1) + r3.cross(r1) * r2.dot(r2) + r1.cross(r2) * r3.dot(r3))
+                    / (2.0 * det);
+            return Sphere::new(a + o, o.length());
+        }
+
+        let all = [a, b, c, d];
+        let mut best = Sphere::largest_pair(&all);
+        for (i, j, k) in [(0, 1, 2), (0, 1, 3), (0, 2, 3), (1, 2, 3)] {
+            let s = Sphere::from_three(all[i], all[j], all[k]);
+            if s.radius < best.radius && all.iter().all(|p| s.contains_loose(*p)) {
+                best = s;
+            }
+        }
+        best
+    }
+
+    // Sphere of the two points farthest apart
+    fn largest_pair(points: &[Vector3]) -> Sphere {
+        let mut best = Sphere::new(points[0], 0.0);
+        for (i, a) in points.iter().enumerate() {
+            for b in points[i + 1..].iter() {
+                let s = Sphere::from_two(*a, *b);
+                if s.radius > best.radius {
+                    best = s;
+                }
+            }
+        }
+        best
+    }
+
+    // Contains with a little slack for rounding, keeps welzl from chasing
+    // points that are on the surface already
+    fn contains_loose(&self, p: Vector3) -> bool {
+        let slack = self.radius * 1e-5 + 1e-6;
+        !self.is_empty() && self.center.distance_to(p) <= self.radius + slack
+    }
+
+    pub fn contains_point(&self, p: Vector3) -> bool {
+        !self.is_empty() && (p - self.center).dot(p - self.center) <= self.radius * self.radius
+    }
+
+    pub fn contains(&self, other: &Sphere) -> bool {
+        other.is_empty()
+            || (!self.is_empty()
+                && self.center.distance_to(other.center) + other.radius <= self.radius)
+    }
+
+    // Touching spheres intersect
+    pub fn intersects(&self, other: &Sphere) -> bool {
+        let r = self.radius + other.radius;
+        !self.is_empty()
+            && !other.is_empty()
+            && (other.center - self.center).dot(other.center - self.center) <= r * r
+    }
+
+    // Smallest sphere around self and p, self if p is inside
+    pub fn add_point(&self, p: Vector3) -> Sphere {
+        if self.is_empty() {
+            return Sphere::new(p, 0.0);
+        }
+        let d = self.center.distance_to(p);
+        if d <= self.radius {
+            return *self;
+        }
+        let radius = (self.radius + d) * 0.5;
+        let center = self.center + (p - self.center) * ((radius - self.radius) / d);
+        Sphere::new(center, radius)
+    }
+
+    // Smallest sphere around both
+    pub fn merge(&self, other: &Sphere) -> Sphere {
+        if self.contains(other) {
+            return *self;
+        }
+        if other.contains(self) {
+            return *other;
+        }
+
+        let d = self.center.distance_to(other.center);
+        let radius = (d + self.radius + other.radius) * 0.5;
+        let center = self.center + (other.center - self.center) * ((radius - self.radius) / d);
+        Sphere::new(center, radius)
+    }
+
+    // The radius grows by the largest axis scale, so the result still holds
+    // the sphere under non uniform scale
+    pub fn transform(&self, mx: &Matrix4x4) -> Sphere {
+        if self.is_empty() {
+            return *self;
+        }
+        let r = mx.raw;
+        let sx = Vector3::new(r[0], r[1], r[2]).length();
+        let sy = Vector3::new(r[4], r[5], r[6]).length();
+        let sz = Vector3::new(r[8], r[9], r[10]).length();
+        Sphere::new(self.center * *mx, self.radius * sx.max(sy).max(sz))
+    }
+}
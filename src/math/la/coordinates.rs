@@ -0,0 +1,228 @@
+use crate::math::la::vector2::Vector2;
+use crate::math::la::vector3::Vector3;
+use crate::math::number::scalar::Float;
+use std::fmt;
+
+// Y is up. Polar angle is measured from +Y in 0..PI, azimuth goes around Y
+// starting at +Z and turning towards +X
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spherical<T = f32> {
+    pub radius: T,
+    pub polar: T,
+    pub azimuth: T,
+}
+
+// Same azimuth as Spherical, height along +Y
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Cylindrical<T = f32> {
+    pub radius: T,
+    pub azimuth: T,
+    pub height: T,
+}
+
+// Angle from +X towards +Y
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Polar<T = f32> {
+    pub radius: T,
+    pub angle: T,
+}
+
+impl<T: fmt::Display> fmt::Display for Spherical<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Spherical({}, {}, {})",
+            self.radius, self.polar, self.azimuth
+        )
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Cylindrical<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Cylindrical({}, {}, {})",
+            self.radius, self.azimuth, self.height
+        )
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Polar<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Polar({}, {})", self.radius, self.angle)
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Float> Spherical<T> {
+    pub const fn new(radius: T, polar: T, azimuth: T) -> Spherical<T> {
+        Spherical {
+            radius,
+            polar,
+            azimuth,
+        }
+    }
+
+    // The origin has zero angles
+    pub fn from_vector3(v: Vector3<T>) -> Spherical<T> {
+        let radius = v.length();
+        if radius == T::ZERO {
+            return Spherical::new(T::ZERO, T::ZERO, T::ZERO);
+        }
+        let y = clamp(v.y / radius, -T::ONE, T::ONE);
+        Spherical::new(radius, y.acos(), v.x.atan2(v.z))
+    }
+
+    pub fn to_vector3(self) -> Vector3<T> {
+        let s = self.radius * self.polar.sin();
+        Vector3::new(
+            s * self.azimuth.sin(),
+            self.radius * self.polar.cos(),
+            s * self.azimuth.cos(),
+        )
+    }
+
+    // Keep polar slightly away from the poles, an orbit camera looking
+    // straight up or down loses its azimuth
+    pub fn make_safe(self) -> Spherical<T> {
+        let eps = T::from_f64(1e-6);
+        Spherical {
+            polar: clamp(self.polar, eps, T::PI - eps),
+            ..self
+        }
+    }
+
+    // Latitude -PI/2..PI/2 from the equator, longitude is the azimuth
+    pub fn from_lat_lon(radius: T, lat: T, lon: T) -> Spherical<T> {
+        Spherical::new(radius, T::PI / T::from_f64(2.0) - lat, lon)
+    }
+
+    pub fn to_lat_lon(self) -> (T, T) {
+        (T::PI / T::from_f64(2.0) - self.polar, self.azimuth)
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Float> Cylindrical<T> {
+    pub const fn new(radius: T, azimuth: T, height: T) -> Cylindrical<T> {
+        Cylindrical {
+            radius,
+            azimuth,
+            height,
+        }
+    }
+
+    pub fn from_vector3(v: Vector3<T>) -> Cylindrical<T> {
+        Cylindrical::new((v.x * v.x + v.z * v.z).sqrt(), v.x.atan2(v.z), v.y)
+    }
+
+    pub fn to_vector3(self) -> Vector3<T> {
+        Vector3::new(
+            self.radius * self.azimuth.sin(),
+            self.height,
+            self.radius * self.azimuth.cos(),
+        )
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Float> Polar<T> {
+    pub const fn new(radius: T, angle: T) -> Polar<T> {
+        Polar { radius, angle }
+    }
+
+    pub fn from_vector2(v: Vector2<T>) -> Polar<T> {
+        Polar::new((v.x * v.x + v.y * v.y).sqrt(), v.y.atan2(v.x))
+    }
+
+    pub fn to_vector2(self) -> Vector2<T> {
+        Vector2::new(
+            self.radius * self.angle.cos(),
+            self.radius * self.angle.sin(),
+        )
+    }
+}
+
+impl<T: Float> From<Vector3<T>> for Spherical<T> {
+    fn from(v: Vector3<T>) -> Spherical<T> {
+        Spherical::from_vector3(v)
+    }
+}
+
+impl<T: Float> From<Spherical<T>> for Vector3<T> {
+    fn from(s: Spherical<T>) -> Vector3<T> {
+        s.to_vector3()
+    }
+}
+
+impl<T: Float> From<Vector3<T>> for Cylindrical<T> {
+    fn from(v: Vector3<T>) -> Cylindrical<T> {
+        Cylindrical::from_vector3(v)
+    }
+}
+
+impl<T: Float> From<Cylindrical<T>> for Vector3<T> {
+    fn from(c: Cylindrical<T>) -> Vector3<T> {
+        c.to_vector3()
+    }
+}
+
+impl<T: Float> From<Vector2<T>> for Polar<T> {
+    fn from(v: Vector2<T>) -> Polar<T> {
+        Polar::from_vector2(v)
+    }
+}
+
+impl<T: Float> From<Polar<T>> for Vector2<T> {
+    fn from(p: Polar<T>) -> Vector2<T> {
+        p.to_vector2()
+    }
+}
+
+// Unit direction for latitude and longitude in radians
+pub fn lat_lon_to_direction<T: Float>(lat: T, lon: T) -> Vector3<T> {
+    Spherical::from_lat_lon(T::ONE, lat, lon).to_vector3()
+}
+
+// Latitude and longitude of a direction, it doesn't need to be normalized
+pub fn direction_to_lat_lon<T: Float>(dir: Vector3<T>) -> (T, T) {
+    Spherical::from_vector3(dir).to_lat_lon()
+}
+
+// Maps two uniform 0..1 numbers to directions spread evenly over the sphere,
+// u picks the height and v the longitude
+pub fn uniform_direction<T: Float>(u: T, v: T) -> Vector3<T> {
+    let two = T::from_f64(2.0);
+    let y = T::ONE - two * u;
+    let r = clamp(T::ONE - y * y, T::ZERO, T::ONE).sqrt();
+    let azimuth = two * T::PI * v;
+    Vector3::new(r * azimuth.sin(), y, r * azimuth.cos())
+}
+
+// Equirectangular texture coordinates, u follows the longitude from -PI at 0
+// to PI at 1 and v goes from the north pole at 0 to the south pole at 1
+pub fn direction_to_equirect_uv<T: Float>(dir: Vector3<T>) -> Vector2<T> {
+    let (lat, lon) = direction_to_lat_lon(dir);
+    let half = T::from_f64(0.5);
+    Vector2::new(lon / (T::from_f64(2.0) * T::PI) + half, half - lat / T::PI)
+}
+
+pub fn equirect_uv_to_direction<T: Float>(uv: Vector2<T>) -> Vector3<T> {
+    let half = T::from_f64(0.5);
+    let lon = (uv.x - half) * T::from_f64(2.0) * T::PI;
+    let lat = (half - uv.y) * T::PI;
+    lat_lon_to_direction(lat, lon)
+}
+
+fn clamp<T: Float>(v: T, min: T, max: T) -> T {
+    if v < min {
+        min
+    } else if v > max {
+        max
+    } else {
+        v
+    }
+}
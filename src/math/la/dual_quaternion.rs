@@ -0,0 +1,227 @@
+use crate::math::la::matrix4::Matrix4x4;
+use crate::math::la::quaternion::Quaternion;
+use crate::math::la::vector3::Vector3;
+use crate::math::number::scalar::Float;
+use std::{fmt, ops};
+
+// Rigid transform (rotation + translation) as a unit dual quaternion
+// real + dual * e, where e * e = 0
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: serde::Serialize + Copy",
+        deserialize = "T: serde::Deserialize<'de>"
+    ))
+)]
+#[repr(C)]
+pub struct DualQuaternion<T = f32> {
+    pub real: Quaternion<T>,
+    pub dual: Quaternion<T>,
+}
+
+// Print
+impl<T: fmt::Display> fmt::Display for DualQuaternion<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DualQuaternion({}, {})", self.real, self.dual)
+    }
+}
+
+// Add DualQuaternion + DualQuaternion
+impl<T: Float> ops::Add<DualQuaternion<T>> for DualQuaternion<T> {
+    type Output = DualQuaternion<T>;
+
+    fn add(self, _rhs: DualQuaternion<T>) -> DualQuaternion<T> {
+        DualQuaternion {
+            real: self.real + _rhs.real,
+            dual: self.dual + _rhs.dual,
+        }
+    }
+}
+
+// Scale DualQuaternion * T
+impl<T: Float> ops::Mul<T> for DualQuaternion<T> {
+    type Output = DualQuaternion<T>;
+
+    fn mul(self, _rhs: T) -> DualQuaternion<T> {
+        DualQuaternion {
+            real: self.real * _rhs,
+            dual: self.dual * _rhs,
+        }
+    }
+}
+
+// Combine DualQuaternion * DualQuaternion, rhs is applied first
+impl<T: Float> ops::Mul<DualQuaternion<T>> for DualQuaternion<T> {
+    type Output = DualQuaternion<T>;
+
+    fn mul(self, _rhs: DualQuaternion<T>) -> DualQuaternion<T> {
+        DualQuaternion {
+            real: self.real * _rhs.real,
+            dual: self.real * _rhs.dual + self.dual * _rhs.real,
+        }
+    }
+}
+
+impl<T: Float> ops::MulAssign<DualQuaternion<T>> for DualQuaternion<T> {
+    fn mul_assign(&mut self, other: DualQuaternion<T>) {
+        *self = *self * other;
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Float> DualQuaternion<T> {
+    pub const fn new(real: Quaternion<T>, dual: Quaternion<T>) -> DualQuaternion<T> {
+        DualQuaternion { real, dual }
+    }
+
+    pub const fn identity() -> DualQuaternion<T> {
+        DualQuaternion {
+            real: Quaternion::identity(),
+            dual: Quaternion::zero(),
+        }
+    }
+
+    // Rotate first, then translate
+    pub fn from_rotation_translation(rotation: Quaternion<T>, translation: Vector3<T>) -> Self {
+        let t = Quaternion::new(translation.x, translation.y, translation.z, T::ZERO);
+        DualQuaternion {
+            real: rotation,
+            dual: (t * rotation) * T::from_f64(0.5),
+        }
+    }
+
+    pub fn from_translation(translation: Vector3<T>) -> DualQuaternion<T> {
+        DualQuaternion::from_rotation_translation(Quaternion::identity(), translation)
+    }
+
+    // Scale of the matrix is ignored, only rotation and translation are kept
+    pub fn from_matrix4x4(mx: Matrix4x4<T>) -> DualQuaternion<T> {
+        let (translation, rotation, _) = mx.decompose();
+        DualQuaternion::from_rotation_translation(rotation, translation)
+    }
+
+    pub fn to_matrix4x4(self) -> Matrix4x4<T> {
+        let n = self.normalize();
+        let mut mx = n.real.to_matrix4x4();
+        let t = n.get_translation();
+        mx.raw[12] = t.x;
+        mx.raw[13] = t.y;
+        mx.raw[14] = t.z;
+        mx
+    }
+
+    pub fn get_rotation(self) -> Quaternion<T> {
+        self.real
+    }
+
+    pub fn get_translation(self) -> Vector3<T> {
+        let t = (self.dual * self.real.conjugate()) * T::from_f64(2.0);
+        Vector3::new(t.x, t.y, t.z)
+    }
+
+    pub fn dot(self, q: DualQuaternion<T>) -> T {
+        self.real.dot(q.real)
+    }
+
+    pub fn conjugate(self) -> DualQuaternion<T> {
+        DualQuaternion {
+            real: self.real.conjugate(),
+            dual: self.dual.conjugate(),
+        }
+    }
+
+    // Inverse of a unit dual quaternion
+    pub fn inverse(self) -> DualQuaternion<T> {
+        self.normalize().conjugate()
+    }
+
+    // Makes the real part unit length and the dual part orthogonal to it
+    pub fn normalize(self) -> DualQuaternion<T> {
+        let l = self.real.length();
+        if l == T::ZERO {
+            return DualQuaternion::identity();
+        }
+        let inv = T::ONE / l;
+        let real = self.real * inv;
+        let dual = self.dual * inv;
+        DualQuaternion {
+            real,
+            dual: dual - real * real.dot(dual),
+        }
+    }
+
+    pub fn transform_point(self, p: Vector3<T>) -> Vector3<T> {
+        self.real.rotate_vector(p) + self.get_translation()
+    }
+
+    // Directions are only rotated
+    pub fn transform_vector(self, v: Vector3<T>) -> Vector3<T> {
+        self.real.rotate_vector(v)
+    }
+
+    // Raise unit dual quaternion to power t via its screw parameters
+    pub fn pow(self, t: T) -> DualQuaternion<T> {
+        let two = T::from_f64(2.0);
+        let mut q = self.normalize();
+        if q.real.w < T::ZERO {
+            q = q * -T::ONE;
+        }
+
+        let axis = Vector3::new(q.real.x, q.real.y, q.real.z);
+        let s = axis.length();
+
+        // Pure translation, no screw axis
+        if s < T::from_f64(1e-6) {
+            return DualQuaternion::from_translation(q.get_translation().scale(t));
+        }
+
+        let w = if q.real.w > T::ONE { T::ONE } else { q.real.w };
+        let angle = two * w.acos();
+        let l = axis.scale(T::ONE / s);
+        let d = -two * q.dual.w / s;
+        let dual_axis = Vector3::new(q.dual.x, q.dual.y, q.dual.z);
+        let m = (dual_axis - l.scale(d / two * w)).scale(T::ONE / s);
+
+        let half = angle * t / two;
+        let dt = d * t / two;
+        let (sin, cos) = (half.sin(), half.cos());
+        let r = l.scale(sin);
+        let e = m.scale(sin) + l.scale(dt * cos);
+
+        DualQuaternion {
+            real: Quaternion::new(r.x, r.y, r.z, cos),
+            dual: Quaternion::new(e.x, e.y, e.z, -dt * sin),
+        }
+    }
+
+    // Screw linear interpolation, constant speed along the screw motion
+    pub fn sclerp(a: DualQuaternion<T>, b: DualQuaternion<T>, t: T) -> DualQuaternion<T> {
+        let b = if a.dot(b) < T::ZERO { b * -T::ONE } else { b };
+        a * (a.inverse() * b).pow(t)
+    }
+
+    // Dual quaternion linear blending, used for skinning
+    pub fn dlb(list: &[DualQuaternion<T>], weights: &[T]) -> DualQuaternion<T> {
+        if list.is_empty() {
+            return DualQuaternion::identity();
+        }
+
+        let pivot = list[0];
+        let mut sum = DualQuaternion::new(Quaternion::zero(), Quaternion::zero());
+        for (q, w) in list.iter().zip(weights.iter()) {
+            // Keep all in the same hemisphere as the first one
+            let w = if pivot.dot(*q) < T::ZERO { -*w } else { *w };
+            sum = sum + *q * w;
+        }
+        sum.normalize()
+    }
+
+    pub fn cast<U: Float>(self) -> DualQuaternion<U> {
+        DualQuaternion {
+            real: self.real.cast(),
+            dual: self.dual.cast(),
+        }
+    }
+}
@@ -0,0 +1,425 @@
+// Operators shared by the la types. Vectors and quaternions list their fields
+// with the index they answer to, matrices work on raw.
+
+// Add, Sub, scale by T, Neg, Index and Sum for types that are a plain list of
+// components
+macro_rules! impl_linear_ops {
+    ($V:ident { $($f:ident: $i:literal),+ }) => {
+        // Add V + V
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::Add<$V<T>> for $V<T> {
+            type Output = $V<T>;
+
+            fn add(self, _rhs: $V<T>) -> $V<T> {
+                $V { $($f: self.$f + _rhs.$f),+ }
+            }
+        }
+
+        // Sub V - V
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::Sub<$V<T>> for $V<T> {
+            type Output = $V<T>;
+
+            fn sub(self, _rhs: $V<T>) -> $V<T> {
+                $V { $($f: self.$f - _rhs.$f),+ }
+            }
+        }
+
+        // Scale V * T
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::Mul<T> for $V<T> {
+            type Output = $V<T>;
+
+            fn mul(self, _rhs: T) -> $V<T> {
+                $V { $($f: self.$f * _rhs),+ }
+            }
+        }
+
+        // Scale V / T
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::Div<T> for $V<T> {
+            type Output = $V<T>;
+
+            fn div(self, _rhs: T) -> $V<T> {
+                $V { $($f: self.$f / _rhs),+ }
+            }
+        }
+
+        // Negate -V
+        impl<T: $crate::math::number::scalar::Scalar + ::std::ops::Neg<Output = T>> ::std::ops::Neg for $V<T> {
+            type Output = $V<T>;
+
+            fn neg(self) -> $V<T> {
+                $V { $($f: -self.$f),+ }
+            }
+        }
+
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::AddAssign<$V<T>> for $V<T> {
+            fn add_assign(&mut self, _rhs: $V<T>) {
+                $(self.$f += _rhs.$f;)+
+            }
+        }
+
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::SubAssign<$V<T>> for $V<T> {
+            fn sub_assign(&mut self, _rhs: $V<T>) {
+                $(self.$f -= _rhs.$f;)+
+            }
+        }
+
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::MulAssign<T> for $V<T> {
+            fn mul_assign(&mut self, _rhs: T) {
+                $(self.$f *= _rhs;)+
+            }
+        }
+
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::DivAssign<T> for $V<T> {
+            fn div_assign(&mut self, _rhs: T) {
+                $(self.$f /= _rhs;)+
+            }
+        }
+
+        impl<T> ::std::ops::Index<usize> for $V<T> {
+            type Output = T;
+
+            fn index(&self, i: usize) -> &T {
+                match i {
+                    $($i => &self.$f,)+
+                    _ => panic!(concat!(stringify!($V), " index out of range: {}"), i),
+                }
+            }
+        }
+
+        impl<T> ::std::ops::IndexMut<usize> for $V<T> {
+            fn index_mut(&mut self, i: usize) -> &mut T {
+                match i {
+                    $($i => &mut self.$f,)+
+                    _ => panic!(concat!(stringify!($V), " index out of range: {}"), i),
+                }
+            }
+        }
+
+        impl<T: $crate::math::number::scalar::Scalar> ::std::iter::Sum for $V<T> {
+            fn sum<I: Iterator<Item = $V<T>>>(iter: I) -> $V<T> {
+                iter.fold($V { $($f: T::ZERO),+ }, |a, b| a + b)
+            }
+        }
+
+        impl<'a, T: $crate::math::number::scalar::Scalar> ::std::iter::Sum<&'a $V<T>> for $V<T> {
+            fn sum<I: Iterator<Item = &'a $V<T>>>(iter: I) -> $V<T> {
+                iter.fold($V { $($f: T::ZERO),+ }, |a, b| a + *b)
+            }
+        }
+
+        $crate::math::la::macros::impl_lhs_scale!($V);
+    };
+}
+
+// Scale T * V for every Scalar type
+macro_rules! impl_lhs_scale {
+    ($V:ident) => {
+        $crate::math::la::macros::impl_lhs_scale!($V, f32, f64, i16, i32, i64, u8, u16, u32, u64);
+    };
+    ($V:ident, $($t:ty),+) => {
+        $(
+            impl ::std::ops::Mul<$V<$t>> for $t {
+                type Output = $V<$t>;
+
+                fn mul(self, _rhs: $V<$t>) -> $V<$t> {
+                    _rhs * self
+                }
+            }
+        )+
+    };
+}
+
+// Linear ops plus component-wise Mul and Div and the min/max/abs/clamp and
+// reflect/project helpers. The type has to provide dot()
+macro_rules! impl_vector_ops {
+    ($V:ident { $($f:ident: $i:literal),+ }) => {
+        $crate::math::la::macros::impl_linear_ops!($V { $($f: $i),+ });
+
+        // Component-wise V * V
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::Mul<$V<T>> for $V<T> {
+            type Output = $V<T>;
+
+            fn mul(self, _rhs: $V<T>) -> $V<T> {
+                $V { $($f: self.$f * _rhs.$f),+ }
+            }
+        }
+
+        // Component-wise V / V
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::Div<$V<T>> for $V<T> {
+            type Output = $V<T>;
+
+            fn div(self, _rhs: $V<T>) -> $V<T> {
+                $V { $($f: self.$f / _rhs.$f),+ }
+            }
+        }
+
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::MulAssign<$V<T>> for $V<T> {
+            fn mul_assign(&mut self, _rhs: $V<T>) {
+                $(self.$f *= _rhs.$f;)+
+            }
+        }
+
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::DivAssign<$V<T>> for $V<T> {
+            fn div_assign(&mut self, _rhs: $V<T>) {
+                $(self.$f /= _rhs.$f;)+
+            }
+        }
+
+        #[allow(dead_code)]
+        impl<T: $crate::math::number::scalar::Scalar> $V<T> {
+            pub fn min(self, v2: $V<T>) -> $V<T> {
+                $V { $($f: if v2.$f < self.$f { v2.$f } else { self.$f }),+ }
+            }
+
+            pub fn max(self, v2: $V<T>) -> $V<T> {
+                $V { $($f: if v2.$f > self.$f { v2.$f } else { self.$f }),+ }
+            }
+
+            pub fn abs(self) -> $V<T> {
+                $V { $($f: self.$f.abs()),+ }
+            }
+
+            pub fn clamp(self, min: $V<T>, max: $V<T>) -> $V<T> {
+                self.max(min).min(max)
+            }
+        }
+
+        #[allow(dead_code)]
+        impl<T: $crate::math::number::scalar::Float> $V<T> {
+            // Mirror around the plane with the given unit normal
+            pub fn reflect(self, normal: $V<T>) -> $V<T> {
+                self - normal * (T::from_f64(2.0) * self.dot(normal))
+            }
+
+            // Part of self along onto, zero if onto is zero
+            pub fn project(self, onto: $V<T>) -> $V<T> {
+                let d = onto.dot(onto);
+                if d == T::ZERO {
+                    return onto;
+                }
+                onto * (self.dot(onto) / d)
+            }
+        }
+    };
+}
+
+// Element-wise Add, Sub, scale and Neg, Index into raw and (column, row)
+macro_rules! impl_matrix_ops {
+    ($M:ident, $n:literal) => {
+        // Add M + M
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::Add<$M<T>> for $M<T> {
+            type Output = $M<T>;
+
+            fn add(mut self, _rhs: $M<T>) -> $M<T> {
+                self += _rhs;
+                self
+            }
+        }
+
+        // Sub M - M
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::Sub<$M<T>> for $M<T> {
+            type Output = $M<T>;
+
+            fn sub(mut self, _rhs: $M<T>) -> $M<T> {
+                self -= _rhs;
+                self
+            }
+        }
+
+        // Scale M * T
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::Mul<T> for $M<T> {
+            type Output = $M<T>;
+
+            fn mul(mut self, _rhs: T) -> $M<T> {
+                self *= _rhs;
+                self
+            }
+        }
+
+        // Scale M / T
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::Div<T> for $M<T> {
+            type Output = $M<T>;
+
+            fn div(mut self, _rhs: T) -> $M<T> {
+                self /= _rhs;
+                self
+            }
+        }
+
+        // Negate -M
+        impl<T: $crate::math::number::scalar::Scalar + ::std::ops::Neg<Output = T>> ::std::ops::Neg
+            for $M<T>
+        {
+            type Output = $M<T>;
+
+            fn neg(mut self) -> $M<T> {
+                for v in self.raw.iter_mut() {
+                    *v = -*v;
+                }
+                self
+            }
+        }
+
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::AddAssign<$M<T>> for $M<T> {
+            fn add_assign(&mut self, _rhs: $M<T>) {
+                for i in 0..$n * $n {
+                    self.raw[i] += _rhs.raw[i];
+                }
+            }
+        }
+
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::SubAssign<$M<T>> for $M<T> {
+            fn sub_assign(&mut self, _rhs: $M<T>) {
+                for i in 0..$n * $n {
+                    self.raw[i] -= _rhs.raw[i];
+                }
+            }
+        }
+
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::MulAssign<T> for $M<T> {
+            fn mul_assign(&mut self, _rhs: T) {
+                for v in self.raw.iter_mut() {
+                    *v *= _rhs;
+                }
+            }
+        }
+
+        impl<T: $crate::math::number::scalar::Scalar> ::std::ops::DivAssign<T> for $M<T> {
+            fn div_assign(&mut self, _rhs: T) {
+                for v in self.raw.iter_mut() {
+                    *v /= _rhs;
+                }
+            }
+        }
+
+        impl<T> ::std::ops::Index<usize> for $M<T> {
+            type Output = T;
+
+            fn index(&self, i: usize) -> &T {
+                &self.raw[i]
+            }
+        }
+
+        impl<T> ::std::ops::IndexMut<usize> for $M<T> {
+            fn index_mut(&mut self, i: usize) -> &mut T {
+                &mut self.raw[i]
+            }
+        }
+
+        // m[(column, row)]
+        impl<T> ::std::ops::Index<(usize, usize)> for $M<T> {
+            type Output = T;
+
+            fn index(&self, (c, r): (usize, usize)) -> &T {
+                assert!(c < $n, "column out of range: {}", c);
+                assert!(r < $n, "row out of range: {}", r);
+                &self.raw[c * $n + r]
+            }
+        }
+
+        impl<T> ::std::ops::IndexMut<(usize, usize)> for $M<T> {
+            fn index_mut(&mut self, (c, r): (usize, usize)) -> &mut T {
+                assert!(c < $n, "column out of range: {}", c);
+                assert!(r < $n, "row out of range: {}", r);
+                &mut self.raw[c * $n + r]
+            }
+        }
+
+        $crate::math::la::macros::impl_lhs_scale!($M);
+    };
+}
+
+// Byte encoding for the f32 types, $n floats little endian like from_bytes
+// reads them, be variants use big endian. The slice casts reinterpret
+// #[repr(C)] memory, so they use the native byte order
+macro_rules! impl_bytes {
+    ($V:ident, $n:literal) => {
+        #[allow(dead_code)]
+        impl $V<f32> {
+            pub fn from_bytes(b: &[u8]) -> $V {
+                $V::from(::std::array::from_fn::<f32, $n, _>(|i| {
+                    f32::from_le_bytes([b[i * 4], b[i * 4 + 1], b[i * 4 + 2], b[i * 4 + 3]])
+                }))
+            }
+
+            pub fn from_be_bytes(b: &[u8]) -> $V {
+                $V::from(::std::array::from_fn::<f32, $n, _>(|i| {
+                    f32::from_be_bytes([b[i * 4], b[i * 4 + 1], b[i * 4 + 2], b[i * 4 + 3]])
+                }))
+            }
+
+            pub fn to_bytes(self) -> [u8; $n * 4] {
+                let mut out = [0; $n * 4];
+                for (o, v) in out.chunks_exact_mut(4).zip(<[f32; $n]>::from(self)) {
+                    o.copy_from_slice(&v.to_le_bytes());
+                }
+                out
+            }
+
+            pub fn to_be_bytes(self) -> [u8; $n * 4] {
+                let mut out = [0; $n * 4];
+                for (o, v) in out.chunks_exact_mut(4).zip(<[f32; $n]>::from(self)) {
+                    o.copy_from_slice(&v.to_be_bytes());
+                }
+                out
+            }
+
+            pub fn write_to<W: ::std::io::Write>(&self, w: &mut W) -> ::std::io::Result<()> {
+                w.write_all(&self.to_bytes())
+            }
+
+            pub fn write_be_to<W: ::std::io::Write>(&self, w: &mut W) -> ::std::io::Result<()> {
+                w.write_all(&self.to_be_bytes())
+            }
+
+            pub fn slice_as_bytes(list: &[$V]) -> &[u8] {
+                // SAFETY: $V is #[repr(C)] of f32 only, no padding and every
+                // byte is initialized
+                unsafe {
+                    ::std::slice::from_raw_parts(
+                        list.as_ptr() as *const u8,
+                        ::std::mem::size_of_val(list),
+                    )
+                }
+            }
+
+            // None if b is not aligned for f32 or not a whole number of items
+            pub fn slice_from_bytes(b: &[u8]) -> Option<&[$V]> {
+                let size = ::std::mem::size_of::<$V>();
+                if b.len() % size != 0 || b.as_ptr() as usize % ::std::mem::align_of::<$V>() != 0 {
+                    return None;
+                }
+                // SAFETY: size and alignment are checked above and any bit
+                // pattern is a valid f32
+                Some(unsafe {
+                    ::std::slice::from_raw_parts(b.as_ptr() as *const $V, b.len() / size)
+                })
+            }
+        }
+    };
+}
+
+// Serialize as a flat array, [x, y, z] instead of {"x": .., "y": .., "z": ..}
+#[cfg(feature = "serde")]
+macro_rules! impl_serde_array {
+    ($V:ident, $n:literal) => {
+        impl<T: serde::Serialize + Copy> serde::Serialize for $V<T> {
+            fn serialize<S: serde::Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+                <[T; $n]>::from(*self).serialize(s)
+            }
+        }
+
+        impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for $V<T> {
+            fn deserialize<D: serde::Deserializer<'de>>(d: D) -> Result<$V<T>, D::Error> {
+                <[T; $n]>::deserialize(d).map($V::from)
+            }
+        }
+    };
+}
+
+pub(crate) use impl_bytes;
+pub(crate) use impl_lhs_scale;
+pub(crate) use impl_linear_ops;
+pub(crate) use impl_matrix_ops;
+#[cfg(feature = "serde")]
+pub(crate) use impl_serde_array;
+pub(crate) use impl_vector_ops;
@@ -0,0 +1,152 @@
+#[cfg(feature = "serde")]
+use crate::math::la::macros::impl_serde_array;
+use crate::math::la::macros::{impl_bytes, impl_matrix_ops};
+use crate::math::number::scalar::{Float, Scalar};
+use std::fmt;
+use std::ops;
+
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[repr(C)]
+pub struct Matrix2x2<T = f32> {
+    pub raw: [T; 4],
+}
+
+impl<T: fmt::Display> fmt::Display for Matrix2x2<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Matrix2x2(\n\t{}, {}\n\t{}, {}\n)",
+            self.raw[0], self.raw[1], self.raw[2], self.raw[3],
+        )
+    }
+}
+
+impl_matrix_ops!(Matrix2x2, 2);
+
+impl<T: Scalar> ops::Mul<Matrix2x2<T>> for Matrix2x2<T> {
+    type Output = Matrix2x2<T>;
+
+    fn mul(self, b: Matrix2x2<T>) -> Matrix2x2<T> {
+        let a0 = self.raw[0];
+        let a1 = self.raw[1];
+        let a2 = self.raw[2];
+        let a3 = self.raw[3];
+
+        let b0 = b.raw[0];
+        let b1 = b.raw[1];
+        let b2 = b.raw[2];
+        let b3 = b.raw[3];
+
+        Matrix2x2 {
+            raw: [
+                a0 * b0 + a2 * b1,
+                a1 * b0 + a3 * b1,
+                a0 * b2 + a2 * b3,
+                a1 * b2 + a3 * b3,
+            ],
+        }
+    }
+}
+
+impl<T: Scalar> ops::MulAssign<Matrix2x2<T>> for Matrix2x2<T> {
+    fn mul_assign(&mut self, b: Matrix2x2<T>) {
+        *self = *self * b;
+    }
+}
+
+impl<T: Scalar> Matrix2x2<T> {
+    pub const fn new() -> Self {
+        Matrix2x2 {
+            raw: [T::ONE, T::ZERO, T::ZERO, T::ONE],
+        }
+    }
+
+    pub fn identity(&mut self) {
+        *self = Matrix2x2::new();
+    }
+
+    pub fn transpose(self) -> Matrix2x2<T> {
+        Matrix2x2 {
+            raw: [self.raw[0], self.raw[2], self.raw[1], self.raw[3]],
+        }
+    }
+
+    pub fn determinant(&self) -> T {
+        self.raw[0] * self.raw[3] - self.raw[2] * self.raw[1]
+    }
+
+    pub fn scale(&mut self, x: T, y: T) {
+        self.raw[0] *= x;
+        self.raw[1] *= x;
+        self.raw[2] *= y;
+        self.raw[3] *= y;
+    }
+
+    pub fn cast<U: Scalar>(self) -> Matrix2x2<U> {
+        Matrix2x2 {
+            raw: [
+                U::from_f64(self.raw[0].to_f64()),
+                U::from_f64(self.raw[1].to_f64()),
+                U::from_f64(self.raw[2].to_f64()),
+                U::from_f64(self.raw[3].to_f64()),
+            ],
+        }
+    }
+}
+
+impl<T: Float> Matrix2x2<T> {
+    pub fn invert(self) -> Matrix2x2<T> {
+        let a0 = self.raw[0];
+        let a1 = self.raw[1];
+        let a2 = self.raw[2];
+        let a3 = self.raw[3];
+
+        let mut det = a0 * a3 - a2 * a1;
+        if det == T::ZERO {
+            return Matrix2x2::new();
+        }
+        det = T::ONE / det;
+
+        Matrix2x2 {
+            raw: [a3 * det, -a1 * det, -a2 * det, a0 * det],
+        }
+    }
+
+    pub fn rotate(&mut self, rad: T) {
+        let s = rad.sin();
+        let c = rad.cos();
+
+        let a0 = self.raw[0];
+        let a1 = self.raw[1];
+        let a2 = self.raw[2];
+        let a3 = self.raw[3];
+
+        self.raw[0] = a0 * c + a2 * s;
+        self.raw[1] = a1 * c + a3 * s;
+        self.raw[2] = a2 * c - a0 * s;
+        self.raw[3] = a3 * c - a1 * s;
+    }
+}
+
+impl_bytes!(Matrix2x2, 4);
+
+impl<T> From<[T; 4]> for Matrix2x2<T> {
+    fn from(raw: [T; 4]) -> Matrix2x2<T> {
+        Matrix2x2 { raw }
+    }
+}
+
+impl<T> From<Matrix2x2<T>> for [T; 4] {
+    fn from(m: Matrix2x2<T>) -> [T; 4] {
+        m.raw
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde_array!(Matrix2x2, 4);
+
+impl From<Matrix2x2<f32>> for Matrix2x2<f64> {
+    fn from(mx: Matrix2x2<f32>) -> Matrix2x2<f64> {
+        mx.cast()
+    }
+}
@@ -0,0 +1,529 @@
+#[cfg(feature = "serde")]
+use crate::math::la::macros::impl_serde_array;
+use crate::math::la::macros::{impl_bytes, impl_matrix_ops};
+use crate::math::la::matrix4::Matrix4x4;
+use crate::math::la::quaternion::{EulerOrder, Quaternion};
+use crate::math::la::vector3::Vector3;
+use crate::math::number::scalar::{Float, Scalar};
+use std::fmt;
+use std::ops;
+
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[repr(C)]
+pub struct Matrix3x3<T = f32> {
+    pub raw: [T; 9],
+}
+
+impl<T: fmt::Display> fmt::Display for Matrix3x3<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Matrix3x3(\n\t{}, {}, {}\n\t{}, {}, {}\n\t{}, {}, {}\n)",
+            self.raw[0],
+            self.raw[1],
+            self.raw[2],
+            self.raw[3],
+            self.raw[4],
+            self.raw[5],
+            self.raw[6],
+            self.raw[7],
+            self.raw[8],
+        )
+    }
+}
+
+impl_matrix_ops!(Matrix3x3, 3);
+
+impl<T: Scalar> ops::Mul<Matrix3x3<T>> for Matrix3x3<T> {
+    type Output = Matrix3x3<T>;
+
+    fn mul(self, b: Matrix3x3<T>) -> Matrix3x3<T> {
+        let mut mx = Matrix3x3::default();
+
+        let a00 = self.raw[0];
+        let a01 = self.raw[1];
+        let a02 = self.raw[2];
+        let a10 = self.raw[3];
+        let a11 = self.raw[4];
+        let a12 = self.raw[5];
+        let a20 = self.raw[6];
+        let a21 = self.raw[7];
+        let a22 = self.raw[8];
+
+        // Cache only the current line of the second matrix
+        let mut b0 = b.raw[0];
+        let mut b1 = b.raw[1];
+        let mut b2 = b.raw[2];
+        mx.raw[0] = b0 * a00 + b1 * a10 + b2 * a20;
+        mx.raw[1] = b0 * a01 + b1 * a11 + b2 * a21;
+        mx.raw[2] = b0 * a02 + b1 * a12 + b2 * a22;
+
+        b0 = b.raw[3];
+        b1 = b.raw[4];
+        b2 = b.raw[5];
+        mx.raw[3] = b0 * a00 + b1 * a10 + b2 * a20;
+        mx.raw[4] = b0 * a01 + b1 * a11 + b2 * a21;
+        mx.raw[5] = b0 * a02 + b1 * a12 + b2 * a22;
+
+        b0 = b.raw[6];
+        b1 = b.raw[7];
+        b2 = b.raw[8];
+        mx.raw[6] = b0 * a00 + b1 * a10 + b2 * a20;
+        mx.raw[7] = b0 * a01 + b1 * a11 + b2 * a21;
+        mx.raw[8] = b0 * a02 + b1 * a12 + b2 * a22;
+
+        mx
+    }
+}
+
+impl<T: Scalar> ops::MulAssign<Matrix3x3<T>> for Matrix3x3<T> {
+    fn mul_assign(&mut self, b: Matrix3x3<T>) {
+        *self = *self * b;
+    }
+}
+
+impl<T: Scalar> Matrix3x3<T> {
+    pub const fn new() -> Self {
+        Matrix3x3 {
+            raw: [
+                T::ONE,
+                T::ZERO,
+                T::ZERO,
+                T::ZERO,
+                T::ONE,
+                T::ZERO,
+                T::ZERO,
+                T::ZERO,
+                T::ONE,
+            ],
+        }
+    }
+
+    pub fn identity(&mut self) {
+        *self = Matrix3x3::new();
+    }
+
+    pub fn transpose(self) -> Matrix3x3<T> {
+        let r = self.raw;
+        Matrix3x3 {
+            raw: [r[0], r[3], r[6], r[1], r[4], r[7], r[2], r[5], r[8]],
+        }
+    }
+
+    pub fn determinant(&self) -> T {
+        let r = self.raw;
+        r[0] * (r[8] * r[4] - r[5] * r[7])
+            + r[1] * (r[5] * r[6] - r[8] * r[3])
+            + r[2] * (r[7] * r[3] - r[4] * r[6])
+    }
+
+    // Upper left 3x3 of the matrix, drops translation and projection
+    pub fn from_matrix4x4(mx: Matrix4x4<T>) -> Matrix3x3<T> {
+        let r = mx.raw;
+        Matrix3x3 {
+            raw: [r[0], r[1], r[2], r[4], r[5], r[6], r[8], r[9], r[10]],
+        }
+    }
+
+    pub fn to_matrix4x4(self) -> Matrix4x4<T> {
+        let mut mx = Matrix4x4::new();
+        let r = self.raw;
+
+        mx.raw[0] = r[0];
+        mx.raw[1] = r[1];
+        mx.raw[2] = r[2];
+
+        mx.raw[4] = r[3];
+        mx.raw[5] = r[4];
+        mx.raw[6] = r[5];
+
+        mx.raw[8] = r[6];
+        mx.raw[9] = r[7];
+        mx.raw[10] = r[8];
+
+        mx
+    }
+
+    // 2D transforms, the matrix is treated as homogeneous 2D transform
+    pub fn translate(&mut self, x: T, y: T) {
+        self.raw[6] = self.raw[0] * x + self.raw[3] * y + self.raw[6];
+        self.raw[7] = self.raw[1] * x + self.raw[4] * y + self.raw[7];
+        self.raw[8] = self.raw[2] * x + self.raw[5] * y + self.raw[8];
+    }
+
+    pub fn scale(&mut self, x: T, y: T) {
+        self.raw[0] *= x;
+        self.raw[1] *= x;
+        self.raw[2] *= x;
+
+        self.raw[3] *= y;
+        self.raw[4] *= y;
+        self.raw[5] *= y;
+    }
+
+    // Columns are the basis vectors
+    pub fn from_columns(x: Vector3<T>, y: Vector3<T>, z: Vector3<T>) -> Matrix3x3<T> {
+        Matrix3x3 {
+            raw: [x.x, x.y, x.z, y.x, y.y, y.z, z.x, z.y, z.z],
+        }
+    }
+
+    pub fn column(&self, i: usize) -> Vector3<T> {
+        Vector3::new(self.raw[i * 3], self.raw[i * 3 + 1], self.raw[i * 3 + 2])
+    }
+
+    pub fn cast<U: Scalar>(self) -> Matrix3x3<U> {
+        let mut mx = Matrix3x3::new();
+        for i in 0..9 {
+            mx.raw[i] = U::from_f64(self.raw[i].to_f64());
+        }
+        mx
+    }
+}
+
+impl<T: Float> Matrix3x3<T> {
+    pub fn invert(self) -> Matrix3x3<T> {
+        let mut mx = Matrix3x3::new();
+
+        let a00 = self.raw[0];
+        let a01 = self.raw[1];
+        let a02 = self.raw[2];
+        let a10 = self.raw[3];
+        let a11 = self.raw[4];
+        let a12 = self.raw[5];
+        let a20 = self.raw[6];
+        let a21 = self.raw[7];
+        let a22 = self.raw[8];
+
+        let b01 = a22 * a11 - a12 * a21;
+        let b11 = -a22 * a10 + a12 * a20;
+        let b21 = a21 * a10 - a11 * a20;
+
+        // Calculate the determinant
+        let mut det = a00 * b01 + a01 * b11 + a02 * b21;
+
+        if det == T::ZERO {
+            return mx;
+        }
+        det = T::ONE / det;
+
+        mx.raw[0] = b01 * det;
+        mx.raw[1] = (-a22 * a01 + a02 * a21) * det;
+        mx.raw[2] = (a12 * a01 - a02 * a11) * det;
+        mx.raw[3] = b11 * det;
+        mx.raw[4] = (a22 * a00 - a02 * a20) * det;
+        mx.raw[5] = (-a12 * a00 + a02 * a10) * det;
+        mx.raw[6] = b21 * det;
+        mx.raw[7] = (-a21 * a00 + a01 * a20) * det;
+        mx.raw[8] = (a11 * a00 - a01 * a10) * det;
+
+        mx
+    }
+
+    pub fn rotate(&mut self, rad: T) {
+        let s = rad.sin();
+        let c = rad.cos();
+
+        let a00 = self.raw[0];
+        let a01 = self.raw[1];
+        let a02 = self.raw[2];
+        let a10 = self.raw[3];
+        let a11 = self.raw[4];
+        let a12 = self.raw[5];
+
+        self.raw[0] = c * a00 + s * a10;
+        self.raw[1] = c * a01 + s * a11;
+        self.raw[2] = c * a02 + s * a12;
+
+        self.raw[3] = c * a10 - s * a00;
+        self.raw[4] = c * a11 - s * a01;
+        self.raw[5] = c * a12 - s * a02;
+    }
+
+    pub fn from_quaternion(q: Quaternion<T>) -> Matrix3x3<T> {
+        Matrix3x3::from_matrix4x4(q.to_matrix4x4())
+    }
+
+    pub fn from_euler_order(v: Vector3<T>, order: EulerOrder) -> Matrix3x3<T> {
+        Matrix3x3::from_quaternion(Quaternion::from_euler_order(v, order))
+    }
+
+    // Expects a pure rotation matrix. Near the poles (gimbal lock) the first
+    // and last axes rotate around the same line, the whole rotation is put
+    // on one of them and the other is zero.
+    pub fn to_euler_order(self, order: EulerOrder) -> Vector3<T> {
+        let m = self.raw;
+        let (m11, m12, m13) = (m[0], m[3], m[6]);
+        let (m21, m22, m23) = (m[1], m[4], m[7]);
+        let (m31, m32, m33) = (m[2], m[5], m[8]);
+
+        let limit = T::from_f64(0.9999999);
+        let asin = |v: T| {
+            if v > T::ONE {
+                T::PI / T::from_f64(2.0)
+            } else if v < -T::ONE {
+                -T::PI / T::from_f64(2.0)
+            } else {
+                v.asin()
+            }
+        };
+        let mut out = Vector3::zero();
+
+        match order {
+            EulerOrder::XYZ => {
+                out.y = asin(m13);
+                if m13.abs() < limit {
+                    out.x = (-m23).atan2(m33);
+                    out.z = (-m12).atan2(m11);
+                } else {
+                    out.x = m32.atan2(m22);
+                }
+            }
+            EulerOrder::YXZ => {
+                out.x = asin(-m23);
+                if m23.abs() < limit {
+                    out.y = m13.atan2(m33);
+                    out.z = m21.atan2(m22);
+                } else {
+                    out.y = (-m31).atan2(m11);
+                }
+            }
+            EulerOrder::ZXY => {
+                out.x = asin(m32);
+                if m32.abs() < limit {
+                    out.y = (-m31).atan2(m33);
+                    out.z = (-m12).atan2(m22);
+                } else {
+                    out.z = m21.atan2(m11);
+                }
+            }
+            EulerOrder::ZYX => {
+                out.y = asin(-m31);
+                if m31.abs() < limit {
+                    out.x = m32.atan2(m33);
+                    out.z = m21.atan2(m11);
+                } else {
+                    out.z = (-m12).atan2(m22);
+                }
+            }
+            EulerOrder::YZX => {
+                out.z = asin(m21);
+                if m21.abs() < limit {
+                    out.x = (-m23).atan2(m22);
+                    out.y = (-m31).atan2(m11);
+                } else {
+                    out.y = m13.atan2(m33);
+                }
+            }
+            EulerOrder::XZY => {
+                out.z = asin(-m12);
+                if m12.abs() < limit {
+                    out.x = m32.atan2(m22);
+                    out.y = m13.atan2(m11);
+                } else {
+                    out.x = (-m23).atan2(m33);
+                }
+            }
+        }
+
+        out
+    }
+
+    // Covariance of the points around their centroid, divided by the point
+    // count. Its eigenvectors are the principal axes of the point set
+    pub fn covariance(points: &[Vector3<T>]) -> Matrix3x3<T> {
+        let mut mx = Matrix3x3 { raw: [T::ZERO; 9] };
+        if points.is_empty() {
+            return mx;
+        }
+
+        let c = Vector3::centroid(points);
+        let (mut xx, mut xy, mut xz) = (T::ZERO, T::ZERO, T::ZERO);
+        let (mut yy, mut yz, mut zz) = (T::ZERO, T::ZERO, T::ZERO);
+        for p in points.iter() {
+            let d = *p - c;
+            xx += d.x * d.x;
+            xy += d.x * d.y;
+            xz += d.x * d.z;
+            yy += d.y * d.y;
+            yz += d.y * d.z;
+            zz += d.z * d.z;
+        }
+
+        let n = T::from_f64(points.len() as f64);
+        mx.raw = [xx, xy, xz, xy, yy, yz, xz, yz, zz];
+        for v in mx.raw.iter_mut() {
+            *v /= n;
+        }
+        mx
+    }
+
+    // Eigenvalues and eigenvectors of a symmetric matrix with cyclic Jacobi
+    // rotations, only the lower triangle is read. Eigenvalues are sorted from
+    // largest to smallest, the columns of the returned matrix are the matching
+    // unit eigenvectors and form a right handed basis
+    pub fn symmetric_eigen(self) -> (Vector3<T>, Matrix3x3<T>) {
+        let r = self.raw;
+        // a[row][column]
+        let mut a = [[r[0], r[1], r[2]], [r[1], r[4], r[5]], [r[2], r[5], r[8]]];
+        let mut v = [
+            [T::ONE, T::ZERO, T::ZERO],
+            [T::ZERO, T::ONE, T::ZERO],
+            [T::ZERO, T::ZERO, T::ONE],
+        ];
+        let two = T::from_f64(2.0);
+
+        for _ in 0..32 {
+            let off = a[0][1] * a[0][1] + a[0][2] * a[0][2] + a[1][2] * a[1][2];
+            let diag = a[0][0] * a[0][0] + a[1][1] * a[1][1] + a[2][2] * a[2][2];
+            if off <= T::EPSILON * T::EPSILON * diag || off == T::ZERO {
+                break;
+            }
+
+            for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+                if a[p][q] == T::ZERO {
+                    continue;
+                }
+
+                // Rotation in the pq plane that zeroes a[p][q]
+                let theta = (a[q][q] - a[p][p]) / (two * a[p][q]);
+                let sign = if theta < T::ZERO { -T::ONE } else { T::ONE };
+                let t = sign / (theta.abs() + (theta * theta + T::ONE).sqrt());
+                let c = T::ONE / (t * t + T::ONE).sqrt();
+                let s = t * c;
+
+                for row in a.iter_mut().chain(v.iter_mut()) {
+                    let (kp, kq) = (row[p], row[q]);
+                    row[p] = c * kp - s * kq;
+                    row[q] = s * kp + c * kq;
+                }
+                let (row_p, row_q) = (a[p], a[q]);
+                a[p] = std::array::from_fn(|k| c * row_p[k] - s * row_q[k]);
+                a[q] = std::array::from_fn(|k| s * row_p[k] + c * row_q[k]);
+            }
+        }
+
+        let mut order = [0, 1, 2];
+        order.sort_by(|i, j| {
+            a[*j][*j]
+                .partial_cmp(&a[*i][*i])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        let values = Vector3::new(
+            a[order[0]][order[0]],
+            a[order[1]][order[1]],
+            a[order[2]][order[2]],
+        );
+        let axis = |i: usize| Vector3::new(v[0][i], v[1][i], v[2][i]);
+        let (x, y) = (axis(order[0]), axis(order[1]));
+        let mut z = axis(order[2]);
+        if x.cross(y).dot(z) < T::ZERO {
+            z = -z;
+        }
+
+        (values, Matrix3x3::from_columns(x, y, z))
+    }
+
+    // Split into rotation * stretch where the stretch is symmetric. The
+    // rotation is always proper, a mirrored matrix gets a negative stretch
+    // along its smallest axis instead. Singular matrices are fine
+    pub fn polar_decomposition(self) -> (Matrix3x3<T>, Matrix3x3<T>) {
+        let (values, v) = (self.transpose() * self).symmetric_eigen();
+        let sigma = |l: T| if l > T::ZERO { l.sqrt() } else { T::ZERO };
+        let (s0, s1) = (sigma(values.x), sigma(values.y));
+        let tol = T::EPSILON * T::from_f64(16.0) * s0;
+
+        if s0 <= T::ZERO {
+            return (Matrix3x3::new(), self);
+        }
+
+        // Left singular vectors, built from the right ones and completed with
+        // cross products where the matrix flattens space
+        let u0 = (v.column(0) * self) / s0;
+        let u1 = if s1 > tol {
+            (v.column(1) * self) / s1
+        } else {
+            let helper = if u0.x.abs() < T::from_f64(0.9) {
+                Vector3::new(T::ONE, T::ZERO, T::ZERO)
+            } else {
+                Vector3::new(T::ZERO, T::ONE, T::ZERO)
+            };
+            u0.cross(helper).normalize()
+        };
+        let u2 = u0.cross(u1);
+
+        let u = Matrix3x3::from_columns(u0, u1, u2);
+        let rotation = u * v.transpose();
+        let mut stretch = rotation.transpose() * self;
+
+        // Clean up rounding so the stretch is exactly symmetric
+        for (i, j) in [(1, 3), (2, 6), (5, 7)] {
+            let m = (stretch.raw[i] + stretch.raw[j]) / T::from_f64(2.0);
+            stretch.raw[i] = m;
+            stretch.raw[j] = m;
+        }
+
+        (rotation, stretch)
+    }
+
+    // Expects a pure rotation matrix
+    pub fn to_quaternion(self) -> Quaternion<T> {
+        let m = self.raw;
+        let two = T::from_f64(2.0);
+        let quarter = T::from_f64(0.25);
+        let trace = m[0] + m[4] + m[8];
+        let mut out = Quaternion::identity();
+
+        if trace > T::ZERO {
+            let s = (trace + T::ONE).sqrt() * two;
+            out.w = quarter * s;
+            out.x = (m[5] - m[7]) / s;
+            out.y = (m[6] - m[2]) / s;
+            out.z = (m[1] - m[3]) / s;
+        } else if m[0] > m[4] && m[0] > m[8] {
+            let s = (T::ONE + m[0] - m[4] - m[8]).sqrt() * two;
+            out.w = (m[5] - m[7]) / s;
+            out.x = quarter * s;
+            out.y = (m[1] + m[3]) / s;
+            out.z = (m[6] + m[2]) / s;
+        } else if m[4] > m[8] {
+            let s = (T::ONE + m[4] - m[0] - m[8]).sqrt() * two;
+            out.w = (m[6] - m[2]) / s;
+            out.x = (m[1] + m[3]) / s;
+            out.y = quarter * s;
+            out.z = (m[5] + m[7]) / s;
+        } else {
+            let s = (T::ONE + m[8] - m[0] - m[4]).sqrt() * two;
+            out.w = (m[1] - m[3]) / s;
+            out.x = (m[6] + m[2]) / s;
+            out.y = (m[5] + m[7]) / s;
+            out.z = quarter * s;
+        }
+
+        out
+    }
+}
+
+impl_bytes!(Matrix3x3, 9);
+
+impl<T> From<[T; 9]> for Matrix3x3<T> {
+    fn from(raw: [T; 9]) -> Matrix3x3<T> {
+        Matrix3x3 { raw }
+    }
+}
+
+impl<T> From<Matrix3x3<T>> for [T; 9] {
+    fn from(m: Matrix3x3<T>) -> [T; 9] {
+        m.raw
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde_array!(Matrix3x3, 9);
+
+impl From<Matrix3x3<f32>> for Matrix3x3<f64> {
+    fn from(mx: Matrix3x3<f32>) -> Matrix3x3<f64> {
+        mx.cast()
+    }
+}
@@ -1,14 +1,32 @@
-use crate::math::la::quaternion::Quaternion;
+#[cfg(feature = "serde")]
+use crate::math::la::macros::impl_serde_array;
+use crate::math::la::macros::{impl_bytes, impl_matrix_ops};
+use crate::math::la::matrix3::Matrix3x3;
+use crate::math::la::quaternion::{EulerOrder, Quaternion};
+use crate::math::la::simd::Mat4Kernels;
 use crate::math::la::vector3::Vector3;
+use crate::math::number::scalar::{Float, Scalar};
 use std::fmt;
 use std::ops;
 
-#[derive(Copy, Clone, Default, Debug)]
-pub struct Matrix4x4 {
-    pub raw: [f32; 16],
+// Depth range of clip space produced by the projection builders
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClipDepth {
+    // WebGL / OpenGL
+    #[default]
+    NegativeOneToOne,
+    // WebGPU / Vulkan / Metal
+    ZeroToOne,
 }
 
-impl fmt::Display for Matrix4x4 {
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+#[repr(C)]
+pub struct Matrix4x4<T = f32> {
+    pub raw: [T; 16],
+}
+
+impl<T: fmt::Display> fmt::Display for Matrix4x4<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
@@ -33,28 +51,91 @@ impl fmt::Display for Matrix4x4 {
     }
 }
 
-impl ops::Mul<Matrix4x4> for Matrix4x4 {
-    type Output = Matrix4x4;
+impl_matrix_ops!(Matrix4x4, 4);
+
+impl<T: Mat4Kernels> ops::Mul<Matrix4x4<T>> for Matrix4x4<T> {
+    type Output = Matrix4x4<T>;
+
+    fn mul(self, b: Matrix4x4<T>) -> Matrix4x4<T> {
+        T::mat4_mul(&self, &b)
+    }
+}
+
+impl<T: Mat4Kernels> ops::MulAssign<Matrix4x4<T>> for Matrix4x4<T> {
+    fn mul_assign(&mut self, b: Matrix4x4<T>) {
+        *self = T::mat4_mul(self, &b);
+    }
+}
+
+impl<T: Scalar> Matrix4x4<T> {
+    pub const fn new() -> Self {
+        Matrix4x4 {
+            raw: [
+                T::ONE,
+                T::ZERO,
+                T::ZERO,
+                T::ZERO,
+                T::ZERO,
+                T::ONE,
+                T::ZERO,
+                T::ZERO,
+                T::ZERO,
+                T::ZERO,
+                T::ONE,
+                T::ZERO,
+                T::ZERO,
+                T::ZERO,
+                T::ZERO,
+                T::ONE,
+            ],
+        }
+    }
 
-    fn mul(self, b: Matrix4x4) -> Matrix4x4 {
+    pub fn identity(&mut self) {
+        self.raw[0] = T::ONE;
+        self.raw[1] = T::ZERO;
+        self.raw[2] = T::ZERO;
+        self.raw[3] = T::ZERO;
+
+        self.raw[4] = T::ZERO;
+        self.raw[5] = T::ONE;
+        self.raw[6] = T::ZERO;
+        self.raw[7] = T::ZERO;
+
+        self.raw[8] = T::ZERO;
+        self.raw[9] = T::ZERO;
+        self.raw[10] = T::ONE;
+        self.raw[11] = T::ZERO;
+
+        self.raw[12] = T::ZERO;
+        self.raw[13] = T::ZERO;
+        self.raw[14] = T::ZERO;
+        self.raw[15] = T::ONE;
+    }
+
+    // Portable version of `*`, used when there is no SIMD path for T. Public
+    // only so tests can compare the SIMD kernels against it
+    #[doc(hidden)]
+    pub fn mul_fallback(&self, b: &Matrix4x4<T>) -> Matrix4x4<T> {
+        let a = self;
         let mut mx = Matrix4x4::default();
 
-        let a00 = self.raw[0];
-        let a01 = self.raw[1];
-        let a02 = self.raw[2];
-        let a03 = self.raw[3];
-        let a10 = self.raw[4];
-        let a11 = self.raw[5];
-        let a12 = self.raw[6];
-        let a13 = self.raw[7];
-        let a20 = self.raw[8];
-        let a21 = self.raw[9];
-        let a22 = self.raw[10];
-        let a23 = self.raw[11];
-        let a30 = self.raw[12];
-        let a31 = self.raw[13];
-        let a32 = self.raw[14];
-        let a33 = self.raw[15];
+        let a00 = a.raw[0];
+        let a01 = a.raw[1];
+        let a02 = a.raw[2];
+        let a03 = a.raw[3];
+        let a10 = a.raw[4];
+        let a11 = a.raw[5];
+        let a12 = a.raw[6];
+        let a13 = a.raw[7];
+        let a20 = a.raw[8];
+        let a21 = a.raw[9];
+        let a22 = a.raw[10];
+        let a23 = a.raw[11];
+        let a30 = a.raw[12];
+        let a31 = a.raw[13];
+        let a32 = a.raw[14];
+        let a33 = a.raw[15];
 
         // Cache only the current line of the second matrix
         let mut b0 = b.raw[0];
@@ -95,12 +176,20 @@ impl ops::Mul<Matrix4x4> for Matrix4x4 {
 
         mx
     }
-}
 
-impl ops::MulAssign<Matrix4x4> for Matrix4x4 {
-    fn mul_assign(&mut self, b: Matrix4x4) {
-        let mut mx = Matrix4x4::default();
+    pub fn cast<U: Scalar>(self) -> Matrix4x4<U> {
+        let mut mx = Matrix4x4::new();
+        for i in 0..16 {
+            mx.raw[i] = U::from_f64(self.raw[i].to_f64());
+        }
+        mx
+    }
 
+    pub fn translate_vec3(&mut self, v: Vector3<T>) {
+        self.translate(v.x, v.y, v.z);
+    }
+
+    pub fn translate(&mut self, x: T, y: T, z: T) {
         let a00 = self.raw[0];
         let a01 = self.raw[1];
         let a02 = self.raw[2];
@@ -113,84 +202,72 @@ impl ops::MulAssign<Matrix4x4> for Matrix4x4 {
         let a21 = self.raw[9];
         let a22 = self.raw[10];
         let a23 = self.raw[11];
-        let a30 = self.raw[12];
-        let a31 = self.raw[13];
-        let a32 = self.raw[14];
-        let a33 = self.raw[15];
 
-        // Cache only the current line of the second matrix
-        let mut b0 = b.raw[0];
-        let mut b1 = b.raw[1];
-        let mut b2 = b.raw[2];
-        let mut b3 = b.raw[3];
-        mx.raw[0] = b0 * a00 + b1 * a10 + b2 * a20 + b3 * a30;
-        mx.raw[1] = b0 * a01 + b1 * a11 + b2 * a21 + b3 * a31;
-        mx.raw[2] = b0 * a02 + b1 * a12 + b2 * a22 + b3 * a32;
-        mx.raw[3] = b0 * a03 + b1 * a13 + b2 * a23 + b3 * a33;
+        self.raw[0] = a00;
+        self.raw[1] = a01;
+        self.raw[2] = a02;
+        self.raw[3] = a03;
+        self.raw[4] = a10;
+        self.raw[5] = a11;
+        self.raw[6] = a12;
+        self.raw[7] = a13;
+        self.raw[8] = a20;
+        self.raw[9] = a21;
+        self.raw[10] = a22;
+        self.raw[11] = a23;
 
-        b0 = b.raw[4];
-        b1 = b.raw[5];
-        b2 = b.raw[6];
-        b3 = b.raw[7];
-        mx.raw[4] = b0 * a00 + b1 * a10 + b2 * a20 + b3 * a30;
-        mx.raw[5] = b0 * a01 + b1 * a11 + b2 * a21 + b3 * a31;
-        mx.raw[6] = b0 * a02 + b1 * a12 + b2 * a22 + b3 * a32;
-        mx.raw[7] = b0 * a03 + b1 * a13 + b2 * a23 + b3 * a33;
+        self.raw[12] = a00 * x + a10 * y + a20 * z + self.raw[12];
+        self.raw[13] = a01 * x + a11 * y + a21 * z + self.raw[13];
+        self.raw[14] = a02 * x + a12 * y + a22 * z + self.raw[14];
+        self.raw[15] = a03 * x + a13 * y + a23 * z + self.raw[15];
+    }
 
-        b0 = b.raw[8];
-        b1 = b.raw[9];
-        b2 = b.raw[10];
-        b3 = b.raw[11];
-        mx.raw[8] = b0 * a00 + b1 * a10 + b2 * a20 + b3 * a30;
-        mx.raw[9] = b0 * a01 + b1 * a11 + b2 * a21 + b3 * a31;
-        mx.raw[10] = b0 * a02 + b1 * a12 + b2 * a22 + b3 * a32;
-        mx.raw[11] = b0 * a03 + b1 * a13 + b2 * a23 + b3 * a33;
+    pub fn scale(&mut self, x: T, y: T, z: T) {
+        self.raw[0] *= x;
+        self.raw[1] *= x;
+        self.raw[2] *= x;
+        self.raw[3] *= x;
 
-        b0 = b.raw[12];
-        b1 = b.raw[13];
-        b2 = b.raw[14];
-        b3 = b.raw[15];
-        mx.raw[12] = b0 * a00 + b1 * a10 + b2 * a20 + b3 * a30;
-        mx.raw[13] = b0 * a01 + b1 * a11 + b2 * a21 + b3 * a31;
-        mx.raw[14] = b0 * a02 + b1 * a12 + b2 * a22 + b3 * a32;
-        mx.raw[15] = b0 * a03 + b1 * a13 + b2 * a23 + b3 * a33;
+        self.raw[4] *= y;
+        self.raw[5] *= y;
+        self.raw[6] *= y;
+        self.raw[7] *= y;
 
-        *self = mx
+        self.raw[8] *= z;
+        self.raw[9] *= z;
+        self.raw[10] *= z;
+        self.raw[11] *= z;
     }
-}
 
-impl Matrix4x4 {
-    pub const fn new() -> Self {
-        Matrix4x4 {
-            raw: [
-                1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 1.0,
-            ],
+    pub fn get_position(&self) -> Vector3<T> {
+        Vector3 {
+            x: self.raw[12],
+            y: self.raw[13],
+            z: self.raw[14],
         }
     }
+}
 
-    pub fn identity(&mut self) {
-        self.raw[0] = 1.0;
-        self.raw[1] = 0.0;
-        self.raw[2] = 0.0;
-        self.raw[3] = 0.0;
-
-        self.raw[4] = 0.0;
-        self.raw[5] = 1.0;
-        self.raw[6] = 0.0;
-        self.raw[7] = 0.0;
-
-        self.raw[8] = 0.0;
-        self.raw[9] = 0.0;
-        self.raw[10] = 1.0;
-        self.raw[11] = 0.0;
+impl<T: Float + Mat4Kernels> Matrix4x4<T> {
+    pub fn invert(self) -> Matrix4x4<T> {
+        T::mat4_invert(&self)
+    }
 
-        self.raw[12] = 0.0;
-        self.raw[13] = 0.0;
-        self.raw[14] = 0.0;
-        self.raw[15] = 1.0;
+    pub fn rotate_quaternion(&mut self, q: Quaternion<T>) {
+        /*let e = q.to_euler();
+        self.rotate_x(e.x);
+        self.rotate_y(e.y);
+        self.rotate_z(e.z);*/
+        let mx = q.to_matrix4x4();
+        *self *= mx;
     }
+}
 
-    pub fn invert(self) -> Matrix4x4 {
+impl<T: Float> Matrix4x4<T> {
+    // Portable version of invert, used when there is no SIMD path for T.
+    // Public only for tests, same as mul_fallback
+    #[doc(hidden)]
+    pub fn invert_fallback(&self) -> Matrix4x4<T> {
         let mut mx = Matrix4x4::new();
 
         let a00 = self.raw[0];
@@ -226,10 +303,10 @@ impl Matrix4x4 {
         // Calculate the determinant
         let mut det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
 
-        if det == 0.0 {
+        if det == T::ZERO {
             return mx;
         }
-        det = 1.0 / det;
+        det = T::ONE / det;
 
         mx.raw[0] = (a11 * b11 - a12 * b10 + a13 * b09) * det;
         mx.raw[1] = (a02 * b10 - a01 * b11 - a03 * b09) * det;
@@ -248,56 +325,15 @@ impl Matrix4x4 {
         mx.raw[14] = (a31 * b01 - a30 * b03 - a32 * b00) * det;
         mx.raw[15] = (a20 * b03 - a21 * b01 + a22 * b00) * det;
 
-        return mx;
-    }
-
-    pub fn translate_vec3(&mut self, v: Vector3) {
-        self.translate(v.x, v.y, v.z);
-    }
-
-    pub fn translate(&mut self, x: f32, y: f32, z: f32) {
-        let a00 = self.raw[0];
-        let a01 = self.raw[1];
-        let a02 = self.raw[2];
-        let a03 = self.raw[3];
-        let a10 = self.raw[4];
-        let a11 = self.raw[5];
-        let a12 = self.raw[6];
-        let a13 = self.raw[7];
-        let a20 = self.raw[8];
-        let a21 = self.raw[9];
-        let a22 = self.raw[10];
-        let a23 = self.raw[11];
-
-        self.raw[0] = a00;
-        self.raw[1] = a01;
-        self.raw[2] = a02;
-        self.raw[3] = a03;
-        self.raw[4] = a10;
-        self.raw[5] = a11;
-        self.raw[6] = a12;
-        self.raw[7] = a13;
-        self.raw[8] = a20;
-        self.raw[9] = a21;
-        self.raw[10] = a22;
-        self.raw[11] = a23;
-
-        self.raw[12] = a00 * x + a10 * y + a20 * z + self.raw[12];
-        self.raw[13] = a01 * x + a11 * y + a21 * z + self.raw[13];
-        self.raw[14] = a02 * x + a12 * y + a22 * z + self.raw[14];
-        self.raw[15] = a03 * x + a13 * y + a23 * z + self.raw[15];
+        mx
     }
 
-    pub fn rotate_quaternion(&mut self, q: Quaternion) {
-        /*let e = q.to_euler();
-        self.rotate_x(e.x);
-        self.rotate_y(e.y);
-        self.rotate_z(e.z);*/
-        let mx = q.to_matrix4x4();
-        *self *= mx;
+    // Inverse transpose of the upper 3x3, transforms normals under non uniform scale
+    pub fn normal_matrix(&self) -> Matrix3x3<T> {
+        Matrix3x3::from_matrix4x4(*self).invert().transpose()
     }
 
-    pub fn rotate_x(&mut self, rad: f32) {
+    pub fn rotate_x(&mut self, rad: T) {
         let s = rad.sin();
         let c = rad.cos();
 
@@ -321,7 +357,7 @@ impl Matrix4x4 {
         self.raw[11] = a23 * c - a13 * s;
     }
 
-    pub fn rotate_y(&mut self, rad: f32) {
+    pub fn rotate_y(&mut self, rad: T) {
         let s = rad.sin();
         let c = rad.cos();
 
@@ -345,7 +381,7 @@ impl Matrix4x4 {
         self.raw[11] = a03 * s + a23 * c;
     }
 
-    pub fn rotate_z(&mut self, rad: f32) {
+    pub fn rotate_z(&mut self, rad: T) {
         let s = rad.sin();
         let c = rad.cos();
 
@@ -369,47 +405,215 @@ impl Matrix4x4 {
         self.raw[7] = a13 * c - a03 * s;
     }
 
-    pub fn scale(&mut self, x: f32, y: f32, z: f32) {
-        self.raw[0] *= x;
-        self.raw[1] *= x;
-        self.raw[2] *= x;
-        self.raw[3] *= x;
+    pub fn perspective(&mut self, fov: T, aspect: T, near: T, far: T) {
+        self.perspective_depth(fov, aspect, near, far, ClipDepth::NegativeOneToOne);
+    }
 
-        self.raw[4] *= y;
-        self.raw[5] *= y;
-        self.raw[6] *= y;
-        self.raw[7] *= y;
+    pub fn perspective_depth(&mut self, fov: T, aspect: T, near: T, far: T, depth: ClipDepth) {
+        self.perspective_base(fov, aspect);
+
+        let nf = T::ONE / (near - far);
+        match depth {
+            ClipDepth::NegativeOneToOne => {
+                self.raw[10] = (far + near) * nf;
+                self.raw[14] = T::from_f64(2.0) * far * near * nf;
+            }
+            ClipDepth::ZeroToOne => {
+                self.raw[10] = far * nf;
+                self.raw[14] = far * near * nf;
+            }
+        }
+    }
 
-        self.raw[8] *= z;
-        self.raw[9] *= z;
-        self.raw[10] *= z;
-        self.raw[11] *= z;
+    // Far plane at infinity
+    pub fn perspective_infinite(&mut self, fov: T, aspect: T, near: T, depth: ClipDepth) {
+        self.perspective_base(fov, aspect);
+
+        self.raw[10] = -T::ONE;
+        self.raw[14] = match depth {
+            ClipDepth::NegativeOneToOne => -T::from_f64(2.0) * near,
+            ClipDepth::ZeroToOne => -near,
+        };
+    }
+
+    // Near plane maps to 1 and far plane to the lowest depth, spreads float
+    // precision evenly over the distance. Use with a "greater" depth test.
+    pub fn perspective_reversed(&mut self, fov: T, aspect: T, near: T, far: T, depth: ClipDepth) {
+        self.perspective_base(fov, aspect);
+
+        let range = T::ONE / (far - near);
+        match depth {
+            ClipDepth::NegativeOneToOne => {
+                self.raw[10] = (far + near) * range;
+                self.raw[14] = T::from_f64(2.0) * far * near * range;
+            }
+            ClipDepth::ZeroToOne => {
+                self.raw[10] = near * range;
+                self.raw[14] = far * near * range;
+            }
+        }
+    }
+
+    pub fn perspective_infinite_reversed(&mut self, fov: T, aspect: T, near: T, depth: ClipDepth) {
+        self.perspective_base(fov, aspect);
+
+        match depth {
+            ClipDepth::NegativeOneToOne => {
+                self.raw[10] = T::ONE;
+                self.raw[14] = T::from_f64(2.0) * near;
+            }
+            ClipDepth::ZeroToOne => {
+                self.raw[10] = T::ZERO;
+                self.raw[14] = near;
+            }
+        }
     }
 
-    pub fn perspective(&mut self, fov: f32, aspect: f32, near: f32, far: f32) {
-        let f = 1.0 / (fov / 2.0).tan();
+    fn perspective_base(&mut self, fov: T, aspect: T) {
+        let f = T::ONE / (fov / T::from_f64(2.0)).tan();
 
         self.raw[0] = f / aspect;
-        self.raw[1] = 0.0;
-        self.raw[2] = 0.0;
-        self.raw[3] = 0.0;
-        self.raw[4] = 0.0;
+        self.raw[1] = T::ZERO;
+        self.raw[2] = T::ZERO;
+        self.raw[3] = T::ZERO;
+        self.raw[4] = T::ZERO;
         self.raw[5] = f;
-        self.raw[6] = 0.0;
-        self.raw[7] = 0.0;
-        self.raw[8] = 0.0;
-        self.raw[9] = 0.0;
-        self.raw[11] = -1.0;
-        self.raw[12] = 0.0;
-        self.raw[13] = 0.0;
-        self.raw[15] = 0.0;
+        self.raw[6] = T::ZERO;
+        self.raw[7] = T::ZERO;
+        self.raw[8] = T::ZERO;
+        self.raw[9] = T::ZERO;
+        self.raw[11] = -T::ONE;
+        self.raw[12] = T::ZERO;
+        self.raw[13] = T::ZERO;
+        self.raw[15] = T::ZERO;
+    }
+
+    // Off center perspective projection
+    #[allow(clippy::too_many_arguments)]
+    pub fn frustum(
+        &mut self,
+        left: T,
+        right: T,
+        bottom: T,
+        top: T,
+        near: T,
+        far: T,
+        depth: ClipDepth,
+    ) {
+        let two = T::from_f64(2.0);
+        let rl = T::ONE / (right - left);
+        let tb = T::ONE / (top - bottom);
+        let nf = T::ONE / (near - far);
+
+        self.raw[0] = two * near * rl;
+        self.raw[1] = T::ZERO;
+        self.raw[2] = T::ZERO;
+        self.raw[3] = T::ZERO;
+        self.raw[4] = T::ZERO;
+        self.raw[5] = two * near * tb;
+        self.raw[6] = T::ZERO;
+        self.raw[7] = T::ZERO;
+        self.raw[8] = (right + left) * rl;
+        self.raw[9] = (top + bottom) * tb;
+        self.raw[11] = -T::ONE;
+        self.raw[12] = T::ZERO;
+        self.raw[13] = T::ZERO;
+        self.raw[15] = T::ZERO;
+
+        match depth {
+            ClipDepth::NegativeOneToOne => {
+                self.raw[10] = (far + near) * nf;
+                self.raw[14] = two * far * near * nf;
+            }
+            ClipDepth::ZeroToOne => {
+                self.raw[10] = far * nf;
+                self.raw[14] = far * near * nf;
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn orthographic(
+        &mut self,
+        left: T,
+        right: T,
+        bottom: T,
+        top: T,
+        near: T,
+        far: T,
+        depth: ClipDepth,
+    ) {
+        let two = T::from_f64(2.0);
+        let lr = T::ONE / (left - right);
+        let bt = T::ONE / (bottom - top);
+        let nf = T::ONE / (near - far);
+
+        self.raw[0] = -two * lr;
+        self.raw[1] = T::ZERO;
+        self.raw[2] = T::ZERO;
+        self.raw[3] = T::ZERO;
+        self.raw[4] = T::ZERO;
+        self.raw[5] = -two * bt;
+        self.raw[6] = T::ZERO;
+        self.raw[7] = T::ZERO;
+        self.raw[8] = T::ZERO;
+        self.raw[9] = T::ZERO;
+        self.raw[11] = T::ZERO;
+        self.raw[12] = (left + right) * lr;
+        self.raw[13] = (top + bottom) * bt;
+        self.raw[15] = T::ONE;
+
+        match depth {
+            ClipDepth::NegativeOneToOne => {
+                self.raw[10] = two * nf;
+                self.raw[14] = (far + near) * nf;
+            }
+            ClipDepth::ZeroToOne => {
+                self.raw[10] = nf;
+                self.raw[14] = near * nf;
+            }
+        }
+    }
+
+    // Right handed view matrix, camera looks down -Z
+    pub fn look_at(&mut self, eye: Vector3<T>, target: Vector3<T>, up: Vector3<T>) {
+        self.look_at_rh(eye, target, up);
+    }
+
+    pub fn look_at_rh(&mut self, eye: Vector3<T>, target: Vector3<T>, up: Vector3<T>) {
+        self.set_view(eye, eye - target, up);
+    }
+
+    // Left handed view matrix, camera looks down +Z
+    pub fn look_at_lh(&mut self, eye: Vector3<T>, target: Vector3<T>, up: Vector3<T>) {
+        self.set_view(eye, target - eye, up);
+    }
+
+    fn set_view(&mut self, eye: Vector3<T>, z: Vector3<T>, up: Vector3<T>) {
+        self.identity();
+        if z.length() == T::ZERO {
+            return;
+        }
 
-        let nf = 1.0 / (near - far);
-        self.raw[10] = (far + near) * nf;
-        self.raw[14] = 2.0 * far * near * nf;
+        let z = z.normalize();
+        let x = up.cross(z).normalize();
+        let y = z.cross(x);
+
+        self.raw[0] = x.x;
+        self.raw[1] = y.x;
+        self.raw[2] = z.x;
+        self.raw[4] = x.y;
+        self.raw[5] = y.y;
+        self.raw[6] = z.y;
+        self.raw[8] = x.z;
+        self.raw[9] = y.z;
+        self.raw[10] = z.z;
+        self.raw[12] = -x.dot(eye);
+        self.raw[13] = -y.dot(eye);
+        self.raw[14] = -z.dot(eye);
     }
 
-    pub fn get_scale(&self) -> Vector3 {
+    pub fn get_scale(&self) -> Vector3<T> {
         let m11 = self.raw[0];
         let m12 = self.raw[1];
         let m13 = self.raw[2];
@@ -427,95 +631,103 @@ impl Matrix4x4 {
         }
     }
 
-    pub fn get_position(&self) -> Vector3 {
-        Vector3 {
-            x: self.raw[12],
-            y: self.raw[13],
-            z: self.raw[14],
-        }
+    pub fn get_rotation(&self) -> Quaternion<T> {
+        self.decompose().1
     }
 
-    pub fn get_rotation(&self) -> Quaternion {
-        let scaling = self.get_scale();
-
-        let is1 = 1.0 / scaling.x;
-        let is2 = 1.0 / scaling.y;
-        let is3 = 1.0 / scaling.z;
-
-        let sm11 = self.raw[0] * is1;
-        let sm12 = self.raw[1] * is2;
-        let sm13 = self.raw[2] * is3;
-        let sm21 = self.raw[4] * is1;
-        let sm22 = self.raw[5] * is2;
-        let sm23 = self.raw[6] * is3;
-        let sm31 = self.raw[8] * is1;
-        let sm32 = self.raw[9] * is2;
-        let sm33 = self.raw[10] * is3;
-
-        let trace = sm11 + sm22 + sm33;
-        let mut s = 0.0;
-        let mut out = Quaternion::identity();
-
-        if trace > 0.0 {
-            s = (trace + 1.0).sqrt() * 2.0;
-            out.x = (sm23 - sm32) / s;
-            out.y = (sm31 - sm13) / s;
-            out.z = (sm12 - sm21) / s;
-            out.w = 0.25 * s;
-            /*
-            out[0] = (sm23 - sm32) / S;
-            out[1] = (sm31 - sm13) / S;
-            out[2] = (sm12 - sm21) / S;
-            out[3] = 0.25 * S;
-            */
-        } else if sm11 > sm22 && sm11 > sm33 {
-            s = (1.0 + sm11 - sm22 - sm33).sqrt() * 2.0;
-            out.x = 0.25 * s;
-            out.y = (sm12 + sm21) / s;
-            out.z = (sm31 + sm13) / s;
-            out.w = (sm23 - sm32) / s;
-            /*
-            out[0] = 0.25 * S;
-            out[1] = (sm12 + sm21) / S;
-            out[2] = (sm31 + sm13) / S;
-            out[3] = (sm23 - sm32) / S;
-            */
-        } else if sm22 > sm33 {
-            s = (1.0 + sm22 - sm11 - sm33).sqrt() * 2.0;
-            out.x = (sm12 + sm21) / s;
-            out.y = 0.25 * s;
-            out.z = (sm23 + sm32) / s;
-            out.w = (sm31 - sm13) / s;
-            /*
-            out[0] = (sm12 + sm21) / S;
-            out[1] = 0.25 * S;
-            out[2] = (sm23 + sm32) / S;
-            out[3] = (sm31 - sm13) / S;
-            */
+    // Splits an affine matrix into translation, rotation and scale so that
+    // from_trs(decompose()) gives the matrix back. A mirrored matrix gets a
+    // negative x scale. Shear can't be represented, the basis is
+    // orthogonalized (Gram-Schmidt) and the shear part is dropped.
+    pub fn decompose(&self) -> (Vector3<T>, Quaternion<T>, Vector3<T>) {
+        let translation = self.get_position();
+
+        let c0 = Vector3::new(self.raw[0], self.raw[1], self.raw[2]);
+        let c1 = Vector3::new(self.raw[4], self.raw[5], self.raw[6]);
+        let c2 = Vector3::new(self.raw[8], self.raw[9], self.raw[10]);
+
+        let mut scale = Vector3::new(c0.length(), T::ZERO, T::ZERO);
+        let x = if scale.x == T::ZERO {
+            Vector3::new(T::ONE, T::ZERO, T::ZERO)
+        } else {
+            c0.scale(T::ONE / scale.x)
+        };
+
+        let c1 = c1 - x.scale(x.dot(c1));
+        scale.y = c1.length();
+        let y = if scale.y == T::ZERO {
+            // Any axis perpendicular to x
+            let helper = if x.x.abs() < T::from_f64(0.9) {
+                Vector3::new(T::ONE, T::ZERO, T::ZERO)
+            } else {
+                Vector3::new(T::ZERO, T::ONE, T::ZERO)
+            };
+            x.cross(helper).cross(x).normalize()
         } else {
-            s = (1.0 + sm33 - sm11 - sm22).sqrt() * 2.0;
-            /*
-            out[0] = (sm31 + sm13) / S;
-            out[1] = (sm23 + sm32) / S;
-            out[2] = 0.25 * S;
-            out[3] = (sm12 - sm21) / S;
-            */
-            out.x = (sm31 + sm13) / s;
-            out.y = (sm23 + sm32) / s;
-            out.z = 0.25 * s;
-            out.w = (sm12 - sm21) / s;
+            c1.scale(T::ONE / scale.y)
+        };
+
+        let c2 = c2 - x.scale(x.dot(c2)) - y.scale(y.dot(c2));
+        scale.z = c2.length();
+        let mut z = x.cross(y);
+        if z.dot(c2) < T::ZERO {
+            // Mirrored basis, move the reflection to the x axis
+            scale.x = -scale.x;
+            z = z.scale(-T::ONE);
         }
+        let x = y.cross(z);
 
-        return out;
+        let rotation = Matrix3x3 {
+            raw: [x.x, x.y, x.z, y.x, y.y, y.z, z.x, z.y, z.z],
+        }
+        .to_quaternion();
+
+        (translation, rotation, scale)
     }
 
-    pub fn from_bytes(b: &[u8]) -> Matrix4x4 {
-        let mut o = Matrix4x4::new();
-        let mut offset = 0;
-        for i in 0..16 {
-            o.raw[i] = f32::from_le_bytes([b[offset], b[offset + 1], b[offset + 2], b[offset + 3]]);
-            offset += 4;
-        }
-        o
+    pub fn from_euler_order(v: Vector3<T>, order: EulerOrder) -> Self {
+        Quaternion::from_euler_order(v, order).to_matrix4x4()
+    }
+
+    // Rotation part only, scale is removed first
+    pub fn to_euler_order(&self, order: EulerOrder) -> Vector3<T> {
+        self.get_rotation().to_euler_order(order)
+    }
+
+    pub fn from_trs(translation: Vector3<T>, rotation: Quaternion<T>, scale: Vector3<T>) -> Self {
+        let mut mx = rotation.to_matrix4x4();
+        mx.scale(scale.x, scale.y, scale.z);
+        mx.raw[12] = translation.x;
+        mx.raw[13] = translation.y;
+        mx.raw[14] = translation.z;
+        mx
+    }
+}
+
+impl_bytes!(Matrix4x4, 16);
+
+impl<T> From<[T; 16]> for Matrix4x4<T> {
+    fn from(raw: [T; 16]) -> Matrix4x4<T> {
+        Matrix4x4 { raw }
     }
 }
+
+impl<T> From<Matrix4x4<T>> for [T; 16] {
+    fn from(m: Matrix4x4<T>) -> [T; 16] {
+        m.raw
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde_array!(Matrix4x4, 16);
+
+impl From<Matrix4x4<f32>> for Matrix4x4<f64> {
+    fn from(mx: Matrix4x4<f32>) -> Matrix4x4<f64> {
+        mx.cast()
+    }
+}
+
+// Applies mx on every point, same as `*p = *p * mx` but uses SIMD for f32
+pub fn transform_points<T: Float + Mat4Kernels>(points: &mut [Vector3<T>], mx: &Matrix4x4<T>) {
+    T::transform_points(points, mx);
+}
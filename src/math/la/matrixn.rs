@@ -0,0 +1,876 @@
+use crate::math::la::matrix3::Matrix3x3;
+use crate::math::la::matrix4::Matrix4x4;
+use crate::math::la::vector3::Vector3;
+use crate::math::la::vector4::Vector4;
+use crate::math::number::scalar::{Float, Scalar};
+use std::{fmt, ops};
+
+// Heap backed rows x cols matrix for fitting and solving. Storage is
+// column-major like the fixed size matrices, but indexing is m[(row, column)]
+// as usual for linear systems
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "MatrixNData<T>"))]
+pub struct MatrixN<T = f32> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+// Unchecked serialized form, the shape must match the data before it
+// becomes a MatrixN
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct MatrixNData<T> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> TryFrom<MatrixNData<T>> for MatrixN<T> {
+    type Error = String;
+
+    fn try_from(m: MatrixNData<T>) -> Result<MatrixN<T>, String> {
+        if m.rows.checked_mul(m.cols) != Some(m.data.len()) {
+            return Err(format!(
+                "MatrixN {}x{} needs {} values, got {}",
+                m.rows,
+                m.cols,
+                m.rows.saturating_mul(m.cols),
+                m.data.len()
+            ));
+        }
+        Ok(MatrixN {
+            rows: m.rows,
+            cols: m.cols,
+            data: m.data,
+        })
+    }
+}
+
+// Print row by row
+impl<T: fmt::Display> fmt::Display for MatrixN<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "MatrixN {}x{}(", self.rows, self.cols)?;
+        for r in 0..self.rows {
+            write!(f, "\t")?;
+            for c in 0..self.cols {
+                if c > 0 {
+                    write!(f, ", ")?;
+                }
+                write!(f, "{}", self.data[c * self.rows + r])?;
+            }
+            writeln!(f)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl<T> ops::Index<(usize, usize)> for MatrixN<T> {
+    type Output = T;
+
+    fn index(&self, (r, c): (usize, usize)) -> &T {
+        assert!(
+            r < self.rows && c < self.cols,
+            "index ({}, {}) out of range",
+            r,
+            c
+        );
+        &self.data[c * self.rows + r]
+    }
+}
+
+impl<T> ops::IndexMut<(usize, usize)> for MatrixN<T> {
+    fn index_mut(&mut self, (r, c): (usize, usize)) -> &mut T {
+        assert!(
+            r < self.rows && c < self.cols,
+            "index ({}, {}) out of range",
+            r,
+            c
+        );
+        &mut self.data[c * self.rows + r]
+    }
+}
+
+// Add &MatrixN + &MatrixN
+impl<T: Scalar> ops::Add<&MatrixN<T>> for &MatrixN<T> {
+    type Output = MatrixN<T>;
+
+    fn add(self, _rhs: &MatrixN<T>) -> MatrixN<T> {
+        self.assert_same_shape(_rhs);
+        MatrixN {
+            rows: self.rows,
+            cols: self.cols,
+            data: self
+                .data
+                .iter()
+                .zip(&_rhs.data)
+                .map(|(a, b)| *a + *b)
+                .collect(),
+        }
+    }
+}
+
+// Sub &MatrixN - &MatrixN
+impl<T: Scalar> ops::Sub<&MatrixN<T>> for &MatrixN<T> {
+    type Output = MatrixN<T>;
+
+    fn sub(self, _rhs: &MatrixN<T>) -> MatrixN<T> {
+        self.assert_same_shape(_rhs);
+        MatrixN {
+            rows: self.rows,
+            cols: self.cols,
+            data: self
+                .data
+                .iter()
+                .zip(&_rhs.data)
+                .map(|(a, b)| *a - *b)
+                .collect(),
+        }
+    }
+}
+
+// Product &MatrixN * &MatrixN
+impl<T: Scalar> ops::Mul<&MatrixN<T>> for &MatrixN<T> {
+    type Output = MatrixN<T>;
+
+    fn mul(self, _rhs: &MatrixN<T>) -> MatrixN<T> {
+        assert_eq!(
+            self.cols, _rhs.rows,
+            "can't multiply {}x{} by {}x{}",
+            self.rows, self.cols, _rhs.rows, _rhs.cols
+        );
+        let mut out = MatrixN::new(self.rows, _rhs.cols);
+        for c in 0.._rhs.cols {
+            for k in 0..self.cols {
+                let b = _rhs.data[c * _rhs.rows + k];
+                for r in 0..self.rows {
+                    out.data[c * out.rows + r] += self.data[k * self.rows + r] * b;
+                }
+            }
+        }
+        out
+    }
+}
+
+// Scale &MatrixN * T
+impl<T: Scalar> ops::Mul<T> for &MatrixN<T> {
+    type Output = MatrixN<T>;
+
+    fn mul(self, _rhs: T) -> MatrixN<T> {
+        MatrixN {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(|v| *v * _rhs).collect(),
+        }
+    }
+}
+
+// Negate -&MatrixN
+impl<T: Scalar + ops::Neg<Output = T>> ops::Neg for &MatrixN<T> {
+    type Output = MatrixN<T>;
+
+    fn neg(self) -> MatrixN<T> {
+        MatrixN {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(|v| -*v).collect(),
+        }
+    }
+}
+
+// Owned versions forward to the reference ones
+impl<T: Scalar> ops::Add<MatrixN<T>> for MatrixN<T> {
+    type Output = MatrixN<T>;
+
+    fn add(self, _rhs: MatrixN<T>) -> MatrixN<T> {
+        &self + &_rhs
+    }
+}
+
+impl<T: Scalar> ops::Sub<MatrixN<T>> for MatrixN<T> {
+    type Output = MatrixN<T>;
+
+    fn sub(self, _rhs: MatrixN<T>) -> MatrixN<T> {
+        &self - &_rhs
+    }
+}
+
+impl<T: Scalar> ops::Mul<MatrixN<T>> for MatrixN<T> {
+    type Output = MatrixN<T>;
+
+    fn mul(self, _rhs: MatrixN<T>) -> MatrixN<T> {
+        &self * &_rhs
+    }
+}
+
+impl<T: Scalar> ops::Mul<T> for MatrixN<T> {
+    type Output = MatrixN<T>;
+
+    fn mul(self, _rhs: T) -> MatrixN<T> {
+        &self * _rhs
+    }
+}
+
+impl<T: Scalar + ops::Neg<Output = T>> ops::Neg for MatrixN<T> {
+    type Output = MatrixN<T>;
+
+    fn neg(self) -> MatrixN<T> {
+        -&self
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Scalar> MatrixN<T> {
+    // Zero filled
+    pub fn new(rows: usize, cols: usize) -> MatrixN<T> {
+        MatrixN {
+            rows,
+            cols,
+            data: vec![T::ZERO; rows * cols],
+        }
+    }
+
+    pub fn identity(n: usize) -> MatrixN<T> {
+        let mut m = MatrixN::new(n, n);
+        for i in 0..n {
+            m.data[i * n + i] = T::ONE;
+        }
+        m
+    }
+
+    pub fn from_fn<F: FnMut(usize, usize) -> T>(rows: usize, cols: usize, mut f: F) -> MatrixN<T> {
+        let mut data = Vec::with_capacity(rows * cols);
+        for c in 0..cols {
+            for r in 0..rows {
+                data.push(f(r, c));
+            }
+        }
+        MatrixN { rows, cols, data }
+    }
+
+    // Values are listed row by row, the way matrices are written down
+    pub fn from_rows(rows: usize, cols: usize, values: &[T]) -> MatrixN<T> {
+        assert_eq!(values.len(), rows * cols, "expected {} values", rows * cols);
+        MatrixN::from_fn(rows, cols, |r, c| values[r * cols + c])
+    }
+
+    // Values are listed column by column, same as the storage
+    pub fn from_columns(rows: usize, cols: usize, values: &[T]) -> MatrixN<T> {
+        assert_eq!(values.len(), rows * cols, "expected {} values", rows * cols);
+        MatrixN {
+            rows,
+            cols,
+            data: values.to_vec(),
+        }
+    }
+
+    // Single column
+    pub fn from_column(values: &[T]) -> MatrixN<T> {
+        MatrixN::from_columns(values.len(), 1, values)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    // Column-major storage
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    pub fn column(&self, c: usize) -> &[T] {
+        &self.data[c * self.rows..(c + 1) * self.rows]
+    }
+
+    pub fn transpose(&self) -> MatrixN<T> {
+        MatrixN::from_fn(self.cols, self.rows, |r, c| self[(c, r)])
+    }
+
+    // self * v for a column vector given as slice
+    pub fn mul_vector(&self, v: &[T]) -> Vec<T> {
+        assert_eq!(v.len(), self.cols, "expected {} values", self.cols);
+        let mut out = vec![T::ZERO; self.rows];
+        for (c, x) in v.iter().enumerate() {
+            for (r, o) in out.iter_mut().enumerate() {
+                *o += self.data[c * self.rows + r] * *x;
+            }
+        }
+        out
+    }
+
+    pub fn cast<U: Scalar>(&self) -> MatrixN<U> {
+        MatrixN {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(|v| U::from_f64(v.to_f64())).collect(),
+        }
+    }
+
+    // None unless the matrix is 4x4
+    pub fn to_matrix4x4(&self) -> Option<Matrix4x4<T>> {
+        if self.rows != 4 || self.cols != 4 {
+            return None;
+        }
+        let mut mx = Matrix4x4::new();
+        mx.raw.copy_from_slice(&self.data);
+        Some(mx)
+    }
+
+    // None unless the matrix is 3x3
+    pub fn to_matrix3x3(&self) -> Option<Matrix3x3<T>> {
+        if self.rows != 3 || self.cols != 3 {
+            return None;
+        }
+        let mut mx = Matrix3x3::new();
+        mx.raw.copy_from_slice(&self.data);
+        Some(mx)
+    }
+
+    // None unless the matrix is a 3 element column or row
+    pub fn to_vector3(&self) -> Option<Vector3<T>> {
+        if self.data.len() != 3 || (self.rows != 1 && self.cols != 1) {
+            return None;
+        }
+        Some(Vector3::new(self.data[0], self.data[1], self.data[2]))
+    }
+
+    // None unless the matrix is a 4 element column or row
+    pub fn to_vector4(&self) -> Option<Vector4<T>> {
+        if self.data.len() != 4 || (self.rows != 1 && self.cols != 1) {
+            return None;
+        }
+        Some(Vector4::new(
+            self.data[0],
+            self.data[1],
+            self.data[2],
+            self.data[3],
+        ))
+    }
+
+    fn assert_same_shape(&self, other: &MatrixN<T>) {
+        assert!(
+            self.rows == other.rows && self.cols == other.cols,
+            "shape mismatch {}x{} and {}x{}",
+            self.rows,
+            self.cols,
+            other.rows,
+            other.cols
+        );
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Float> MatrixN<T> {
+    // Frobenius norm
+    pub fn norm(&self) -> T {
+        let mut sum = T::ZERO;
+        for v in self.data.iter() {
+            sum += *v * *v;
+        }
+        sum.sqrt()
+    }
+
+    fn max_abs(&self) -> T {
+        let mut m = T::ZERO;
+        for v in self.data.iter() {
+            if v.abs() > m {
+                m = v.abs();
+            }
+        }
+        m
+    }
+
+    // PA = LU with partial pivoting, None if the matrix is singular
+    pub fn lu(&self) -> Option<Lu<T>> {
+        assert!(self.is_square(), "lu needs a square matrix");
+        let n = self.rows;
+        let mut a = self.clone();
+        let mut perm: Vec<usize> = (0..n).collect();
+        let mut sign = T::ONE;
+        let tiny = self.max_abs() * T::EPSILON * T::from_f64(n as f64);
+
+        for k in 0..n {
+            let mut p = k;
+            for i in k + 1..n {
+                if a[(i, k)].abs() > a[(p, k)].abs() {
+                    p = i;
+                }
+            }
+            if a[(p, k)].abs() <= tiny {
+                return None;
+            }
+            if p != k {
+                for c in 0..n {
+                    a.data.swap(c * n + p, c * n + k);
+                }
+                perm.swap(p, k);
+                sign = -sign;
+            }
+
+            let pivot = a[(k, k)];
+            for i in k + 1..n {
+                let f = a[(i, k)] / pivot;
+                a[(i, k)] = f;
+                for j in k + 1..n {
+                    let v = a[(k, j)];
+                    a[(i, j)] -= f * v;
+                }
+            }
+        }
+
+        Some(Lu { lu: a, perm, sign })
+    }
+
+    // A = QR with Householder reflections, Q is rows x rows orthogonal and R
+    // is rows x cols upper triangular
+    pub fn qr(&self) -> Qr<T> {
+        let (m, n) = (self.rows, self.cols);
+        let mut r = self.clone();
+        let mut q = MatrixN::identity(m);
+        let two = T::from_f64(2.0);
+
+        for k in 0..n.min(m.saturating_sub(1)) {
+            let mut v: Vec<T> = (k..m).map(|i| r[(i, k)]).collect();
+            let mut len = T::ZERO;
+            for x in v.iter() {
+                len += *x * *x;
+            }
+            let len = len.sqrt();
+            if len == T::ZERO {
+                continue;
+            }
+
+            // Reflect onto -sign(x0) * |x| * e0 to avoid cancellation
+            let lead = if v[0] < T::ZERO { -len } else { len };
+            v[0] += lead;
+            let mut vlen = T::ZERO;
+            for x in v.iter() {
+                vlen += *x * *x;
+            }
+            let vlen = vlen.sqrt();
+            for x in v.iter_mut() {
+                *x /= vlen;
+            }
+
+            for j in 0..n {
+                let mut d = T::ZERO;
+                for (i, x) in v.iter().enumerate() {
+                    d += *x * r[(k + i, j)];
+                }
+                for (i, x) in v.iter().enumerate() {
+                    r[(k + i, j)] -= two * *x * d;
+                }
+            }
+            for i in 0..m {
+                let mut d = T::ZERO;
+                for (l, x) in v.iter().enumerate() {
+                    d += q[(i, k + l)] * *x;
+                }
+                for (l, x) in v.iter().enumerate() {
+                    q[(i, k + l)] -= two * d * *x;
+                }
+            }
+        }
+
+        // Below the diagonal is only rounding noise now
+        for c in 0..n {
+            for i in c + 1..m {
+                r[(i, c)] = T::ZERO;
+            }
+        }
+
+        Qr { q, r }
+    }
+
+    // A = L * L^T for symmetric positive definite matrices, only the lower
+    // triangle is read. None if the matrix is not positive definite
+    pub fn cholesky(&self) -> Option<Cholesky<T>> {
+        assert!(self.is_square(), "cholesky needs a square matrix");
+        let n = self.rows;
+        let mut l = MatrixN::new(n, n);
+
+        for j in 0..n {
+            let mut d = self[(j, j)];
+            for k in 0..j {
+                d -= l[(j, k)] * l[(j, k)];
+            }
+            if d <= T::ZERO {
+                return None;
+            }
+            let d = d.sqrt();
+            l[(j, j)] = d;
+
+            for i in j + 1..n {
+                let mut s = self[(i, j)];
+                for k in 0..j {
+                    s -= l[(i, k)] * l[(j, k)];
+                }
+                l[(i, j)] = s / d;
+            }
+        }
+
+        Some(Cholesky { l })
+    }
+
+    // A = U * diag(s) * V^T with one sided Jacobi rotations. For a rows x cols
+    // matrix U is rows x k, V is cols x k with k = min(rows, cols) and the
+    // singular values are sorted from largest to smallest. Columns of U and V
+    // for zero singular values are filled in so both stay orthonormal
+    pub fn svd(&self) -> Svd<T> {
+        if self.rows < self.cols {
+            let t = self.transpose().svd();
+            return Svd {
+                u: t.v,
+                s: t.s,
+                v: t.u,
+            };
+        }
+
+        let (m, n) = (self.rows, self.cols);
+        let mut u = self.clone();
+        let mut v = MatrixN::identity(n);
+
+        for _ in 0..64 {
+            let mut rotated = false;
+            for p in 0..n {
+                for q in p + 1..n {
+                    let (mut alpha, mut beta, mut gamma) = (T::ZERO, T::ZERO, T::ZERO);
+                    for i in 0..m {
+                        let (a, b) = (u[(i, p)], u[(i, q)]);
+                        alpha += a * a;
+                        beta += b * b;
+                        gamma += a * b;
+                    }
+                    if gamma.abs() <= T::EPSILON * (alpha * beta).sqrt() {
+                        continue;
+                    }
+                    rotated = true;
+
+                    // Rotation that makes columns p and q orthogonal
+                    let zeta = (beta - alpha) / (T::from_f64(2.0) * gamma);
+                    let sign = if zeta < T::ZERO { -T::ONE } else { T::ONE };
+                    let t = sign / (zeta.abs() + (T::ONE + zeta * zeta).sqrt());
+                    let c = T::ONE / (T::ONE + t * t).sqrt();
+                    let s = c * t;
+
+                    for mx in [&mut u, &mut v] {
+                        for i in 0..mx.rows {
+                            let (a, b) = (mx[(i, p)], mx[(i, q)]);
+                            mx[(i, p)] = c * a - s * b;
+                            mx[(i, q)] = s * a + c * b;
+                        }
+                    }
+                }
+            }
+            if !rotated {
+                break;
+            }
+        }
+
+        // Column lengths are the singular values
+        let mut s: Vec<T> = (0..n)
+            .map(|c| {
+                let mut l = T::ZERO;
+                for x in u.column(c) {
+                    l += *x * *x;
+                }
+                l.sqrt()
+            })
+            .collect();
+        for (c, len) in s.iter().enumerate() {
+            if *len > T::ZERO {
+                for i in 0..m {
+                    u[(i, c)] /= *len;
+                }
+            }
+        }
+
+        let mut order: Vec<usize> = (0..n).collect();
+        order.sort_by(|a, b| {
+            s[*b]
+                .partial_cmp(&s[*a])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let mut u = MatrixN::from_fn(m, n, |r, c| u[(r, order[c])]);
+        let v = MatrixN::from_fn(n, n, |r, c| v[(r, order[c])]);
+        s = order.iter().map(|i| s[*i]).collect();
+
+        // A zero column has no direction, use the unit axis that is furthest
+        // from the columns before it. m >= n so there is always one left
+        let tol = s.first().copied().unwrap_or(T::ZERO) * T::EPSILON * T::from_f64(m as f64);
+        for c in 0..n {
+            if s[c] > tol {
+                continue;
+            }
+            let mut best = (T::ZERO, vec![T::ZERO; m]);
+            for e in 0..m {
+                let mut w = vec![T::ZERO; m];
+                w[e] = T::ONE;
+                for k in 0..c {
+                    let d = u[(e, k)];
+                    for (i, x) in w.iter_mut().enumerate() {
+                        *x -= d * u[(i, k)];
+                    }
+                }
+                let mut len = T::ZERO;
+                for x in w.iter() {
+                    len += *x * *x;
+                }
+                let len = len.sqrt();
+                if len > best.0 {
+                    best = (len, w);
+                }
+            }
+            for (i, x) in best.1.iter().enumerate() {
+                u[(i, c)] = *x / best.0;
+            }
+        }
+
+        Svd { u, s, v }
+    }
+
+    // 0 for singular matrices
+    pub fn determinant(&self) -> T {
+        match self.lu() {
+            Some(lu) => lu.determinant(),
+            None => T::ZERO,
+        }
+    }
+
+    pub fn inverse(&self) -> Option<MatrixN<T>> {
+        self.lu().map(|lu| lu.inverse())
+    }
+
+    // Solve A * x = b for square A, None if A is singular
+    pub fn solve(&self, b: &[T]) -> Option<Vec<T>> {
+        self.lu().map(|lu| lu.solve(b))
+    }
+
+    // x minimizing |A * x - b|, None if rows < cols or the columns are
+    // linearly dependent. Use svd().solve() for rank deficient systems
+    pub fn least_squares(&self, b: &[T]) -> Option<Vec<T>> {
+        self.qr().solve(b)
+    }
+
+    pub fn rank(&self) -> usize {
+        self.svd().rank()
+    }
+
+    pub fn pseudo_inverse(&self) -> MatrixN<T> {
+        self.svd().pseudo_inverse()
+    }
+}
+
+// Result of MatrixN::lu, L (unit diagonal) and U share one matrix
+#[derive(Clone, Debug)]
+pub struct Lu<T = f32> {
+    pub lu: MatrixN<T>,
+    // Row i of PA is row perm[i] of A
+    pub perm: Vec<usize>,
+    sign: T,
+}
+
+#[allow(dead_code)]
+impl<T: Float> Lu<T> {
+    pub fn solve(&self, b: &[T]) -> Vec<T> {
+        let n = self.lu.rows;
+        assert_eq!(b.len(), n, "expected {} values", n);
+        let a = &self.lu;
+
+        let mut x: Vec<T> = self.perm.iter().map(|p| b[*p]).collect();
+        for i in 0..n {
+            for k in 0..i {
+                let v = x[k];
+                x[i] -= a[(i, k)] * v;
+            }
+        }
+        for i in (0..n).rev() {
+            for k in i + 1..n {
+                let v = x[k];
+                x[i] -= a[(i, k)] * v;
+            }
+            x[i] /= a[(i, i)];
+        }
+        x
+    }
+
+    pub fn determinant(&self) -> T {
+        let mut d = self.sign;
+        for i in 0..self.lu.rows {
+            d *= self.lu[(i, i)];
+        }
+        d
+    }
+
+    pub fn inverse(&self) -> MatrixN<T> {
+        let n = self.lu.rows;
+        let mut out = MatrixN::new(n, n);
+        let mut e = vec![T::ZERO; n];
+        for c in 0..n {
+            e[c] = T::ONE;
+            let x = self.solve(&e);
+            out.data[c * n..(c + 1) * n].copy_from_slice(&x);
+            e[c] = T::ZERO;
+        }
+        out
+    }
+}
+
+// Result of MatrixN::qr
+#[derive(Clone, Debug)]
+pub struct Qr<T = f32> {
+    pub q: MatrixN<T>,
+    pub r: MatrixN<T>,
+}
+
+#[allow(dead_code)]
+impl<T: Float> Qr<T> {
+    // Least squares solution of A * x = b, None if R has a zero on the
+    // diagonal or A has more columns than rows
+    pub fn solve(&self, b: &[T]) -> Option<Vec<T>> {
+        let (m, n) = (self.r.rows, self.r.cols);
+        assert_eq!(b.len(), m, "expected {} values", m);
+        if n > m {
+            return None;
+        }
+
+        // Q^T * b, only the first n values are needed
+        let mut x: Vec<T> = (0..n)
+            .map(|c| {
+                let mut d = T::ZERO;
+                for (q, v) in self.q.column(c).iter().zip(b) {
+                    d += *q * *v;
+                }
+                d
+            })
+            .collect();
+
+        let tiny = self.r.max_abs() * T::EPSILON * T::from_f64(m.max(n) as f64);
+        for i in (0..n).rev() {
+            if self.r[(i, i)].abs() <= tiny {
+                return None;
+            }
+            for k in i + 1..n {
+                let v = x[k];
+                x[i] -= self.r[(i, k)] * v;
+            }
+            x[i] /= self.r[(i, i)];
+        }
+        Some(x)
+    }
+}
+
+// Result of MatrixN::cholesky, lower triangular l
+#[derive(Clone, Debug)]
+pub struct Cholesky<T = f32> {
+    pub l: MatrixN<T>,
+}
+
+#[allow(dead_code)]
+impl<T: Float> Cholesky<T> {
+    pub fn solve(&self, b: &[T]) -> Vec<T> {
+        let n = self.l.rows;
+        assert_eq!(b.len(), n, "expected {} values", n);
+        let l = &self.l;
+
+        // L * y = b, then L^T * x = y
+        let mut x = b.to_vec();
+        for i in 0..n {
+            for k in 0..i {
+                let v = x[k];
+                x[i] -= l[(i, k)] * v;
+            }
+            x[i] /= l[(i, i)];
+        }
+        for i in (0..n).rev() {
+            for k in i + 1..n {
+                let v = x[k];
+                x[i] -= l[(k, i)] * v;
+            }
+            x[i] /= l[(i, i)];
+        }
+        x
+    }
+}
+
+// Result of MatrixN::svd
+#[derive(Clone, Debug)]
+pub struct Svd<T = f32> {
+    pub u: MatrixN<T>,
+    pub s: Vec<T>,
+    pub v: MatrixN<T>,
+}
+
+#[allow(dead_code)]
+impl<T: Float> Svd<T> {
+    // Singular values below this count as zero
+    pub fn tolerance(&self) -> T {
+        let largest = self.s.first().copied().unwrap_or(T::ZERO);
+        largest * T::EPSILON * T::from_f64(self.u.rows.max(self.v.rows) as f64)
+    }
+
+    pub fn rank(&self) -> usize {
+        let tol = self.tolerance();
+        self.s.iter().filter(|s| **s > tol).count()
+    }
+
+    pub fn pseudo_inverse(&self) -> MatrixN<T> {
+        let tol = self.tolerance();
+        let (rows, cols) = (self.v.rows, self.u.rows);
+        let mut out = MatrixN::new(rows, cols);
+        for (k, s) in self.s.iter().enumerate() {
+            if *s <= tol {
+                continue;
+            }
+            let inv = T::ONE / *s;
+            for c in 0..cols {
+                let f = self.u[(c, k)] * inv;
+                for r in 0..rows {
+                    out[(r, c)] += self.v[(r, k)] * f;
+                }
+            }
+        }
+        out
+    }
+
+    // Minimum norm least squares solution, works for rank deficient systems
+    pub fn solve(&self, b: &[T]) -> Vec<T> {
+        self.pseudo_inverse().mul_vector(b)
+    }
+}
+
+impl<T: Scalar> From<Matrix4x4<T>> for MatrixN<T> {
+    fn from(mx: Matrix4x4<T>) -> MatrixN<T> {
+        MatrixN::from_columns(4, 4, &mx.raw)
+    }
+}
+
+impl<T: Scalar> From<Matrix3x3<T>> for MatrixN<T> {
+    fn from(mx: Matrix3x3<T>) -> MatrixN<T> {
+        MatrixN::from_columns(3, 3, &mx.raw)
+    }
+}
+
+// Column vector
+impl<T: Scalar> From<Vector3<T>> for MatrixN<T> {
+    fn from(v: Vector3<T>) -> MatrixN<T> {
+        MatrixN::from_column(&[v.x, v.y, v.z])
+    }
+}
+
+// Column vector
+impl<T: Scalar> From<Vector4<T>> for MatrixN<T> {
+    fn from(v: Vector4<T>) -> MatrixN<T> {
+        MatrixN::from_column(&[v.x, v.y, v.z, v.w])
+    }
+}
@@ -1,5 +1,13 @@
+pub mod coordinates;
+pub mod dual_quaternion;
+mod macros;
+pub mod matrix2;
+pub mod matrix3;
 pub mod matrix4;
+pub mod matrixn;
 pub mod quaternion;
+pub mod simd;
+pub mod transform;
 pub mod vector2;
 pub mod vector3;
 pub mod vector4;
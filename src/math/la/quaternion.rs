@@ -1,19 +1,38 @@
+#[cfg(feature = "serde")]
+use crate::math::la::macros::impl_serde_array;
+use crate::math::la::macros::{impl_bytes, impl_linear_ops};
+use crate::math::la::matrix3::Matrix3x3;
 use crate::math::la::matrix4::Matrix4x4;
 use crate::math::la::vector3::Vector3;
 use crate::math::la::vector4::Vector4;
+use crate::math::number::scalar::Float;
 use std::{fmt, ops};
 
-#[derive(Copy, Clone, Default, Debug)]
+// Intrinsic rotation order of Euler angles, XYZ rotates around X first, then
+// around the rotated Y and then around the rotated Z
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EulerOrder {
+    #[default]
+    XYZ,
+    XZY,
+    YXZ,
+    YZX,
+    ZXY,
+    ZYX,
+}
+
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 #[repr(C)]
-pub struct Quaternion {
-    pub x: f32,
-    pub y: f32,
-    pub z: f32,
-    pub w: f32,
+pub struct Quaternion<T = f32> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T,
 }
 
 // Print
-impl fmt::Display for Quaternion {
+impl<T: fmt::Display> fmt::Display for Quaternion<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
@@ -23,39 +42,18 @@ impl fmt::Display for Quaternion {
     }
 }
 
-// Add Quaternion + Quaternion
-impl ops::Add<Quaternion> for Quaternion {
-    type Output = Quaternion;
-
-    fn add(self, _rhs: Quaternion) -> Quaternion {
-        Quaternion {
-            x: self.x + _rhs.x,
-            y: self.y + _rhs.y,
-            z: self.z + _rhs.z,
-            w: self.w + _rhs.w,
-        }
-    }
-}
-
-// Add Quaternion - Quaternion
-impl ops::Sub<Quaternion> for Quaternion {
-    type Output = Quaternion;
-
-    fn sub(self, _rhs: Quaternion) -> Quaternion {
-        Quaternion {
-            x: self.x - _rhs.x,
-            y: self.y - _rhs.y,
-            z: self.z - _rhs.z,
-            w: self.w - _rhs.w,
-        }
-    }
-}
+impl_linear_ops!(Quaternion {
+    x: 0,
+    y: 1,
+    z: 2,
+    w: 3
+});
 
 // Add Quaternion * Quaternion
-impl ops::Mul<Quaternion> for Quaternion {
-    type Output = Quaternion;
+impl<T: Float> ops::Mul<Quaternion<T>> for Quaternion<T> {
+    type Output = Quaternion<T>;
 
-    fn mul(self, _rhs: Quaternion) -> Quaternion {
+    fn mul(self, _rhs: Quaternion<T>) -> Quaternion<T> {
         let w1 = self.w;
         let x1 = self.x;
         let y1 = self.y;
@@ -75,9 +73,18 @@ impl ops::Mul<Quaternion> for Quaternion {
     }
 }
 
+// Rotate Vector3 by Quaternion
+impl<T: Float> ops::Mul<Vector3<T>> for Quaternion<T> {
+    type Output = Vector3<T>;
+
+    fn mul(self, v: Vector3<T>) -> Vector3<T> {
+        self.rotate_vector(v)
+    }
+}
+
 // Add Quaternion * Quaternion
-impl ops::MulAssign<Quaternion> for Quaternion {
-    fn mul_assign(&mut self, other: Quaternion) {
+impl<T: Float> ops::MulAssign<Quaternion<T>> for Quaternion<T> {
+    fn mul_assign(&mut self, other: Quaternion<T>) {
         let w1 = self.w;
         let x1 = self.x;
         let y1 = self.y;
@@ -98,50 +105,50 @@ impl ops::MulAssign<Quaternion> for Quaternion {
 }
 
 #[allow(dead_code)]
-impl Quaternion {
-    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Quaternion {
+impl<T: Float> Quaternion<T> {
+    pub const fn new(x: T, y: T, z: T, w: T) -> Quaternion<T> {
         Quaternion { x, y, z, w }
     }
 
-    pub const fn zero() -> Quaternion {
+    pub const fn zero() -> Quaternion<T> {
         Quaternion {
-            x: 0.0,
-            y: 0.0,
-            z: 0.0,
-            w: 0.0,
+            x: T::ZERO,
+            y: T::ZERO,
+            z: T::ZERO,
+            w: T::ZERO,
         }
     }
 
-    pub const fn identity() -> Quaternion {
+    pub const fn identity() -> Quaternion<T> {
         Quaternion {
-            x: 0.0,
-            y: 0.0,
-            z: 0.0,
-            w: 1.0,
+            x: T::ZERO,
+            y: T::ZERO,
+            z: T::ZERO,
+            w: T::ONE,
         }
     }
 
-    pub fn inverse(self) -> Quaternion {
+    pub fn inverse(self) -> Quaternion<T> {
         let w = self.w;
         let x = self.x;
         let y = self.y;
         let z = self.z;
 
         let mut norm_sq = w * w + x * x + y * y + z * z;
-        if norm_sq == 0.0 {
+        if norm_sq == T::ZERO {
             return Quaternion::zero();
         }
 
-        norm_sq = 1.0 / norm_sq;
-        return Quaternion {
+        norm_sq = T::ONE / norm_sq;
+        Quaternion {
             x: -x * norm_sq,
             y: -y * norm_sq,
             z: -z * norm_sq,
             w: w * norm_sq,
-        };
+        }
     }
 
-    pub fn to_matrix4x4(self) -> Matrix4x4 {
+    pub fn to_matrix4x4(self) -> Matrix4x4<T> {
         let w = self.w;
         let x = self.x;
         let y = self.y;
@@ -172,28 +179,28 @@ impl Quaternion {
 
         let mut mx = Matrix4x4::new();
 
-        mx.raw[0] = 1.0 - (yy + zz);
+        mx.raw[0] = T::ONE - (yy + zz);
         mx.raw[4] = xy - wz;
         mx.raw[8] = xz + wy;
 
         mx.raw[1] = xy + wz;
-        mx.raw[5] = 1.0 - (xx + zz);
+        mx.raw[5] = T::ONE - (xx + zz);
         mx.raw[9] = yz - wx;
 
         mx.raw[2] = xz - wy;
         mx.raw[6] = yz + wx;
-        mx.raw[10] = 1.0 - (xx + yy);
+        mx.raw[10] = T::ONE - (xx + yy);
 
         // last column
-        mx.raw[3] = 0.0;
-        mx.raw[7] = 0.0;
-        mx.raw[11] = 0.0;
+        mx.raw[3] = T::ZERO;
+        mx.raw[7] = T::ZERO;
+        mx.raw[11] = T::ZERO;
 
         // bottom row
-        mx.raw[12] = 0.0;
-        mx.raw[13] = 0.0;
-        mx.raw[14] = 0.0;
-        mx.raw[15] = 1.0;
+        mx.raw[12] = T::ZERO;
+        mx.raw[13] = T::ZERO;
+        mx.raw[14] = T::ZERO;
+        mx.raw[15] = T::ONE;
 
         mx
 
@@ -219,10 +226,28 @@ impl Quaternion {
         }*/
     }
 
-    pub fn from_euler(v: Vector3) -> Quaternion {
-        let _x = v.x * 0.5;
-        let _y = v.y * 0.5;
-        let _z = v.z * 0.5;
+    pub fn to_matrix3x3(self) -> Matrix3x3<T> {
+        Matrix3x3::from_quaternion(self)
+    }
+
+    // Angles in radians, X first, then the rotated Y and Z. Same as
+    // from_euler_order(v, EulerOrder::XYZ)
+    pub fn from_euler(v: Vector3<T>) -> Quaternion<T> {
+        Quaternion::from_euler_order(v, EulerOrder::XYZ)
+    }
+
+    // Angles in radians read in ZYX order, so this is not the inverse of
+    // from_euler. Use to_euler_order with the order the angles were built in
+    pub fn to_euler(self) -> Vector3<T> {
+        self.to_euler_order(EulerOrder::ZYX)
+    }
+
+    // Angles in radians, applied in the given intrinsic order
+    pub fn from_euler_order(v: Vector3<T>, order: EulerOrder) -> Quaternion<T> {
+        let half = T::from_f64(0.5);
+        let _x = v.x * half;
+        let _y = v.y * half;
+        let _z = v.z * half;
 
         let c_x = _x.cos();
         let c_y = _y.cos();
@@ -232,52 +257,35 @@ impl Quaternion {
         let s_y = _y.sin();
         let s_z = _z.sin();
 
+        // Sign of the second term of each component depends on the order
+        let (sx, sy, sz, sw) = match order {
+            EulerOrder::XYZ => (T::ONE, -T::ONE, T::ONE, -T::ONE),
+            EulerOrder::YXZ => (T::ONE, -T::ONE, -T::ONE, T::ONE),
+            EulerOrder::ZXY => (-T::ONE, T::ONE, T::ONE, -T::ONE),
+            EulerOrder::ZYX => (-T::ONE, T::ONE, -T::ONE, T::ONE),
+            EulerOrder::YZX => (T::ONE, T::ONE, -T::ONE, -T::ONE),
+            EulerOrder::XZY => (-T::ONE, -T::ONE, T::ONE, T::ONE),
+        };
+
         Quaternion {
-            w: c_x * c_y * c_z - s_x * s_y * s_z,
-            x: c_y * c_z * s_x + c_x * s_y * s_z,
-            y: c_x * c_z * s_y - c_y * s_x * s_z,
-            z: c_x * c_y * s_z + c_z * s_x * s_y,
+            w: c_x * c_y * c_z + sw * s_x * s_y * s_z,
+            x: c_y * c_z * s_x + sx * c_x * s_y * s_z,
+            y: c_x * c_z * s_y + sy * c_y * s_x * s_z,
+            z: c_x * c_y * s_z + sz * c_z * s_x * s_y,
         }
     }
 
-    pub fn to_euler(self) -> Vector3 {
-        let t = 2.0 * (self.w * self.y - self.z * self.x);
-        let mut v = Vector3::zero();
-
-        // Set X
-        let a = 2.0 * (self.w * self.x + self.y * self.z);
-        v.x = a.atan2(1.0 - 2.0 * (self.x * self.x + self.y * self.y));
-
-        // Set Y
-        if t >= 1.0 {
-            v.y = std::f32::consts::PI / 2.0;
-        } else {
-            if t <= -1.0 {
-                v.y = -std::f32::consts::PI / 2.0;
-            } else {
-                v.y = t.asin();
-            }
-        }
-
-        // Set Z
-        let a = 2.0 * (self.w * self.z + self.x * self.y);
-        v.z = a.atan2(1.0 - 2.0 * (self.y * self.y + self.z * self.z));
-
-        return v;
+    pub fn to_euler_order(self, order: EulerOrder) -> Vector3<T> {
+        self.normalize().to_matrix3x3().to_euler_order(order)
     }
 
-    pub fn to_vector4(&self) -> Vector4 {
+    pub fn to_vector4(&self) -> Vector4<T> {
         Vector4::new(self.x, self.y, self.z, self.w)
     }
 
-    pub fn lerp(a: Quaternion, b: Quaternion, t: f32) -> Quaternion {
-        let mut result = Quaternion {
-            w: 0.0,
-            x: 0.0,
-            y: 0.0,
-            z: 0.0,
-        };
-        let t_inv = 1.0 - t;
+    pub fn lerp(a: Quaternion<T>, b: Quaternion<T>, t: T) -> Quaternion<T> {
+        let mut result = Quaternion::zero();
+        let t_inv = T::ONE - t;
 
         // Linear interpolation for the quaternion components
         result.w = a.w * t_inv + b.w * t;
@@ -297,16 +305,296 @@ impl Quaternion {
         result
     }
 
-    pub fn from_vector4(v: Vector4) -> Quaternion {
-        Quaternion::new(v.x, v.y, v.z, v.w)
+    // Shortest path spherical interpolation, constant angular speed
+    pub fn slerp(a: Quaternion<T>, b: Quaternion<T>, t: T) -> Quaternion<T> {
+        let mut b = b;
+        let mut d = a.dot(b);
+        if d < T::ZERO {
+            b = -b;
+            d = -d;
+        }
+
+        // Nearly the same rotation, sin(theta) goes to zero
+        if d > T::from_f64(0.9995) {
+            return Quaternion::lerp(a, b, t);
+        }
+
+        let theta = d.acos();
+        let sin_theta = theta.sin();
+        let wa = ((T::ONE - t) * theta).sin() / sin_theta;
+        let wb = (t * theta).sin() / sin_theta;
+        a * wa + b * wb
+    }
+
+    // Shortest path normalized linear interpolation, cheaper than slerp but
+    // the speed isn't constant
+    pub fn nlerp(a: Quaternion<T>, b: Quaternion<T>, t: T) -> Quaternion<T> {
+        if a.dot(b) < T::ZERO {
+            return Quaternion::lerp(a, -b, t);
+        }
+        Quaternion::lerp(a, b, t)
+    }
+
+    // Spherical cubic interpolation between a and b, sa and sb are control
+    // points from squad_control
+    pub fn squad(
+        a: Quaternion<T>,
+        b: Quaternion<T>,
+        sa: Quaternion<T>,
+        sb: Quaternion<T>,
+        t: T,
+    ) -> Quaternion<T> {
+        let two = T::from_f64(2.0);
+        Quaternion::slerp_long(
+            Quaternion::slerp_long(a, b, t),
+            Quaternion::slerp_long(sa, sb, t),
+            two * t * (T::ONE - t),
+        )
+    }
+
+    // Squad control point of current key, from its neighbours
+    pub fn squad_control(
+        prev: Quaternion<T>,
+        current: Quaternion<T>,
+        next: Quaternion<T>,
+    ) -> Quaternion<T> {
+        let inv = current.conjugate();
+        let a = (inv * next).ln();
+        let b = (inv * prev).ln();
+        current * ((a + b) * T::from_f64(-0.25)).exp()
+    }
+
+    // Slerp without the shortest path flip, squad needs it to stay smooth
+    fn slerp_long(a: Quaternion<T>, b: Quaternion<T>, t: T) -> Quaternion<T> {
+        let d = a.dot(b);
+        if d > T::from_f64(0.9995) {
+            return Quaternion::lerp(a, b, t);
+        }
+        // Nearly opposite, the arc below is badly conditioned. Go through a
+        // quaternion perpendicular to a for the first half, then on to b
+        if d < T::from_f64(-0.9995) {
+            let p = Quaternion::new(-a.y, a.x, -a.w, a.z);
+            let two = T::from_f64(2.0);
+            if t <= T::from_f64(0.5) {
+                let angle = t * T::PI;
+                return a * angle.cos() + p * angle.sin();
+            }
+            return Quaternion::slerp_long(p, b, two * t - T::ONE);
+        }
+
+        let theta = d.acos();
+        let sin_theta = theta.sin();
+        let wa = ((T::ONE - t) * theta).sin() / sin_theta;
+        let wb = (t * theta).sin() / sin_theta;
+        a * wa + b * wb
+    }
+
+    // e^q, for a quaternion with zero w this is a rotation by twice the
+    // length of xyz
+    pub fn exp(self) -> Quaternion<T> {
+        let v = Vector3::new(self.x, self.y, self.z);
+        let l = v.length();
+        let e = self.w.exp();
+        if l == T::ZERO {
+            return Quaternion::new(T::ZERO, T::ZERO, T::ZERO, e);
+        }
+        let v = v.scale(e * l.sin() / l);
+        Quaternion::new(v.x, v.y, v.z, e * l.cos())
+    }
+
+    // Natural logarithm, inverse of exp. Unit quaternions get zero w
+    pub fn ln(self) -> Quaternion<T> {
+        let v = Vector3::new(self.x, self.y, self.z);
+        let l = v.length();
+        let w = self.length().ln();
+        if l == T::ZERO {
+            return Quaternion::new(T::ZERO, T::ZERO, T::ZERO, w);
+        }
+        let v = v.scale(l.atan2(self.w) / l);
+        Quaternion::new(v.x, v.y, v.z, w)
     }
 
-    pub fn from_bytes(b: &[u8]) -> Quaternion {
+    // q^t, for unit quaternions this scales the rotation angle by t
+    pub fn pow(self, t: T) -> Quaternion<T> {
+        if self.length() == T::ZERO {
+            return Quaternion::zero();
+        }
+        (self.ln() * t).exp()
+    }
+
+    // Rotation around v by |v| radians
+    pub fn from_scaled_axis(v: Vector3<T>) -> Quaternion<T> {
+        let h = v.scale(T::from_f64(0.5));
+        Quaternion::new(h.x, h.y, h.z, T::ZERO).exp()
+    }
+
+    // Axis scaled by the angle in 0..PI range, inverse of from_scaled_axis
+    pub fn to_scaled_axis(self) -> Vector3<T> {
+        let mut q = self.normalize();
+        if q.w < T::ZERO {
+            q = -q;
+        }
+        let l = q.ln();
+        Vector3::new(l.x, l.y, l.z).scale(T::from_f64(2.0))
+    }
+
+    // Rotate by a world space angular velocity in radians per second for dt
+    // seconds
+    pub fn integrate(self, angular_velocity: Vector3<T>, dt: T) -> Quaternion<T> {
+        (Quaternion::from_scaled_axis(angular_velocity.scale(dt)) * self).normalize()
+    }
+
+    // World space angular velocity that integrates from into to in dt
+    // seconds, taking the shortest way
+    pub fn angular_velocity(from: Quaternion<T>, to: Quaternion<T>, dt: T) -> Vector3<T> {
+        (to * from.conjugate()).to_scaled_axis().scale(T::ONE / dt)
+    }
+
+    // Split into swing * twist where twist rotates around axis and swing
+    // around an axis perpendicular to it. A half turn swing has no defined
+    // twist, it is identity then
+    pub fn swing_twist(self, axis: Vector3<T>) -> (Quaternion<T>, Quaternion<T>) {
+        let axis = axis.normalize();
+        let p = axis.scale(Vector3::new(self.x, self.y, self.z).dot(axis));
+        let mut twist = Quaternion::new(p.x, p.y, p.z, self.w);
+        if twist.length() < T::from_f64(1e-6) {
+            twist = Quaternion::identity();
+        } else {
+            twist = twist.normalize();
+        }
+        (self * twist.conjugate(), twist)
+    }
+
+    pub fn from_axis_angle(axis: Vector3<T>, angle: T) -> Quaternion<T> {
+        let half = angle * T::from_f64(0.5);
+        let v = axis.normalize().scale(half.sin());
+        Quaternion::new(v.x, v.y, v.z, half.cos())
+    }
+
+    // Returns unit axis and angle in radians in 0..PI range
+    pub fn to_axis_angle(self) -> (Vector3<T>, T) {
+        let mut q = self.normalize();
+        if q.w < T::ZERO {
+            q = -q;
+        }
+
+        let s = Vector3::new(q.x, q.y, q.z).length();
+        if s < T::EPSILON {
+            return (Vector3::new(T::ONE, T::ZERO, T::ZERO), T::ZERO);
+        }
+
+        let axis = Vector3::new(q.x / s, q.y / s, q.z / s);
+        (axis, T::from_f64(2.0) * s.atan2(q.w))
+    }
+
+    // Shortest rotation that turns direction from into direction to
+    pub fn from_rotation_arc(from: Vector3<T>, to: Vector3<T>) -> Quaternion<T> {
+        let from = from.normalize();
+        let to = to.normalize();
+        let d = from.dot(to);
+
+        // Opposite directions, rotate half turn around any perpendicular axis
+        if d < -T::ONE + T::from_f64(1e-6) {
+            let mut axis = Vector3::new(T::ONE, T::ZERO, T::ZERO).cross(from);
+            if axis.length() < T::from_f64(1e-6) {
+                axis = Vector3::new(T::ZERO, T::ONE, T::ZERO).cross(from);
+            }
+            return Quaternion::from_axis_angle(axis, T::PI);
+        }
+
+        let c = from.cross(to);
+        Quaternion::new(c.x, c.y, c.z, T::ONE + d).normalize()
+    }
+
+    // Rotation that points +Z along forward with +Y as close to up as possible
+    pub fn look_rotation(forward: Vector3<T>, up: Vector3<T>) -> Quaternion<T> {
+        let z = forward.normalize();
+        if z.length() == T::ZERO {
+            return Quaternion::identity();
+        }
+
+        let mut x = up.cross(z);
+        if x.length() < T::from_f64(1e-6) {
+            // Up is parallel to forward, any side axis will do
+            return Quaternion::from_rotation_arc(Vector3::new(T::ZERO, T::ZERO, T::ONE), z);
+        }
+        x = x.normalize();
+        let y = z.cross(x);
+
+        Matrix3x3 {
+            raw: [x.x, x.y, x.z, y.x, y.y, y.z, z.x, z.y, z.z],
+        }
+        .to_quaternion()
+    }
+
+    pub fn conjugate(self) -> Quaternion<T> {
+        Quaternion::new(-self.x, -self.y, -self.z, self.w)
+    }
+
+    pub fn dot(self, q: Quaternion<T>) -> T {
+        self.x * q.x + self.y * q.y + self.z * q.z + self.w * q.w
+    }
+
+    pub fn length(self) -> T {
+        self.dot(self).sqrt()
+    }
+
+    pub fn normalize(self) -> Quaternion<T> {
+        let l = self.length();
+        if l == T::ZERO {
+            return Quaternion::identity();
+        }
+        self * (T::ONE / l)
+    }
+
+    // Angle in radians of the rotation that turns self into q
+    pub fn angle_between(self, q: Quaternion<T>) -> T {
+        let mut d = self.normalize().dot(q.normalize()).abs();
+        if d > T::ONE {
+            d = T::ONE;
+        }
+        T::from_f64(2.0) * d.acos()
+    }
+
+    pub fn rotate_vector(self, v: Vector3<T>) -> Vector3<T> {
+        let u = Vector3::new(self.x, self.y, self.z);
+        let t = u.cross(v).scale(T::from_f64(2.0));
+        v + t.scale(self.w) + u.cross(t)
+    }
+
+    pub fn cast<U: Float>(self) -> Quaternion<U> {
         Quaternion {
-            x: f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
-            y: f32::from_le_bytes([b[4], b[5], b[6], b[7]]),
-            z: f32::from_le_bytes([b[8], b[9], b[10], b[11]]),
-            w: f32::from_le_bytes([b[12], b[13], b[14], b[15]]),
+            x: U::from_f64(self.x.to_f64()),
+            y: U::from_f64(self.y.to_f64()),
+            z: U::from_f64(self.z.to_f64()),
+            w: U::from_f64(self.w.to_f64()),
         }
     }
+
+    pub fn from_vector4(v: Vector4<T>) -> Quaternion<T> {
+        Quaternion::new(v.x, v.y, v.z, v.w)
+    }
+}
+
+impl_bytes!(Quaternion, 4);
+
+impl<T> From<[T; 4]> for Quaternion<T> {
+    fn from([x, y, z, w]: [T; 4]) -> Quaternion<T> {
+        Quaternion { x, y, z, w }
+    }
+}
+
+impl<T> From<Quaternion<T>> for [T; 4] {
+    fn from(v: Quaternion<T>) -> [T; 4] {
+        [v.x, v.y, v.z, v.w]
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde_array!(Quaternion, 4);
+
+impl From<Quaternion<f32>> for Quaternion<f64> {
+    fn from(q: Quaternion<f32>) -> Quaternion<f64> {
+        q.cast()
+    }
 }
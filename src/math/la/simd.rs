@@ -0,0 +1,341 @@
+// 4 lane f32 kernels for Matrix4x4. The backend is picked at compile time:
+// SSE on x86, simd128 on wasm (build with -C target-feature=+simd128) and
+// plain scalar code everywhere else. Results match the scalar Matrix4x4 code
+// bit for bit for mul and transform_points, invert differs within epsilon.
+use crate::math::la::matrix4::Matrix4x4;
+use crate::math::la::vector3::Vector3;
+use crate::math::number::scalar::{Float, Scalar};
+
+#[cfg(all(
+    any(target_arch = "x86", target_arch = "x86_64"),
+    target_feature = "sse"
+))]
+mod lanes {
+    #[cfg(target_arch = "x86")]
+    use std::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use std::arch::x86_64::*;
+
+    // Used by the shuffle! macro at the call site
+    #[cfg(target_arch = "x86")]
+    pub use std::arch::x86::_mm_shuffle_ps;
+    #[cfg(target_arch = "x86_64")]
+    pub use std::arch::x86_64::_mm_shuffle_ps;
+
+    pub type F32x4 = __m128;
+
+    pub fn load(a: &[f32]) -> F32x4 {
+        assert!(a.len() >= 4);
+        // SAFETY: sse is enabled for the target and the slice has 4 floats
+        unsafe { _mm_loadu_ps(a.as_ptr()) }
+    }
+
+    pub fn store(v: F32x4, out: &mut [f32]) {
+        assert!(out.len() >= 4);
+        // SAFETY: sse is enabled for the target and the slice has 4 floats
+        unsafe { _mm_storeu_ps(out.as_mut_ptr(), v) }
+    }
+
+    pub fn splat(v: f32) -> F32x4 {
+        // SAFETY: sse is enabled for the target, the intrinsic only touches registers
+        unsafe { _mm_set1_ps(v) }
+    }
+
+    pub fn add(a: F32x4, b: F32x4) -> F32x4 {
+        // SAFETY: sse is enabled for the target, the intrinsic only touches registers
+        unsafe { _mm_add_ps(a, b) }
+    }
+
+    pub fn sub(a: F32x4, b: F32x4) -> F32x4 {
+        // SAFETY: sse is enabled for the target, the intrinsic only touches registers
+        unsafe { _mm_sub_ps(a, b) }
+    }
+
+    pub fn mul(a: F32x4, b: F32x4) -> F32x4 {
+        // SAFETY: sse is enabled for the target, the intrinsic only touches registers
+        unsafe { _mm_mul_ps(a, b) }
+    }
+
+    pub fn div(a: F32x4, b: F32x4) -> F32x4 {
+        // SAFETY: sse is enabled for the target, the intrinsic only touches registers
+        unsafe { _mm_div_ps(a, b) }
+    }
+
+    // Lanes 0 and 1 from a, lanes 2 and 3 from b
+    macro_rules! shuffle {
+        ($a:expr, $b:expr, $i0:literal, $i1:literal, $i2:literal, $i3:literal) => {
+            // SAFETY: sse is enabled for the target, the intrinsic only touches registers
+            unsafe { _mm_shuffle_ps::<{ ($i3 << 6) | ($i2 << 4) | ($i1 << 2) | $i0 }>($a, $b) }
+        };
+    }
+    pub(super) use shuffle;
+}
+
+#[cfg(all(target_arch = "wasm32", target_feature = "simd128"))]
+mod lanes {
+    use std::arch::wasm32::*;
+
+    // Used by the shuffle! macro at the call site
+    pub use std::arch::wasm32::i32x4_shuffle;
+
+    pub type F32x4 = v128;
+
+    pub fn load(a: &[f32]) -> F32x4 {
+        f32x4(a[0], a[1], a[2], a[3])
+    }
+
+    pub fn store(v: F32x4, out: &mut [f32]) {
+        out[0] = f32x4_extract_lane::<0>(v);
+        out[1] = f32x4_extract_lane::<1>(v);
+        out[2] = f32x4_extract_lane::<2>(v);
+        out[3] = f32x4_extract_lane::<3>(v);
+    }
+
+    pub fn splat(v: f32) -> F32x4 {
+        f32x4_splat(v)
+    }
+
+    pub fn add(a: F32x4, b: F32x4) -> F32x4 {
+        f32x4_add(a, b)
+    }
+
+    pub fn sub(a: F32x4, b: F32x4) -> F32x4 {
+        f32x4_sub(a, b)
+    }
+
+    pub fn mul(a: F32x4, b: F32x4) -> F32x4 {
+        f32x4_mul(a, b)
+    }
+
+    pub fn div(a: F32x4, b: F32x4) -> F32x4 {
+        f32x4_div(a, b)
+    }
+
+    // Lanes 0 and 1 from a, lanes 2 and 3 from b
+    macro_rules! shuffle {
+        ($a:expr, $b:expr, $i0:literal, $i1:literal, $i2:literal, $i3:literal) => {
+            i32x4_shuffle::<$i0, $i1, { $i2 + 4 }, { $i3 + 4 }>($a, $b)
+        };
+    }
+    pub(super) use shuffle;
+}
+
+#[cfg(not(any(
+    all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse"
+    ),
+    all(target_arch = "wasm32", target_feature = "simd128")
+)))]
+mod lanes {
+    pub type F32x4 = [f32; 4];
+
+    pub fn load(a: &[f32]) -> F32x4 {
+        [a[0], a[1], a[2], a[3]]
+    }
+
+    pub fn store(v: F32x4, out: &mut [f32]) {
+        out[..4].copy_from_slice(&v);
+    }
+
+    pub fn splat(v: f32) -> F32x4 {
+        [v; 4]
+    }
+
+    pub fn add(a: F32x4, b: F32x4) -> F32x4 {
+        [a[0] + b[0], a[1] + b[1], a[2] + b[2], a[3] + b[3]]
+    }
+
+    pub fn sub(a: F32x4, b: F32x4) -> F32x4 {
+        [a[0] - b[0], a[1] - b[1], a[2] - b[2], a[3] - b[3]]
+    }
+
+    pub fn mul(a: F32x4, b: F32x4) -> F32x4 {
+        [a[0] * b[0], a[1] * b[1], a[2] * b[2], a[3] * b[3]]
+    }
+
+    pub fn div(a: F32x4, b: F32x4) -> F32x4 {
+        [a[0] / b[0], a[1] / b[1], a[2] / b[2], a[3] / b[3]]
+    }
+
+    // Lanes 0 and 1 from a, lanes 2 and 3 from b
+    macro_rules! shuffle {
+        ($a:expr, $b:expr, $i0:literal, $i1:literal, $i2:literal, $i3:literal) => {{
+            let (a, b) = ($a, $b);
+            [a[$i0], a[$i1], b[$i2], b[$i3]]
+        }};
+    }
+    pub(super) use shuffle;
+}
+
+use lanes::*;
+
+// True when mul, invert and transform_points run on SIMD registers
+pub const ENABLED: bool = cfg!(any(
+    all(
+        any(target_arch = "x86", target_arch = "x86_64"),
+        target_feature = "sse"
+    ),
+    all(target_arch = "wasm32", target_feature = "simd128")
+));
+
+// Matrix4x4 kernels per element type, f32 replaces them with the SIMD
+// versions below
+pub trait Mat4Kernels: Scalar {
+    fn mat4_mul(a: &Matrix4x4<Self>, b: &Matrix4x4<Self>) -> Matrix4x4<Self> {
+        a.mul_fallback(b)
+    }
+
+    fn mat4_invert(m: &Matrix4x4<Self>) -> Matrix4x4<Self>
+    where
+        Self: Float,
+    {
+        m.invert_fallback()
+    }
+
+    fn transform_points(points: &mut [Vector3<Self>], mx: &Matrix4x4<Self>)
+    where
+        Self: Float,
+    {
+        for p in points.iter_mut() {
+            *p *= *mx;
+        }
+    }
+}
+
+impl Mat4Kernels for f32 {
+    fn mat4_mul(a: &Matrix4x4<f32>, b: &Matrix4x4<f32>) -> Matrix4x4<f32> {
+        mat4_mul(a, b)
+    }
+
+    fn mat4_invert(m: &Matrix4x4<f32>) -> Matrix4x4<f32> {
+        mat4_invert(m)
+    }
+
+    fn transform_points(points: &mut [Vector3<f32>], mx: &Matrix4x4<f32>) {
+        transform_points(points, mx)
+    }
+}
+
+impl Mat4Kernels for f64 {}
+impl Mat4Kernels for i16 {}
+impl Mat4Kernels for i32 {}
+impl Mat4Kernels for i64 {}
+impl Mat4Kernels for u8 {}
+impl Mat4Kernels for u16 {}
+impl Mat4Kernels for u32 {}
+impl Mat4Kernels for u64 {}
+
+pub fn mat4_mul(a: &Matrix4x4, b: &Matrix4x4) -> Matrix4x4 {
+    let mut mx = Matrix4x4::default();
+
+    let a0 = load(&a.raw[0..4]);
+    let a1 = load(&a.raw[4..8]);
+    let a2 = load(&a.raw[8..12]);
+    let a3 = load(&a.raw[12..16]);
+
+    // Every column of the result is a combination of the columns of a
+    for i in 0..4 {
+        let b = &b.raw[i * 4..i * 4 + 4];
+        let mut r = mul(a0, splat(b[0]));
+        r = add(r, mul(a1, splat(b[1])));
+        r = add(r, mul(a2, splat(b[2])));
+        r = add(r, mul(a3, splat(b[3])));
+        store(r, &mut mx.raw[i * 4..i * 4 + 4]);
+    }
+
+    mx
+}
+
+pub fn mat4_invert(m: &Matrix4x4) -> Matrix4x4 {
+    let c0 = load(&m.raw[0..4]);
+    let c1 = load(&m.raw[4..8]);
+    let c2 = load(&m.raw[8..12]);
+    let c3 = load(&m.raw[12..16]);
+
+    // 2x2 sub determinants, same as b00..b11 of the scalar version
+    // b0 = (b00, b01, b02, b03)
+    let b0 = sub(
+        mul(shuffle!(c0, c0, 0, 0, 0, 1), shuffle!(c1, c1, 1, 2, 3, 2)),
+        mul(shuffle!(c0, c0, 1, 2, 3, 2), shuffle!(c1, c1, 0, 0, 0, 1)),
+    );
+    // b1 = (b04, b05, b10, b11)
+    let b1 = sub(
+        mul(shuffle!(c0, c2, 1, 2, 1, 2), shuffle!(c1, c3, 3, 3, 3, 3)),
+        mul(shuffle!(c0, c2, 3, 3, 3, 3), shuffle!(c1, c3, 1, 2, 1, 2)),
+    );
+    // b2 = (b06, b07, b08, b09)
+    let b2 = sub(
+        mul(shuffle!(c2, c2, 0, 0, 0, 1), shuffle!(c3, c3, 1, 2, 3, 2)),
+        mul(shuffle!(c2, c2, 1, 2, 3, 2), shuffle!(c3, c3, 0, 0, 0, 1)),
+    );
+
+    let mut b = [0.0; 12];
+    store(b0, &mut b[0..4]);
+    store(b1, &mut b[4..8]);
+    store(b2, &mut b[8..12]);
+    let (b00, b01, b02, b03) = (b[0], b[1], b[2], b[3]);
+    let (b04, b05, b10, b11) = (b[4], b[5], b[6], b[7]);
+    let (b06, b07, b08, b09) = (b[8], b[9], b[10], b[11]);
+
+    let det = b00 * b11 - b01 * b10 + b02 * b09 + b03 * b08 - b04 * b07 + b05 * b06;
+    if det == 0.0 {
+        return Matrix4x4::new();
+    }
+
+    // Rows of the matrix with swapped pairs, r1 = (a11, a01, a31, a21)
+    let t0 = shuffle!(c0, c1, 0, 1, 0, 1);
+    let t1 = shuffle!(c2, c3, 0, 1, 0, 1);
+    let t2 = shuffle!(c0, c1, 2, 3, 2, 3);
+    let t3 = shuffle!(c2, c3, 2, 3, 2, 3);
+    let r0 = shuffle!(t0, t1, 2, 0, 2, 0);
+    let r1 = shuffle!(t0, t1, 3, 1, 3, 1);
+    let r2 = shuffle!(t2, t3, 2, 0, 2, 0);
+    let r3 = shuffle!(t2, t3, 3, 1, 3, 1);
+
+    // Sub determinant pairs, b11_5 = (b11, b11, b05, b05)
+    let b11_5 = shuffle!(b1, b1, 3, 3, 1, 1);
+    let b10_4 = shuffle!(b1, b1, 2, 2, 0, 0);
+    let b9_3 = shuffle!(b2, b0, 3, 3, 3, 3);
+    let b8_2 = shuffle!(b2, b0, 2, 2, 2, 2);
+    let b7_1 = shuffle!(b2, b0, 1, 1, 1, 1);
+    let b6_0 = shuffle!(b2, b0, 0, 0, 0, 0);
+
+    let inv = 1.0 / det;
+    let s = mul(load(&[1.0, -1.0, 1.0, -1.0]), splat(inv));
+
+    let o0 = add(sub(mul(r1, b11_5), mul(r2, b10_4)), mul(r3, b9_3));
+    let o1 = sub(sub(mul(r2, b8_2), mul(r0, b11_5)), mul(r3, b7_1));
+    let o2 = add(sub(mul(r0, b10_4), mul(r1, b8_2)), mul(r3, b6_0));
+    let o3 = sub(sub(mul(r1, b7_1), mul(r0, b9_3)), mul(r2, b6_0));
+
+    let mut mx = Matrix4x4::new();
+    store(mul(o0, s), &mut mx.raw[0..4]);
+    store(mul(o1, s), &mut mx.raw[4..8]);
+    store(mul(o2, s), &mut mx.raw[8..12]);
+    store(mul(o3, s), &mut mx.raw[12..16]);
+    mx
+}
+
+pub fn transform_points(points: &mut [Vector3], mx: &Matrix4x4) {
+    let c0 = load(&mx.raw[0..4]);
+    let c1 = load(&mx.raw[4..8]);
+    let c2 = load(&mx.raw[8..12]);
+    let c3 = load(&mx.raw[12..16]);
+    let mut out = [0.0; 4];
+
+    for p in points.iter_mut() {
+        let mut r = mul(c0, splat(p.x));
+        r = add(r, mul(c1, splat(p.y)));
+        r = add(r, mul(c2, splat(p.z)));
+        r = add(r, c3);
+
+        store(r, &mut out);
+        let w = if out[3] == 0.0 { 1.0 } else { out[3] };
+        store(div(r, splat(w)), &mut out);
+
+        p.x = out[0];
+        p.y = out[1];
+        p.z = out[2];
+    }
+}
@@ -0,0 +1,166 @@
+use crate::math::la::matrix4::Matrix4x4;
+use crate::math::la::quaternion::Quaternion;
+use crate::math::la::vector3::Vector3;
+use crate::math::number::scalar::Float;
+use std::{fmt, ops};
+
+// Translation, rotation and scale applied as scale first, then rotation and
+// then translation, same as Matrix4x4::from_trs
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    serde(bound(
+        serialize = "T: serde::Serialize + Copy",
+        deserialize = "T: serde::Deserialize<'de>"
+    ))
+)]
+#[repr(C)]
+pub struct Transform<T = f32> {
+    pub translation: Vector3<T>,
+    pub rotation: Quaternion<T>,
+    pub scale: Vector3<T>,
+}
+
+// Print
+impl<T: fmt::Display> fmt::Display for Transform<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Transform({}, {}, {})",
+            self.translation, self.rotation, self.scale
+        )
+    }
+}
+
+impl<T: Float> Default for Transform<T> {
+    fn default() -> Transform<T> {
+        Transform::identity()
+    }
+}
+
+// Compose parent * child, the result moves child space to parent's parent
+impl<T: Float> ops::Mul<Transform<T>> for Transform<T> {
+    type Output = Transform<T>;
+
+    fn mul(self, child: Transform<T>) -> Transform<T> {
+        Transform {
+            translation: self.transform_point(child.translation),
+            rotation: self.rotation * child.rotation,
+            scale: self.scale * child.scale,
+        }
+    }
+}
+
+impl<T: Float> ops::MulAssign<Transform<T>> for Transform<T> {
+    fn mul_assign(&mut self, child: Transform<T>) {
+        *self = *self * child;
+    }
+}
+
+// Apply Transform on point
+impl<T: Float> ops::Mul<Vector3<T>> for Transform<T> {
+    type Output = Vector3<T>;
+
+    fn mul(self, p: Vector3<T>) -> Vector3<T> {
+        self.transform_point(p)
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Float> Transform<T> {
+    pub const fn new(
+        translation: Vector3<T>,
+        rotation: Quaternion<T>,
+        scale: Vector3<T>,
+    ) -> Transform<T> {
+        Transform {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub const fn identity() -> Transform<T> {
+        Transform {
+            translation: Vector3::zero(),
+            rotation: Quaternion::identity(),
+            scale: Vector3::one(),
+        }
+    }
+
+    pub fn from_translation(translation: Vector3<T>) -> Transform<T> {
+        Transform {
+            translation,
+            ..Transform::identity()
+        }
+    }
+
+    pub fn from_rotation(rotation: Quaternion<T>) -> Transform<T> {
+        Transform {
+            rotation,
+            ..Transform::identity()
+        }
+    }
+
+    pub fn from_scale(scale: Vector3<T>) -> Transform<T> {
+        Transform {
+            scale,
+            ..Transform::identity()
+        }
+    }
+
+    // Shear of the matrix is lost, see Matrix4x4::decompose
+    pub fn from_matrix4x4(mx: Matrix4x4<T>) -> Transform<T> {
+        let (translation, rotation, scale) = mx.decompose();
+        Transform {
+            translation,
+            rotation,
+            scale,
+        }
+    }
+
+    pub fn to_matrix4x4(self) -> Matrix4x4<T> {
+        Matrix4x4::from_trs(self.translation, self.rotation, self.scale)
+    }
+
+    pub fn transform_point(self, p: Vector3<T>) -> Vector3<T> {
+        self.rotation.rotate_vector(p * self.scale) + self.translation
+    }
+
+    // Directions get scale and rotation but no translation
+    pub fn transform_vector(self, v: Vector3<T>) -> Vector3<T> {
+        self.rotation.rotate_vector(v * self.scale)
+    }
+
+    // Exact for uniform scale. Non uniform scale under a rotation would need
+    // shear, which this type can't hold, so it is approximated like in mul.
+    // Zero scale axes stay zero
+    pub fn inverse(self) -> Transform<T> {
+        let inv = |v: T| if v == T::ZERO { T::ZERO } else { T::ONE / v };
+        let scale = Vector3::new(inv(self.scale.x), inv(self.scale.y), inv(self.scale.z));
+        let rotation = self.rotation.conjugate();
+        Transform {
+            translation: rotation.rotate_vector(-self.translation) * scale,
+            rotation,
+            scale,
+        }
+    }
+
+    // Lerp translation and scale, slerp rotation
+    pub fn lerp(a: Transform<T>, b: Transform<T>, t: T) -> Transform<T> {
+        Transform {
+            translation: a.translation + (b.translation - a.translation) * t,
+            rotation: Quaternion::slerp(a.rotation, b.rotation, t),
+            scale: a.scale + (b.scale - a.scale) * t,
+        }
+    }
+
+    pub fn cast<U: Float>(self) -> Transform<U> {
+        Transform {
+            translation: self.translation.cast(),
+            rotation: self.rotation.cast(),
+            scale: self.scale.cast(),
+        }
+    }
+}
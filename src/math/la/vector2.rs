@@ -1,76 +1,94 @@
+#[cfg(feature = "serde")]
+use crate::math::la::macros::impl_serde_array;
+use crate::math::la::macros::{impl_bytes, impl_vector_ops};
+use crate::math::la::matrix2::Matrix2x2;
+use crate::math::la::matrix3::Matrix3x3;
+use crate::math::number::scalar::{Float, Scalar};
 use std::fmt;
 use std::ops;
 
-#[derive(Copy, Clone, Default, Debug)]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 #[repr(C)]
-pub struct Vector2 {
-    pub x: f32,
-    pub y: f32,
+pub struct Vector2<T = f32> {
+    pub x: T,
+    pub y: T,
 }
 
 // Print
-impl fmt::Display for Vector2 {
+impl<T: fmt::Display> fmt::Display for Vector2<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Vector2({}, {})", self.x, self.y)
     }
 }
 
-// Add Vector2 + Vector2
-impl ops::Add<Vector2> for Vector2 {
-    type Output = Vector2;
+impl_vector_ops!(Vector2 { x: 0, y: 1 });
 
-    fn add(self, _rhs: Vector2) -> Vector2 {
-        return Vector2 {
-            x: self.x + _rhs.x,
-            y: self.y + _rhs.y,
-        };
-    }
-}
-
-// Sub Vector2 - Vector2
-impl ops::Sub<Vector2> for Vector2 {
-    type Output = Vector2;
+// Apply matrix2x2 on Vector2
+impl<T: Scalar> ops::Mul<Matrix2x2<T>> for Vector2<T> {
+    type Output = Vector2<T>;
 
-    fn sub(self, _rhs: Vector2) -> Vector2 {
+    fn mul(self, mx: Matrix2x2<T>) -> Vector2<T> {
         Vector2 {
-            x: self.x - _rhs.x,
-            y: self.y - _rhs.y,
+            x: mx.raw[0] * self.x + mx.raw[2] * self.y,
+            y: mx.raw[1] * self.x + mx.raw[3] * self.y,
         }
     }
 }
 
-// Scale Vector2 * f32
-impl ops::Mul<f32> for Vector2 {
-    type Output = Vector2;
+// Apply 2D homogeneous matrix3x3 on Vector2
+impl<T: Scalar> ops::Mul<Matrix3x3<T>> for Vector2<T> {
+    type Output = Vector2<T>;
 
-    fn mul(self, _rhs: f32) -> Vector2 {
+    fn mul(self, mx: Matrix3x3<T>) -> Vector2<T> {
         Vector2 {
-            x: self.x * _rhs,
-            y: self.y * _rhs,
+            x: mx.raw[0] * self.x + mx.raw[3] * self.y + mx.raw[6],
+            y: mx.raw[1] * self.x + mx.raw[4] * self.y + mx.raw[7],
         }
     }
 }
 
 #[allow(dead_code)]
-impl Vector2 {
-    pub const fn new(x: f32, y: f32) -> Vector2 {
+impl<T: Scalar> Vector2<T> {
+    pub const fn new(x: T, y: T) -> Vector2<T> {
         Vector2 { x, y }
     }
 
-    pub fn add_xy(mut self, x: f32, y: f32) -> Vector2 {
+    pub fn add_xy(mut self, x: T, y: T) -> Vector2<T> {
         self.x += x;
         self.y += y;
         self
     }
 
-    pub fn length(self) -> f32 {
-        f32::sqrt((self.x * self.x) + (self.y * self.y))
+    pub fn dot(self, v2: Vector2<T>) -> T {
+        self.x * v2.x + self.y * v2.y
+    }
+
+    // Z of the 3D cross product, positive when v2 is counter clockwise
+    pub fn cross(self, v2: Vector2<T>) -> T {
+        self.x * v2.y - self.y * v2.x
+    }
+
+    pub fn cast<U: Scalar>(self) -> Vector2<U> {
+        Vector2 {
+            x: U::from_f64(self.x.to_f64()),
+            y: U::from_f64(self.y.to_f64()),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Float> Vector2<T> {
+    pub fn length(self) -> T {
+        T::sqrt((self.x * self.x) + (self.y * self.y))
     }
 
-    pub fn normalize(self) -> Vector2 {
+    pub fn normalize(self) -> Vector2<T> {
         let l = self.length();
-        if l == 0.0 {
-            return Vector2 { x: 0.0, y: 0.0 };
+        if l == T::ZERO {
+            return Vector2 {
+                x: T::ZERO,
+                y: T::ZERO,
+            };
         }
         Vector2 {
             x: self.x / l,
@@ -78,20 +96,44 @@ impl Vector2 {
         }
     }
 
-    pub fn dot(self, v2: Vector2) -> f32 {
-        self.x * v2.x + self.y * v2.y
-    }
-
-    pub fn distance_to(self, to: Vector2) -> f32 {
+    pub fn distance_to(self, to: Vector2<T>) -> T {
         let a = self.x - to.x;
         let b = self.y - to.y;
-        f32::sqrt(a * a + b * b)
+        T::sqrt(a * a + b * b)
     }
+}
 
-    pub fn from_bytes(b: &[u8]) -> Vector2 {
-        Vector2 {
-            x: f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
-            y: f32::from_le_bytes([b[4], b[5], b[6], b[7]]),
-        }
+impl_bytes!(Vector2, 2);
+
+impl<T> From<[T; 2]> for Vector2<T> {
+    fn from([x, y]: [T; 2]) -> Vector2<T> {
+        Vector2 { x, y }
+    }
+}
+
+impl<T> From<Vector2<T>> for [T; 2] {
+    fn from(v: Vector2<T>) -> [T; 2] {
+        [v.x, v.y]
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde_array!(Vector2, 2);
+
+impl From<Vector2<f32>> for Vector2<f64> {
+    fn from(v: Vector2<f32>) -> Vector2<f64> {
+        v.cast()
+    }
+}
+
+impl From<Vector2<i32>> for Vector2<f32> {
+    fn from(v: Vector2<i32>) -> Vector2<f32> {
+        v.cast()
+    }
+}
+
+impl From<Vector2<i32>> for Vector2<f64> {
+    fn from(v: Vector2<i32>) -> Vector2<f64> {
+        v.cast()
     }
 }
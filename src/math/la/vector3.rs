@@ -1,159 +1,150 @@
+#[cfg(feature = "serde")]
+use crate::math::la::macros::impl_serde_array;
+use crate::math::la::macros::{impl_bytes, impl_vector_ops};
+use crate::math::la::matrix3::Matrix3x3;
 use crate::math::la::matrix4::Matrix4x4;
 use crate::math::la::vector4::Vector4;
+use crate::math::number::scalar::{Float, Scalar};
 use std::fmt;
 use std::ops;
 
-#[derive(Copy, Clone, Default, Debug)]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 #[repr(C)]
-pub struct Vector3 {
-    pub x: f32,
-    pub y: f32,
-    pub z: f32,
+pub struct Vector3<T = f32> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
 }
 
 // Print
-impl fmt::Display for Vector3 {
+impl<T: fmt::Display> fmt::Display for Vector3<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Vector3({}, {}, {})", self.x, self.y, self.z)
     }
 }
 
-// Add Vector3 + Vector3
-impl ops::Add<Vector3> for Vector3 {
-    type Output = Vector3;
-
-    fn add(self, _rhs: Vector3) -> Vector3 {
-        Vector3 {
-            x: self.x + _rhs.x,
-            y: self.y + _rhs.y,
-            z: self.z + _rhs.z,
-        }
-    }
-}
-
-// Sub Vector3 - Vector3
-impl ops::Sub<Vector3> for Vector3 {
-    type Output = Vector3;
-
-    fn sub(self, _rhs: Vector3) -> Vector3 {
-        Vector3 {
-            x: self.x - _rhs.x,
-            y: self.y - _rhs.y,
-            z: self.z - _rhs.z,
-        }
-    }
-}
-
-// Mul Vector3 * f32
-impl ops::Mul<f32> for Vector3 {
-    type Output = Vector3;
-
-    fn mul(self, _rhs: f32) -> Vector3 {
-        Vector3 {
-            x: self.x * _rhs,
-            y: self.y * _rhs,
-            z: self.z * _rhs,
-        }
-    }
-}
+impl_vector_ops!(Vector3 { x: 0, y: 1, z: 2 });
 
 // Apply matrix4x4 on Vector3
-impl ops::Mul<Matrix4x4> for Vector3 {
-    type Output = Vector3;
+impl<T: Float> ops::Mul<Matrix4x4<T>> for Vector3<T> {
+    type Output = Vector3<T>;
 
-    fn mul(self, mx: Matrix4x4) -> Vector3 {
+    fn mul(self, mx: Matrix4x4<T>) -> Vector3<T> {
         let mut v = Vector3::default();
 
         let mut w = mx.raw[3] * self.x + mx.raw[7] * self.y + mx.raw[11] * self.z + mx.raw[15];
-        if w == 0.0 {
-            w = 1.0;
+        if w == T::ZERO {
+            w = T::ONE;
         }
         v.x = (mx.raw[0] * self.x + mx.raw[4] * self.y + mx.raw[8] * self.z + mx.raw[12]) / w;
         v.y = (mx.raw[1] * self.x + mx.raw[5] * self.y + mx.raw[9] * self.z + mx.raw[13]) / w;
         v.z = (mx.raw[2] * self.x + mx.raw[6] * self.y + mx.raw[10] * self.z + mx.raw[14]) / w;
 
-        return v;
+        v
     }
 }
-impl ops::MulAssign<Matrix4x4> for Vector3 {
-    fn mul_assign(&mut self, mx: Matrix4x4) {
-        let x = self.x;
-        let y = self.y;
-        let z = self.z;
-
-        let mut w = mx.raw[3] * x + mx.raw[7] * y + mx.raw[11] * z + mx.raw[15];
-        if w == 0.0 {
-            w = 1.0;
-        }
-        self.x = (mx.raw[0] * x + mx.raw[4] * y + mx.raw[8] * z + mx.raw[12]) / w;
-        self.y = (mx.raw[1] * x + mx.raw[5] * y + mx.raw[9] * z + mx.raw[13]) / w;
-        self.z = (mx.raw[2] * x + mx.raw[6] * y + mx.raw[10] * z + mx.raw[14]) / w;
+impl<T: Float> ops::MulAssign<Matrix4x4<T>> for Vector3<T> {
+    fn mul_assign(&mut self, mx: Matrix4x4<T>) {
+        *self = *self * mx;
     }
 }
 
-// Add Vector3 += Vector3
-impl ops::AddAssign<Vector3> for Vector3 {
-    fn add_assign(&mut self, other: Vector3) {
-        *self = Self {
-            x: self.x + other.x,
-            y: self.y + other.y,
-            z: self.z + other.z,
+// Apply matrix3x3 on Vector3
+impl<T: Scalar> ops::Mul<Matrix3x3<T>> for Vector3<T> {
+    type Output = Vector3<T>;
+
+    fn mul(self, mx: Matrix3x3<T>) -> Vector3<T> {
+        Vector3 {
+            x: mx.raw[0] * self.x + mx.raw[3] * self.y + mx.raw[6] * self.z,
+            y: mx.raw[1] * self.x + mx.raw[4] * self.y + mx.raw[7] * self.z,
+            z: mx.raw[2] * self.x + mx.raw[5] * self.y + mx.raw[8] * self.z,
         }
     }
 }
-
-// Sub Vector3 -= Vector3
-impl ops::SubAssign<Vector3> for Vector3 {
-    fn sub_assign(&mut self, other: Vector3) {
-        *self = Self {
-            x: self.x - other.x,
-            y: self.y - other.y,
-            z: self.z - other.z,
-        }
+impl<T: Scalar> ops::MulAssign<Matrix3x3<T>> for Vector3<T> {
+    fn mul_assign(&mut self, mx: Matrix3x3<T>) {
+        *self = *self * mx;
     }
 }
 
 #[allow(dead_code)]
-impl Vector3 {
-    pub const fn new(x: f32, y: f32, z: f32) -> Vector3 {
+impl<T: Scalar> Vector3<T> {
+    pub const fn new(x: T, y: T, z: T) -> Vector3<T> {
         Vector3 { x, y, z }
     }
 
-    pub const fn zero() -> Vector3 {
+    pub const fn zero() -> Vector3<T> {
         Vector3 {
-            x: 0.0,
-            y: 0.0,
-            z: 0.0,
+            x: T::ZERO,
+            y: T::ZERO,
+            z: T::ZERO,
         }
     }
 
-    pub const fn one() -> Vector3 {
+    pub const fn one() -> Vector3<T> {
         Vector3 {
-            x: 1.0,
-            y: 1.0,
-            z: 1.0,
+            x: T::ONE,
+            y: T::ONE,
+            z: T::ONE,
         }
     }
 
-    pub fn add_xyz(mut self, x: f32, y: f32, z: f32) -> Vector3 {
+    pub fn add_xyz(mut self, x: T, y: T, z: T) -> Vector3<T> {
         self.x += x;
         self.y += y;
         self.z += z;
         self
     }
 
-    pub fn length(self) -> f32 {
-        f32::sqrt((self.x * self.x) + (self.y * self.y) + (self.z * self.z))
+    pub fn dot(self, v2: Vector3<T>) -> T {
+        self.x * v2.x + self.y * v2.y + self.z * v2.z
+    }
+
+    pub fn cross(self, v2: Vector3<T>) -> Vector3<T> {
+        Vector3 {
+            x: self.y * v2.z - self.z * v2.y,
+            y: self.z * v2.x - self.x * v2.z,
+            z: self.x * v2.y - self.y * v2.x,
+        }
+    }
+
+    pub fn scale(mut self, v: T) -> Vector3<T> {
+        self.x *= v;
+        self.y *= v;
+        self.z *= v;
+        self
+    }
+
+    // Kept for old callers, shadows ops::Mul::mul
+    #[deprecated(note = "use the `*` operator")]
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(self, v: Vector3<T>) -> Vector3<T> {
+        self * v
+    }
+
+    pub fn cast<U: Scalar>(self) -> Vector3<U> {
+        Vector3 {
+            x: U::from_f64(self.x.to_f64()),
+            y: U::from_f64(self.y.to_f64()),
+            z: U::from_f64(self.z.to_f64()),
+        }
+    }
+
+    pub fn from_vector4(v: Vector4<T>) -> Vector3<T> {
+        Vector3::new(v.x, v.y, v.z)
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Float> Vector3<T> {
+    pub fn length(self) -> T {
+        T::sqrt((self.x * self.x) + (self.y * self.y) + (self.z * self.z))
     }
 
-    pub fn normalize(self) -> Vector3 {
+    pub fn normalize(self) -> Vector3<T> {
         let l = self.length();
-        if l == 0.0 {
-            return Vector3 {
-                x: 0.0,
-                y: 0.0,
-                z: 0.0,
-            };
+        if l == T::ZERO {
+            return Vector3::zero();
         }
         Vector3 {
             x: self.x / l,
@@ -162,54 +153,67 @@ impl Vector3 {
         }
     }
 
-    pub fn dot(self, v2: Vector3) -> f32 {
-        self.x * v2.x + self.y * v2.y + self.z * v2.z
-    }
-
-    pub fn distance_to(self, to: Vector3) -> f32 {
+    pub fn distance_to(self, to: Vector3<T>) -> T {
         let a = self.x - to.x;
         let b = self.y - to.y;
         let c = self.z - to.z;
-        f32::sqrt(a * a + b * b + c * c)
-    }
-
-    pub fn scale(mut self, v: f32) -> Vector3 {
-        self.x *= v;
-        self.y *= v;
-        self.z *= v;
-        self
-    }
-
-    pub fn mul(mut self, v: Vector3) -> Vector3 {
-        self.x *= v.x;
-        self.y *= v.y;
-        self.z *= v.z;
-        self
+        T::sqrt(a * a + b * b + c * c)
     }
 
-    pub fn to_degrees(mut self) -> Vector3 {
+    pub fn to_degrees(mut self) -> Vector3<T> {
         self.x = self.x.to_degrees();
         self.y = self.y.to_degrees();
         self.z = self.z.to_degrees();
         self
     }
 
-    pub fn to_radians(mut self) -> Vector3 {
+    pub fn to_radians(mut self) -> Vector3<T> {
         self.x = self.x.to_radians();
         self.y = self.y.to_radians();
         self.z = self.z.to_radians();
         self
     }
 
-    pub fn from_bytes(b: &[u8]) -> Vector3 {
-        Vector3 {
-            x: f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
-            y: f32::from_le_bytes([b[4], b[5], b[6], b[7]]),
-            z: f32::from_le_bytes([b[8], b[9], b[10], b[11]]),
+    // Average of the points, zero for an empty slice
+    pub fn centroid(points: &[Vector3<T>]) -> Vector3<T> {
+        if points.is_empty() {
+            return Vector3::zero();
         }
+        points.iter().sum::<Vector3<T>>() / T::from_f64(points.len() as f64)
     }
+}
 
-    pub fn from_vector4(v: Vector4) -> Vector3 {
-        Vector3::new(v.x, v.y, v.z)
+impl_bytes!(Vector3, 3);
+
+impl<T> From<[T; 3]> for Vector3<T> {
+    fn from([x, y, z]: [T; 3]) -> Vector3<T> {
+        Vector3 { x, y, z }
+    }
+}
+
+impl<T> From<Vector3<T>> for [T; 3] {
+    fn from(v: Vector3<T>) -> [T; 3] {
+        [v.x, v.y, v.z]
+    }
+}
+
+#[cfg(feature = "serde")]
+impl_serde_array!(Vector3, 3);
+
+impl From<Vector3<f32>> for Vector3<f64> {
+    fn from(v: Vector3<f32>) -> Vector3<f64> {
+        v.cast()
+    }
+}
+
+impl From<Vector3<i32>> for Vector3<f32> {
+    fn from(v: Vector3<i32>) -> Vector3<f32> {
+        v.cast()
+    }
+}
+
+impl From<Vector3<i32>> for Vector3<f64> {
+    fn from(v: Vector3<i32>) -> Vector3<f64> {
+        v.cast()
     }
 }
@@ -1,94 +1,99 @@
-use crate::math::la::matrix4::Matrix4x4;
+#[cfg(feature = "serde")]
+use crate::math::la::macros::impl_serde_array;
+use crate::math::la::macros::{impl_bytes, impl_vector_ops};
 use crate::math::la::vector3::Vector3;
+use crate::math::number::scalar::{Float, Scalar};
 use std::fmt;
-use std::ops;
 
-#[derive(Copy, Clone, Default, Debug)]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
 #[repr(C)]
-pub struct Vector4 {
-    pub x: f32,
-    pub y: f32,
-    pub z: f32,
-    pub w: f32,
+pub struct Vector4<T = f32> {
+    pub x: T,
+    pub y: T,
+    pub z: T,
+    pub w: T,
 }
 
 // Print
-impl fmt::Display for Vector4 {
+impl<T: fmt::Display> fmt::Display for Vector4<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "Vector4({}, {}, {}, {})", self.x, self.y, self.z, self.w)
     }
 }
 
-// Add Vector4 + Vector4
-impl ops::Add<Vector4> for Vector4 {
-    type Output = Vector4;
+impl_vector_ops!(Vector4 {
+    x: 0,
+    y: 1,
+    z: 2,
+    w: 3
+});
 
-    fn add(self, _rhs: Vector4) -> Vector4 {
-        Vector4 {
-            x: self.x + _rhs.x,
-            y: self.y + _rhs.y,
-            z: self.z + _rhs.z,
-            w: self.w + _rhs.w,
-        }
+#[allow(dead_code)]
+impl<T: Scalar> Vector4<T> {
+    pub const fn new(x: T, y: T, z: T, w: T) -> Vector4<T> {
+        Vector4 { x, y, z, w }
     }
-}
-
-// Sub Vector4 - Vector4
-impl ops::Sub<Vector4> for Vector4 {
-    type Output = Vector4;
 
-    fn sub(self, _rhs: Vector4) -> Vector4 {
+    pub const fn zero() -> Vector4<T> {
         Vector4 {
-            x: self.x - _rhs.x,
-            y: self.y - _rhs.y,
-            z: self.z - _rhs.z,
-            w: self.w - _rhs.w,
+            x: T::ZERO,
+            y: T::ZERO,
+            z: T::ZERO,
+            w: T::ZERO,
         }
     }
-}
 
-// Mul Vector4 * f32
-impl ops::Mul<f32> for Vector4 {
-    type Output = Vector4;
+    pub fn scale(mut self, v: T) -> Vector4<T> {
+        self.x *= v;
+        self.y *= v;
+        self.z *= v;
+        self.w *= v;
+        self
+    }
+
+    // Kept for old callers, shadows ops::Mul::mul
+    #[deprecated(note = "use the `*` operator")]
+    #[allow(clippy::should_implement_trait)]
+    pub fn mul(self, v: Vector4<T>) -> Vector4<T> {
+        self * v
+    }
 
-    fn mul(self, _rhs: f32) -> Vector4 {
-        Vector4 {
-            x: self.x * _rhs,
-            y: self.y * _rhs,
-            z: self.z * _rhs,
-            w: self.w * _rhs,
-        }
+    pub fn dot(self, v2: Vector4<T>) -> T {
+        self.x * v2.x + self.y * v2.y + self.z * v2.z + self.w * v2.w
     }
-}
 
-#[allow(dead_code)]
-impl Vector4 {
-    pub const fn new(x: f32, y: f32, z: f32, w: f32) -> Vector4 {
+    pub fn lerp(a: Vector4<T>, b: Vector4<T>, t: T) -> Vector4<T> {
+        let x = a.x + (b.x - a.x) * t;
+        let y = a.y + (b.y - a.y) * t;
+        let z = a.z + (b.z - a.z) * t;
+        let w = a.w + (b.w - a.w) * t;
         Vector4 { x, y, z, w }
     }
 
-    pub const fn zero() -> Vector4 {
+    pub fn cast<U: Scalar>(self) -> Vector4<U> {
         Vector4 {
-            x: 0.0,
-            y: 0.0,
-            z: 0.0,
-            w: 0.0,
+            x: U::from_f64(self.x.to_f64()),
+            y: U::from_f64(self.y.to_f64()),
+            z: U::from_f64(self.z.to_f64()),
+            w: U::from_f64(self.w.to_f64()),
         }
     }
 
-    pub fn length(self) -> f32 {
-        f32::sqrt((self.x * self.x) + (self.y * self.y) + (self.z * self.z) + (self.w * self.w))
+    pub fn from_vector3(v: Vector3<T>) -> Vector4<T> {
+        Vector4::new(v.x, v.y, v.z, T::ZERO)
+    }
+}
+
+#[allow(dead_code)]
+impl<T: Float> Vector4<T> {
+    pub fn length(self) -> T {
+        T::sqrt((self.x * self.x) + (self.y * self.y) + (self.z * self.z) + (self.w * self.w))
     }
 
-    pub fn normalize(self) -> Vector4 {
+    pub fn normalize(self) -> Vector4<T> {
         let l = self.length();
-        if l == 0.0 {
-            return Vector4 {
-                x: 0.0,
-                y: 0.0,
-                z: 0.0,
-                w: 0.0,
-            };
+        if l == T::ZERO {
+            return Vector4::zero();
         }
         Vector4 {
             x: self.x / l,
@@ -97,41 +102,39 @@ impl Vector4 {
             w: self.w / l,
         }
     }
+}
 
-    pub fn scale(mut self, v: f32) -> Vector4 {
-        self.x *= v;
-        self.y *= v;
-        self.z *= v;
-        self.w *= v;
-        self
+impl_bytes!(Vector4, 4);
+
+impl<T> From<[T; 4]> for Vector4<T> {
+    fn from([x, y, z, w]: [T; 4]) -> Vector4<T> {
+        Vector4 { x, y, z, w }
     }
+}
 
-    pub fn mul(mut self, v: Vector4) -> Vector4 {
-        self.x *= v.x;
-        self.y *= v.y;
-        self.z *= v.z;
-        self.w *= v.w;
-        self
+impl<T> From<Vector4<T>> for [T; 4] {
+    fn from(v: Vector4<T>) -> [T; 4] {
+        [v.x, v.y, v.z, v.w]
     }
+}
 
-    pub fn lerp(a: Vector4, b: Vector4, t: f32) -> Vector4 {
-        let x = a.x + (b.x - a.x) * t;
-        let y = a.y + (b.y - a.y) * t;
-        let z = a.z + (b.z - a.z) * t;
-        let w = a.w + (b.w - a.w) * t;
-        Vector4 { x, y, z, w }
+#[cfg(feature = "serde")]
+impl_serde_array!(Vector4, 4);
+
+impl From<Vector4<f32>> for Vector4<f64> {
+    fn from(v: Vector4<f32>) -> Vector4<f64> {
+        v.cast()
     }
+}
 
-    pub fn from_bytes(b: &[u8]) -> Vector4 {
-        Vector4 {
-            x: f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
-            y: f32::from_le_bytes([b[4], b[5], b[6], b[7]]),
-            z: f32::from_le_bytes([b[8], b[9], b[10], b[11]]),
-            w: f32::from_le_bytes([b[12], b[13], b[14], b[15]]),
-        }
+impl From<Vector4<i32>> for Vector4<f32> {
+    fn from(v: Vector4<i32>) -> Vector4<f32> {
+        v.cast()
     }
+}
 
-    pub fn from_vector3(v: Vector3) -> Vector4 {
-        Vector4::new(v.x, v.y, v.z, 0.0)
+impl From<Vector4<i32>> for Vector4<f64> {
+    fn from(v: Vector4<i32>) -> Vector4<f64> {
+        v.cast()
     }
 }
@@ -0,0 +1,97 @@
+// IEEE 754 binary16 stored as u16. Conversions round to nearest even, keep
+// denormals, infinities and NaN, values past 65504 become infinity
+const F32_EXP_BIAS: i32 = 127;
+const F16_EXP_BIAS: i32 = 15;
+
+pub const ONE: u16 = 0x3C00;
+pub const INFINITY: u16 = 0x7C00;
+pub const NEG_INFINITY: u16 = 0xFC00;
+pub const NAN: u16 = 0x7E00;
+// Largest finite value, 65504
+pub const MAX: u16 = 0x7BFF;
+// Smallest positive denormal, 2^-24
+pub const MIN_POSITIVE_SUBNORMAL: u16 = 0x0001;
+
+pub fn from_f32(v: f32) -> u16 {
+    let bits = v.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exp = ((bits >> 23) & 0xFF) as i32;
+    let man = bits & 0x7F_FFFF;
+
+    // Infinity and NaN, NaN keeps the top of the payload and stays quiet
+    if exp == 0xFF {
+        if man == 0 {
+            return sign | INFINITY;
+        }
+        return sign | NAN | (man >> 13) as u16;
+    }
+
+    let e = exp - F32_EXP_BIAS + F16_EXP_BIAS;
+    if e >= 0x1F {
+        return sign | INFINITY;
+    }
+
+    // Denormal or zero, shift the mantissa with its implicit bit into place
+    if e <= 0 {
+        if e < -10 {
+            return sign;
+        }
+        let m = man | 0x80_0000;
+        let shift = (14 - e) as u32;
+        return sign | round_shift(m, shift) as u16;
+    }
+
+    // A carry out of the mantissa moves into the exponent, which is also
+    // how 65520 and up round to infinity
+    let h = ((e as u32) << 10) | (man >> 13);
+    let rem = man & 0x1FFF;
+    let h = if rem > 0x1000 || (rem == 0x1000 && h & 1 == 1) {
+        h + 1
+    } else {
+        h
+    };
+    sign | h as u16
+}
+
+pub fn to_f32(h: u16) -> f32 {
+    let sign = ((h & 0x8000) as u32) << 16;
+    let exp = ((h >> 10) & 0x1F) as i32;
+    let man = (h & 0x3FF) as u32;
+
+    match exp {
+        0 => {
+            // Denormals are man * 2^-24, exact in f32
+            let v = man as f32 * (1.0 / 16_777_216.0);
+            if sign != 0 {
+                -v
+            } else {
+                v
+            }
+        }
+        0x1F => f32::from_bits(sign | 0x7F80_0000 | (man << 13)),
+        _ => {
+            let e = (exp - F16_EXP_BIAS + F32_EXP_BIAS) as u32;
+            f32::from_bits(sign | (e << 23) | (man << 13))
+        }
+    }
+}
+
+pub fn from_f32_slice(values: &[f32]) -> Vec<u16> {
+    values.iter().map(|v| from_f32(*v)).collect()
+}
+
+pub fn to_f32_slice(values: &[u16]) -> Vec<f32> {
+    values.iter().map(|h| to_f32(*h)).collect()
+}
+
+// v >> shift rounded to nearest even
+fn round_shift(v: u32, shift: u32) -> u32 {
+    let out = v >> shift;
+    let rem = v & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    if rem > half || (rem == half && out & 1 == 1) {
+        out + 1
+    } else {
+        out
+    }
+}
@@ -1,3 +1,7 @@
+pub mod f16;
+pub mod quantize;
+pub mod scalar;
+
 pub fn le_slice_to_u16(b: &[u8]) -> u16 {
     (b[0] as u16) | (b[1] as u16) << 8
 }
@@ -16,6 +20,7 @@ pub fn le_slice_to_f32(b: &[u8]) -> f32 {
 
 pub trait F32Ext {
     fn denormalize(&self, min: f32, max: f32) -> Self;
+    fn normalize(&self, min: f32, max: f32) -> Self;
 }
 
 impl F32Ext for f32 {
@@ -23,4 +28,13 @@ impl F32Ext for f32 {
         let range_val = max - min;
         (*self * range_val) + min
     }
+
+    // Inverse of denormalize, zero for an empty range
+    fn normalize(&self, min: f32, max: f32) -> Self {
+        let range_val = max - min;
+        if range_val == 0.0 {
+            return 0.0;
+        }
+        (*self - min) / range_val
+    }
 }
@@ -0,0 +1,117 @@
+// Normalized integer and octahedral encodings shared by mesh readers and
+// writers. unorm covers 0..1, snorm covers -1..1 and never uses the most
+// negative integer, decoding it clamps to -1 like GPUs do
+use crate::math::la::vector2::Vector2;
+use crate::math::la::vector3::Vector3;
+use crate::math::number::F32Ext;
+
+pub fn unorm8(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+pub fn from_unorm8(v: u8) -> f32 {
+    v as f32 / 255.0
+}
+
+pub fn unorm16(v: f32) -> u16 {
+    (v.clamp(0.0, 1.0) * 65535.0).round() as u16
+}
+
+pub fn from_unorm16(v: u16) -> f32 {
+    v as f32 / 65535.0
+}
+
+pub fn snorm8(v: f32) -> i8 {
+    (v.clamp(-1.0, 1.0) * 127.0).round() as i8
+}
+
+pub fn from_snorm8(v: i8) -> f32 {
+    (v as f32 / 127.0).max(-1.0)
+}
+
+pub fn snorm16(v: f32) -> i16 {
+    (v.clamp(-1.0, 1.0) * 32767.0).round() as i16
+}
+
+pub fn from_snorm16(v: i16) -> f32 {
+    (v as f32 / 32767.0).max(-1.0)
+}
+
+// min..max range on 16 bits, values outside the range are clamped
+pub fn range_unorm16(v: f32, min: f32, max: f32) -> u16 {
+    unorm16(v.normalize(min, max))
+}
+
+pub fn from_range_unorm16(v: u16, min: f32, max: f32) -> f32 {
+    from_unorm16(v).denormalize(min, max)
+}
+
+// min..max range on 8 bits, values outside the range are clamped
+pub fn range_unorm8(v: f32, min: f32, max: f32) -> u8 {
+    unorm8(v.normalize(min, max))
+}
+
+pub fn from_range_unorm8(v: u8, min: f32, max: f32) -> f32 {
+    from_unorm8(v).denormalize(min, max)
+}
+
+// Unit vector folded onto an octahedron and flattened to -1..1 square
+pub fn oct_encode(n: Vector3) -> Vector2 {
+    let l1 = n.x.abs() + n.y.abs() + n.z.abs();
+    if l1 == 0.0 {
+        return Vector2::new(0.0, 0.0);
+    }
+
+    let (x, y) = (n.x / l1, n.y / l1);
+    if n.z >= 0.0 {
+        return Vector2::new(x, y);
+    }
+
+    // Lower half is mirrored over the diagonals
+    Vector2::new(
+        (1.0 - y.abs()) * sign_not_zero(x),
+        (1.0 - x.abs()) * sign_not_zero(y),
+    )
+}
+
+// Inverse of oct_encode, always returns a unit vector
+pub fn oct_decode(e: Vector2) -> Vector3 {
+    let z = 1.0 - e.x.abs() - e.y.abs();
+    let (x, y) = if z < 0.0 {
+        (
+            (1.0 - e.y.abs()) * sign_not_zero(e.x),
+            (1.0 - e.x.abs()) * sign_not_zero(e.y),
+        )
+    } else {
+        (e.x, e.y)
+    };
+    Vector3::new(x, y, z).normalize()
+}
+
+// 4 byte normal, error stays below 0.005 degrees
+pub fn oct_encode_snorm16(n: Vector3) -> [i16; 2] {
+    let e = oct_encode(n);
+    [snorm16(e.x), snorm16(e.y)]
+}
+
+pub fn oct_decode_snorm16(v: [i16; 2]) -> Vector3 {
+    oct_decode(Vector2::new(from_snorm16(v[0]), from_snorm16(v[1])))
+}
+
+// 2 byte normal, error stays below 1 degree
+pub fn oct_encode_snorm8(n: Vector3) -> [i8; 2] {
+    let e = oct_encode(n);
+    [snorm8(e.x), snorm8(e.y)]
+}
+
+pub fn oct_decode_snorm8(v: [i8; 2]) -> Vector3 {
+    oct_decode(Vector2::new(from_snorm8(v[0]), from_snorm8(v[1])))
+}
+
+fn sign_not_zero(v: f32) -> f32 {
+    if v < 0.0 {
+        -1.0
+    } else {
+        1.0
+    }
+}
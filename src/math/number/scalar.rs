@@ -0,0 +1,129 @@
+use std::fmt;
+use std::ops;
+
+// Numeric element of the linear algebra types. Implemented for the float and
+// integer primitives so vectors can be used both for world space math and for
+// grid coordinates.
+pub trait Scalar:
+    Copy
+    + Default
+    + PartialEq
+    + PartialOrd
+    + fmt::Debug
+    + fmt::Display
+    + ops::Add<Output = Self>
+    + ops::Sub<Output = Self>
+    + ops::Mul<Output = Self>
+    + ops::Div<Output = Self>
+    + ops::AddAssign
+    + ops::SubAssign
+    + ops::MulAssign
+    + ops::DivAssign
+{
+    const ZERO: Self;
+    const ONE: Self;
+
+    // Lossy `as` conversions, used to cast between precisions
+    fn from_f64(v: f64) -> Self;
+    fn to_f64(self) -> f64;
+
+    fn abs(self) -> Self;
+}
+
+// Scalar with real number operations, required by lengths, rotations and
+// everything that divides.
+pub trait Float: Scalar + ops::Neg<Output = Self> {
+    const PI: Self;
+    const EPSILON: Self;
+    const INFINITY: Self;
+
+    fn sqrt(self) -> Self;
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    fn tan(self) -> Self;
+    fn asin(self) -> Self;
+    fn acos(self) -> Self;
+    fn atan2(self, other: Self) -> Self;
+    fn exp(self) -> Self;
+    fn ln(self) -> Self;
+    fn to_degrees(self) -> Self;
+    fn to_radians(self) -> Self;
+}
+
+macro_rules! impl_scalar {
+    ($t:ty, $zero:expr, $one:expr, $abs:expr) => {
+        impl Scalar for $t {
+            const ZERO: Self = $zero;
+            const ONE: Self = $one;
+
+            fn from_f64(v: f64) -> Self {
+                v as $t
+            }
+
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+
+            fn abs(self) -> Self {
+                $abs(self)
+            }
+        }
+    };
+}
+
+macro_rules! impl_float {
+    ($t:ident) => {
+        impl_scalar!($t, 0.0, 1.0, $t::abs);
+
+        impl Float for $t {
+            const PI: Self = std::$t::consts::PI;
+            const EPSILON: Self = $t::EPSILON;
+            const INFINITY: Self = $t::INFINITY;
+
+            fn sqrt(self) -> Self {
+                $t::sqrt(self)
+            }
+            fn sin(self) -> Self {
+                $t::sin(self)
+            }
+            fn cos(self) -> Self {
+                $t::cos(self)
+            }
+            fn tan(self) -> Self {
+                $t::tan(self)
+            }
+            fn asin(self) -> Self {
+                $t::asin(self)
+            }
+            fn acos(self) -> Self {
+                $t::acos(self)
+            }
+            fn atan2(self, other: Self) -> Self {
+                $t::atan2(self, other)
+            }
+            fn exp(self) -> Self {
+                $t::exp(self)
+            }
+            fn ln(self) -> Self {
+                $t::ln(self)
+            }
+            fn to_degrees(self) -> Self {
+                $t::to_degrees(self)
+            }
+            fn to_radians(self) -> Self {
+                $t::to_radians(self)
+            }
+        }
+    };
+}
+
+impl_float!(f32);
+impl_float!(f64);
+
+impl_scalar!(i16, 0, 1, i16::abs);
+impl_scalar!(i32, 0, 1, i32::abs);
+impl_scalar!(i64, 0, 1, i64::abs);
+impl_scalar!(u8, 0, 1, std::convert::identity);
+impl_scalar!(u16, 0, 1, std::convert::identity);
+impl_scalar!(u32, 0, 1, std::convert::identity);
+impl_scalar!(u64, 0, 1, std::convert::identity);
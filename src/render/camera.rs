@@ -1,7 +1,11 @@
-use crate::math::la::matrix4::Matrix4x4;
+use crate::math::geom::frustum::Frustum;
+use crate::math::geom::ray::Ray;
+use crate::math::la::matrix4::{ClipDepth, Matrix4x4};
 use crate::math::la::quaternion::Quaternion;
+use crate::math::la::vector2::Vector2;
 use crate::math::la::vector3::Vector3;
 
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PerspectiveCamera {
     pub position: Vector3,
     pub rotation: Quaternion,
@@ -9,11 +13,13 @@ pub struct PerspectiveCamera {
     pub aspect: f32,
     pub near: f32,
     pub far: f32,
+    pub clip_depth: ClipDepth,
     pub projection_matrix: Matrix4x4,
     pub view_matrix: Matrix4x4,
 }
 
 impl PerspectiveCamera {
+    #[allow(clippy::new_without_default)]
     pub const fn new() -> PerspectiveCamera {
         PerspectiveCamera {
             position: Vector3::new(0.0, 0.0, 0.0),
@@ -22,16 +28,44 @@ impl PerspectiveCamera {
             aspect: 1.0,
             near: 0.001,
             far: 120.0,
+            clip_depth: ClipDepth::NegativeOneToOne,
             projection_matrix: Matrix4x4::new(),
             view_matrix: Matrix4x4::new(),
         }
     }
 
+    // Turn the camera towards target, position stays the same
+    pub fn look_at(&mut self, target: Vector3, up: Vector3) {
+        let mut view = Matrix4x4::new();
+        view.look_at(self.position, target, up);
+        self.rotation = view.get_rotation();
+    }
+
+    // Ray from the camera through a point in normalized device coordinates,
+    // (-1, -1) is the bottom left corner. Uses the matrices from calculate
+    pub fn screen_ray(&self, ndc: Vector2) -> Ray {
+        let inverse = (self.projection_matrix * self.view_matrix).invert();
+        Ray::from_ndc(ndc, &inverse, self.clip_depth)
+    }
+
+    // View frustum from the matrices of the last calculate
+    pub fn frustum(&self) -> Frustum {
+        Frustum::from_matrix4x4(
+            &(self.projection_matrix * self.view_matrix),
+            self.clip_depth,
+        )
+    }
+
     pub fn calculate(&mut self) {
         // Projection
         self.projection_matrix.identity();
-        self.projection_matrix
-            .perspective(self.fov, self.aspect, self.near, self.far);
+        self.projection_matrix.perspective_depth(
+            self.fov,
+            self.aspect,
+            self.near,
+            self.far,
+            self.clip_depth,
+        );
 
         // View matrix
         self.view_matrix.identity();
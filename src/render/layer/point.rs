@@ -25,6 +25,6 @@ impl PointLayer {
     }
 
     pub fn draw(&self) {
-        print!("{}", "sas")
+        print!("sas")
     }
 }
@@ -5,6 +5,7 @@ use crate::math::number;
 use std::collections::HashMap;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct MeshAnimation {
     pub name: String,
@@ -14,6 +15,7 @@ pub struct MeshAnimation {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct MeshAnimationSequence {
     pub key: String,
@@ -25,6 +27,7 @@ pub struct MeshAnimationSequence {
 }
 
 impl MeshAnimationSequence {
+    #[allow(clippy::new_without_default)]
     pub const fn new() -> Self {
         MeshAnimationSequence {
             key: String::new(),
@@ -62,7 +65,7 @@ impl MeshAnimationSequence {
         if self.kind == 2 {
             let q1 = Quaternion::from_vector4(self.value[self.prev_frame]);
             let q2 = Quaternion::from_vector4(self.value[self.next_frame]);
-            let v = Quaternion::lerp(q1, q2, t);
+            let v = Quaternion::slerp(q1, q2, t);
             return v.to_vector4();
         }
         Vector4::new(0.0, 0.0, 0.0, 0.0)
@@ -70,6 +73,7 @@ impl MeshAnimationSequence {
 }
 
 impl MeshAnimation {
+    #[allow(clippy::new_without_default)]
     pub const fn new() -> Self {
         MeshAnimation {
             name: String::new(),
@@ -1,8 +1,11 @@
+use crate::math::la::dual_quaternion::DualQuaternion;
 use crate::math::la::matrix4::Matrix4x4;
 use crate::math::la::quaternion::Quaternion;
+use crate::math::la::transform::Transform;
 use crate::math::la::vector3::Vector3;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Bone {
     pub name: String,
@@ -23,6 +26,7 @@ pub struct Bone {
 }
 
 impl Bone {
+    #[allow(clippy::new_without_default)]
     pub const fn new() -> Bone {
         Bone {
             name: String::new(),
@@ -43,6 +47,16 @@ impl Bone {
         }
     }
 
+    pub fn local_transform(&self) -> Transform {
+        Transform::new(self.local_position, self.local_rotation, self.local_scale)
+    }
+
+    // Skinning transform for dual quaternion skinning, same as
+    // matrix * inverse_bind_matrix without the scale
+    pub fn skin_dual_quaternion(&self) -> DualQuaternion {
+        DualQuaternion::from_matrix4x4(self.matrix * self.inverse_bind_matrix)
+    }
+
     /*pub fn calculate(&mut self, parent: Matrix4x4) {
 
     }*/
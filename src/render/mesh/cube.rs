@@ -79,7 +79,7 @@ pub fn new(size: Vector3, side: u8) -> MeshData {
 
     // Scale
     for i in 0..m.vertex.len() {
-        m.vertex[i] = m.vertex[i].mul(size)
+        m.vertex[i] *= size
     }
 
     // UV
@@ -101,5 +101,5 @@ pub fn new(size: Vector3, side: u8) -> MeshData {
         m.index.push(3 + next);
     }
 
-    return m;
+    m
 }
@@ -1,21 +1,22 @@
-use crate::image::color::ColorRGBA;
+use crate::math::geom::bvh::Bvh;
+use crate::math::geom::oriented_box::OrientedBox;
+use crate::math::geom::ray::{MeshHit, Ray};
+use crate::math::geom::sphere::Sphere;
 use crate::math::la::matrix4::Matrix4x4;
-use crate::math::la::quaternion::Quaternion;
+use crate::math::la::quaternion::{EulerOrder, Quaternion};
+use crate::math::la::transform::Transform;
 use crate::math::la::vector2::Vector2;
 use crate::math::la::vector3::Vector3;
 use crate::math::la::vector4::Vector4;
 use crate::math::number;
+use crate::math::number::quantize::{from_snorm16, from_unorm16};
 use crate::math::number::{le_slice_to_f32, le_slice_to_i16, le_slice_to_u16, F32Ext};
 use crate::render::mesh::animation::MeshAnimation;
 use crate::render::mesh::bone::Bone;
-use log::log;
-use std::cell::RefMut;
 use std::collections::HashMap;
-use std::ffi::c_char;
-use std::ops::Deref;
-use std::rc::Rc;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct MeshData {
     pub id: u32,
@@ -29,11 +30,14 @@ pub struct MeshData {
     pub bone_list: Vec<Bone>,
     pub bone_root_id: usize,
 
+    // Lookup cache, rebuilt on demand
+    #[cfg_attr(feature = "serde", serde(skip))]
     bone_name_to_id: Option<HashMap<String, u32>>,
     pub animation_list: Vec<MeshAnimation>,
 }
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct MeshInstance {
     pub mesh_id: u32,
@@ -43,6 +47,7 @@ pub struct MeshInstance {
 }
 
 impl MeshData {
+    #[allow(clippy::new_without_default)]
     pub const fn new() -> Self {
         MeshData {
             id: 0,
@@ -117,9 +122,10 @@ impl MeshData {
             mesh.uv0.push(Vector2::new(x, y));
         }
 
-        return mesh;
+        mesh
     }
 
+    #[allow(clippy::only_used_in_recursion)]
     pub fn from_mm2_bytes(bytes: &[u8]) -> MeshData {
         let mut mesh = MeshData::new();
         let mut offset = 0;
@@ -256,7 +262,7 @@ impl MeshData {
                         }
                     }
 
-                    // Float16 precision
+                    // 16 bit precision, snorm within the min/max range
                     if vertex_precision == 2 {
                         // Read min and max
                         let min_x = le_slice_to_f32(&bytes[offset..offset + 4]);
@@ -282,9 +288,9 @@ impl MeshData {
                             offset += 2;
 
                             mesh.vertex.push(Vector3::new(
-                                (x as f32 / 0x7FFF as f32).denormalize(min_x, max_x),
-                                (y as f32 / 0x7FFF as f32).denormalize(min_y, max_y),
-                                (z as f32 / 0x7FFF as f32).denormalize(min_z, max_z),
+                                from_snorm16(x).denormalize(min_x, max_x),
+                                from_snorm16(y).denormalize(min_y, max_y),
+                                from_snorm16(z).denormalize(min_z, max_z),
                             ));
                         }
                     }
@@ -303,17 +309,15 @@ impl MeshData {
                             mesh.uv0.push(position);
                         }
                     }
-                    // Float16 precision
+                    // 16 bit precision, unorm
                     if uv_precision == 2 {
                         for _ in 0..amount {
                             let x = le_slice_to_u16(&bytes[offset..offset + 2]);
                             offset += 2;
                             let y = le_slice_to_u16(&bytes[offset..offset + 2]);
                             offset += 2;
-                            mesh.uv0.push(Vector2::new(
-                                x as f32 / 0xFFFF as f32,
-                                y as f32 / 0xFFFF as f32,
-                            ));
+                            mesh.uv0
+                                .push(Vector2::new(from_unorm16(x), from_unorm16(y)));
 
                             /*let position = Vector2::from_bytes(&bytes[offset..offset + 4 * 2]);
                             offset += 4 * 2;
@@ -335,7 +339,7 @@ impl MeshData {
                             mesh.normal.push(position);
                         }
                     }
-                    // Float16 precision
+                    // 16 bit precision, snorm
                     if uv_precision == 2 {
                         for _ in 0..amount {
                             let x = le_slice_to_i16(&bytes[offset..offset + 2]);
@@ -346,9 +350,9 @@ impl MeshData {
                             offset += 2;
 
                             mesh.normal.push(Vector3::new(
-                                x as f32 / 0x7FFF as f32,
-                                y as f32 / 0x7FFF as f32,
-                                z as f32 / 0x7FFF as f32,
+                                from_snorm16(x),
+                                from_snorm16(y),
+                                from_snorm16(z),
                             ));
                         }
                     }
@@ -383,7 +387,7 @@ impl MeshData {
                     offset += 1;
 
                     // Read bone info
-                    for i in 0..amount {
+                    for _ in 0..amount {
                         // Read bone name
                         let l = bytes[offset];
                         offset += 1;
@@ -464,11 +468,8 @@ impl MeshData {
                         mesh.bone_list[bone_index as usize] = bone;
 
                         // Fill
-                        match &mut mesh.bone_name_to_id {
-                            Some(map) => {
-                                map.insert(String::from(name), bone_index as u32);
-                            }
-                            None => {}
+                        if let Some(map) = &mut mesh.bone_name_to_id {
+                            map.insert(String::from(name), bone_index as u32);
                         }
                         /*mesh.bone_name_to_id
                         .as_mut()
@@ -527,7 +528,176 @@ impl MeshData {
         do_x(start_index, &mut self.bone_list, Matrix4x4::new());
     }
 
+    // Vertices in the current pose with linear blend skinning, bone matrices
+    // come from calculate_bones. Meshes without bones or weights return the
+    // bind pose
+    pub fn skinned_vertices(&self) -> Vec<Vector3> {
+        if self.bone_list.is_empty() || self.bone_weight.len() != self.vertex.len() {
+            return self.vertex.clone();
+        }
+
+        let skin: Vec<Matrix4x4> = self
+            .bone_list
+            .iter()
+            .map(|b| b.matrix * b.inverse_bind_matrix)
+            .collect();
+
+        let mut out = Vec::with_capacity(self.vertex.len());
+        for (i, v) in self.vertex.iter().enumerate() {
+            let weight = self.bone_weight[i];
+            let index = self.bone_index.get(i).copied().unwrap_or_default();
+
+            let mut p = Vector3::zero();
+            let mut total = 0.0;
+            for (w, id) in [
+                (weight.x, index.x),
+                (weight.y, index.y),
+                (weight.z, index.z),
+                (weight.w, index.w),
+            ] {
+                if w == 0.0 {
+                    continue;
+                }
+                if let Some(mx) = skin.get(id as usize) {
+                    p += (*v * *mx) * w;
+                    total += w;
+                }
+            }
+
+            out.push(if total > 0.0 { p / total } else { *v });
+        }
+        out
+    }
+
+    // Minimum sphere around the current pose, see skinned_vertices
+    pub fn bounding_sphere(&self) -> Sphere {
+        Sphere::welzl(&self.skinned_vertices())
+    }
+
+    // Closest triangle hit in bind pose. Meshes without index are read as a
+    // plain triangle list
+    pub fn raycast(&self, ray: &Ray, cull_backface: bool) -> Option<MeshHit> {
+        let count = if self.index.is_empty() {
+            self.vertex.len() / 3
+        } else {
+            self.index.len() / 3
+        };
+        let corner = |t: usize, k: usize| {
+            if self.index.is_empty() {
+                t * 3 + k
+            } else {
+                self.index[t * 3 + k] as usize
+            }
+        };
+
+        let mut best: Option<MeshHit> = None;
+        for t in 0..count {
+            let (ia, ib, ic) = (corner(t, 0), corner(t, 1), corner(t, 2));
+            let (a, b, c) = match (
+                self.vertex.get(ia),
+                self.vertex.get(ib),
+                self.vertex.get(ic),
+            ) {
+                (Some(a), Some(b), Some(c)) => (*a, *b, *c),
+                _ => continue,
+            };
+            let hit = match ray.intersect_triangle(a, b, c, cull_backface) {
+                Some(hit) => hit,
+                None => continue,
+            };
+            if best.is_some_and(|b| b.hit.distance <= hit.hit.distance) {
+                continue;
+            }
+
+            best = Some(MeshHit {
+                hit: hit.hit,
+                triangle: t,
+                barycentric: hit.barycentric,
+                uv: self.triangle_uv(t, hit.barycentric),
+            });
+        }
+        best
+    }
+
+    // uv0 at a point of triangle t given by barycentric weights, None if the
+    // mesh has no uvs there
+    pub fn triangle_uv(&self, t: usize, barycentric: Vector3) -> Option<Vector2> {
+        let corner = |k: usize| {
+            if self.index.is_empty() {
+                Some(t * 3 + k)
+            } else {
+                self.index.get(t * 3 + k).map(|i| *i as usize)
+            }
+        };
+        let w = barycentric;
+        match (
+            corner(0).and_then(|i| self.uv0.get(i)),
+            corner(1).and_then(|i| self.uv0.get(i)),
+            corner(2).and_then(|i| self.uv0.get(i)),
+        ) {
+            (Some(a), Some(b), Some(c)) => Some(*a * w.x + *b * w.y + *c * w.z),
+            _ => None,
+        }
+    }
+
+    // Acceleration structure for repeated ray and closest point queries
+    pub fn build_bvh(&self) -> Bvh {
+        Bvh::from_mesh(self)
+    }
+
+    // Box per bone around the vertices it moves with at least min_weight, in
+    // bind pose. Transform with the bone's matrix * inverse_bind_matrix to
+    // follow the pose. None for bones that move no vertices
+    pub fn bone_oriented_boxes(&self, min_weight: f32) -> Vec<Option<OrientedBox>> {
+        let mut points: Vec<Vec<Vector3>> = vec![vec![]; self.bone_list.len()];
+        for (i, v) in self.vertex.iter().enumerate() {
+            let (weight, index) = match (self.bone_weight.get(i), self.bone_index.get(i)) {
+                (Some(w), Some(id)) => (*w, *id),
+                _ => break,
+            };
+            for (w, id) in [
+                (weight.x, index.x),
+                (weight.y, index.y),
+                (weight.z, index.z),
+                (weight.w, index.w),
+            ] {
+                if w > 0.0 && w >= min_weight {
+                    if let Some(list) = points.get_mut(id as usize) {
+                        list.push(*v);
+                    }
+                }
+            }
+        }
+
+        points
+            .iter()
+            .map(|p| {
+                if p.is_empty() {
+                    None
+                } else {
+                    Some(OrientedBox::from_points_hull(p))
+                }
+            })
+            .collect()
+    }
+
+    // Named bones by index. Only the mm2 reader fills it, deserialized or
+    // hand built meshes get it on first use
+    fn build_bone_map(&mut self) {
+        if self.bone_name_to_id.is_some() {
+            return;
+        }
+        let mut map = HashMap::new();
+        for (i, bone) in self.bone_list.iter().enumerate() {
+            if !bone.name.is_empty() {
+                map.entry(bone.name.clone()).or_insert(i as u32);
+            }
+        }
+        self.bone_name_to_id = Some(map);
+    }
+
     pub fn set_bone_rotation(&mut self, name: &str, q: Quaternion) {
+        self.build_bone_map();
         let map = self.bone_name_to_id.as_ref().unwrap();
         /*if map.contains_key(name) {
             let id = ;
@@ -540,6 +710,7 @@ impl MeshData {
     }
 
     pub fn apply_animation(&mut self, animation_name: &str, delta: f32) {
+        self.build_bone_map();
         let mut a = None;
         for i in 0..self.animation_list.len() {
             if self.animation_list[i].name == animation_name {
@@ -632,6 +803,7 @@ impl MeshData {
 }
 
 impl MeshInstance {
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         MeshInstance {
             mesh_id: 0,
@@ -640,6 +812,19 @@ impl MeshInstance {
             scale: Vector3::new(1.0, 1.0, 1.0),
         }
     }
+
+    // rotation is Euler angles in radians, XYZ order
+    pub fn to_transform(&self) -> Transform {
+        Transform::new(
+            self.position,
+            Quaternion::from_euler_order(self.rotation, EulerOrder::XYZ),
+            self.scale,
+        )
+    }
+
+    pub fn to_matrix4x4(&self) -> Matrix4x4 {
+        self.to_transform().to_matrix4x4()
+    }
 }
 
 /*impl PointLayer {
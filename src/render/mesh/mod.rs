@@ -1,4 +1,5 @@
 pub mod animation;
 pub mod bone;
 pub mod cube;
+#[allow(clippy::module_inception)]
 pub mod mesh;
@@ -1,10 +1,32 @@
 mod tests {
+    use ml::image::color::ColorRGBA;
     use ml::io::mouse;
-    use ml::math::la::quaternion::Quaternion;
+    use ml::math;
+    use ml::math::approx::ApproxEq;
+    use ml::math::geom::bounding_box::BoundingBox;
+    use ml::math::geom::frustum::Intersection;
+    use ml::math::geom::hull;
+    use ml::math::geom::oriented_box::OrientedBox;
+    use ml::math::geom::plane::Plane;
+    use ml::math::geom::ray::Ray;
+    use ml::math::geom::sphere::Sphere;
+    use ml::math::la::coordinates::{self, Cylindrical, Polar, Spherical};
+    use ml::math::la::dual_quaternion::DualQuaternion;
+    use ml::math::la::matrix2::Matrix2x2;
+    use ml::math::la::matrix3::Matrix3x3;
+    use ml::math::la::matrix4::{self, ClipDepth, Matrix4x4};
+    use ml::math::la::matrixn::MatrixN;
+    use ml::math::la::quaternion::{EulerOrder, Quaternion};
+    use ml::math::la::transform::Transform;
     use ml::math::la::vector2::Vector2;
     use ml::math::la::vector3::Vector3;
+    use ml::math::la::vector4::Vector4;
+    use ml::math::number::{f16, quantize};
     use ml::math::random::Random;
-    use ml::{math};
+    use ml::render::camera::PerspectiveCamera;
+    use ml::render::mesh::bone::Bone;
+    use ml::render::mesh::mesh::MeshInstance;
+    use ml::{assert_approx_eq, assert_relative_eq, assert_ulps_eq};
 
     /*#[test]
     fn quat() {
@@ -22,23 +44,26 @@ mod tests {
         let q1 = Quaternion::from_euler(Vector3::new(45.0f32, 0.0, 0.0).to_radians());
         let q2 = Quaternion::from_euler(Vector3::new(90.0f32, 0.0, 0.0).to_radians());
 
-        println!("{}", Quaternion::lerp(q1, q2, 0.0).to_euler().to_degrees());
-        println!("{}", Quaternion::lerp(q1, q2, 0.5).to_euler().to_degrees());
-        println!("{}", Quaternion::lerp(q1, q2, 1.0).to_euler().to_degrees());
+        let euler = |t| Quaternion::lerp(q1, q2, t).to_euler().to_degrees();
+        assert_approx_eq!(euler(0.0), Vector3::new(45.0, 0.0, 0.0), epsilon = 1e-3);
+        assert_approx_eq!(euler(0.5), Vector3::new(67.5, 0.0, 0.0), epsilon = 1e-3);
+        assert_approx_eq!(euler(1.0), Vector3::new(90.0, 0.0, 0.0), epsilon = 1e-3);
     }
 
     #[test]
     fn sas() {
         let v1 = Vector3::default();
         let v2 = Vector3::new(1.0, 1.0, 1.0);
-        println!("{}", (v1 - v2).normalize());
+        let n = 1.0 / 3.0f32.sqrt();
+        assert_ulps_eq!((v1 - v2).normalize(), Vector3::new(-n, -n, -n));
     }
 
     #[test]
     fn sas2() {
         let v1 = Vector2::default();
         let v2 = Vector2::new(1.0, 1.0);
-        println!("{}", (v1 - v2).normalize());
+        let n = 1.0 / 2.0f32.sqrt();
+        assert_ulps_eq!((v1 - v2).normalize(), Vector2::new(-n, -n));
     }
 
     #[test]
@@ -46,18 +71,19 @@ mod tests {
         let mut r = Random::new(0);
 
         for _i in 0..100 {
-            println!("{}", r.range(-4.0, 4.0));
+            let v = r.range(-4.0, 4.0);
+            assert!((-4.0..=4.0).contains(&v));
         }
     }
 
     #[test]
     fn sas5() {
-        println!("{}", math::lerp(0.0, 1.0, 0.5));
+        assert_approx_eq!(math::lerp(0.0, 1.0, 0.5), 0.5);
     }
 
     #[test]
     fn remap() {
-        println!("{}", math::remap(1.0, 0.0, 1.0, 0.0, 5.0));
+        assert_approx_eq!(math::remap(1.0, 0.0, 1.0, 0.0, 5.0), 5.0);
     }
 
     #[test]
@@ -66,4 +92,2040 @@ mod tests {
         println!("{}", mouse::is_down(12222));
         println!("{}", mouse::is_down(1000));
     }
+
+    #[test]
+    fn vector_precision() {
+        // Far from the origin f32 can't represent a 1cm offset anymore
+        let far = Vector3::new(10_000_000.0f64, 0.0, 0.0);
+        let moved = far + Vector3::new(0.01, 0.0, 0.0);
+        assert!((moved - far).length() > 0.009);
+
+        let v: Vector3<f64> = Vector3::new(1.5f32, -2.0, 4.0).into();
+        let back: Vector3 = v.cast();
+        assert_eq!(back.x, 1.5);
+        assert_eq!(back.y, -2.0);
+        assert_eq!(back.z, 4.0);
+    }
+
+    #[test]
+    fn vector_grid() {
+        let cell = Vector2::new(3i32, -4) + Vector2::new(1, 1);
+        assert_eq!(cell.x, 4);
+        assert_eq!(cell.y, -3);
+        assert_eq!(Vector3::new(1i32, 2, 3).dot(Vector3::new(4, 5, 6)), 32);
+
+        let world: Vector2<f32> = cell.into();
+        assert_eq!(world.length(), 5.0);
+    }
+
+    #[test]
+    fn matrix_precision() {
+        let mut mx = Matrix4x4::<f64>::new();
+        mx.translate(1.0, 2.0, 3.0);
+        mx.rotate_y(0.5);
+        let p = (Vector3::new(1.0, 1.0, 1.0) * mx) * mx.invert();
+        assert!((p - Vector3::one()).length() < 1e-12);
+
+        let single: Matrix4x4 = mx.cast();
+        assert_eq!(single.get_position().z, 3.0);
+    }
+
+    #[test]
+    fn matrix3_invert() {
+        let mut mx: Matrix3x3 = Matrix3x3::new();
+        mx.translate(4.0, -2.0);
+        mx.rotate(0.7);
+        mx.scale(2.0, 3.0);
+
+        let p = Vector2::new(1.0, 2.0);
+        let back = (p * mx) * mx.invert();
+        assert!((back - p).length() < 1e-5);
+
+        let id = mx * mx.invert();
+        for i in 0..9 {
+            let expected = if i % 4 == 0 { 1.0 } else { 0.0 };
+            assert!((id.raw[i] - expected).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn matrix2_invert() {
+        let mut mx: Matrix2x2 = Matrix2x2::new();
+        mx.rotate(1.2);
+        mx.scale(0.5, 4.0);
+        let p = Vector2::new(3.0, -1.0);
+        assert!(((p * mx) * mx.invert() - p).length() < 1e-5);
+    }
+
+    #[test]
+    fn matrix3_quaternion() {
+        let q = Quaternion::from_euler_order(
+            Vector3::new(30.0f32, 60.0, -20.0).to_radians(),
+            EulerOrder::XYZ,
+        );
+        let r = Matrix3x3::from_quaternion(q).to_quaternion();
+        let d = q.x * r.x + q.y * r.y + q.z * r.z + q.w * r.w;
+        assert!((d.abs() - 1.0).abs() < 1e-5);
+
+        let v = Vector3::new(1.0, 2.0, 3.0);
+        let a = v * q.to_matrix3x3();
+        let b = v * q.to_matrix4x4();
+        assert!((a - b).length() < 1e-5);
+    }
+
+    #[test]
+    fn normal_matrix() {
+        let mut mx: Matrix4x4 = Matrix4x4::new();
+        mx.translate(5.0, 0.0, 0.0);
+        mx.rotate_z(0.3);
+        mx.scale(1.0, 4.0, 1.0);
+
+        // A tangent and its normal must stay perpendicular after the transform
+        let tangent = Vector3::new(1.0, 1.0, 0.0);
+        let normal = Vector3::new(1.0, -1.0, 0.0);
+        let t = tangent * Matrix3x3::from_matrix4x4(mx);
+        let n = normal * mx.normal_matrix();
+        assert!(tangent.dot(normal).abs() < 1e-6);
+        assert!(t.dot(n).abs() < 1e-5);
+    }
+
+    fn depth_at(mx: Matrix4x4, distance: f32) -> f32 {
+        (Vector3::new(0.0, 0.0, -distance) * mx).z
+    }
+
+    #[test]
+    fn projection_depth() {
+        let fov = 1.0;
+        let mut mx = Matrix4x4::new();
+
+        mx.perspective_depth(fov, 1.5, 0.1, 100.0, ClipDepth::NegativeOneToOne);
+        assert!((depth_at(mx, 0.1) + 1.0).abs() < 1e-4);
+        assert!((depth_at(mx, 100.0) - 1.0).abs() < 1e-4);
+
+        mx.perspective_depth(fov, 1.5, 0.1, 100.0, ClipDepth::ZeroToOne);
+        assert!(depth_at(mx, 0.1).abs() < 1e-4);
+        assert!((depth_at(mx, 100.0) - 1.0).abs() < 1e-4);
+
+        mx.perspective_reversed(fov, 1.5, 0.1, 100.0, ClipDepth::ZeroToOne);
+        assert!((depth_at(mx, 0.1) - 1.0).abs() < 1e-4);
+        assert!(depth_at(mx, 100.0).abs() < 1e-4);
+
+        mx.perspective_infinite(fov, 1.5, 0.1, ClipDepth::ZeroToOne);
+        assert!(depth_at(mx, 0.1).abs() < 1e-4);
+        assert!((depth_at(mx, 1e7) - 1.0).abs() < 1e-4);
+
+        mx.perspective_infinite_reversed(fov, 1.5, 0.1, ClipDepth::NegativeOneToOne);
+        assert!((depth_at(mx, 0.1) - 1.0).abs() < 1e-4);
+        assert!((depth_at(mx, 1e7) + 1.0).abs() < 1e-4);
+
+        mx.orthographic(-2.0, 2.0, -1.0, 1.0, 1.0, 10.0, ClipDepth::ZeroToOne);
+        assert!(depth_at(mx, 1.0).abs() < 1e-5);
+        assert!((depth_at(mx, 10.0) - 1.0).abs() < 1e-5);
+        let corner = Vector3::new(2.0, 1.0, -5.0) * mx;
+        assert!((corner.x - 1.0).abs() < 1e-5 && (corner.y - 1.0).abs() < 1e-5);
+
+        // Symmetric frustum is the same as perspective
+        let mut p = Matrix4x4::new();
+        p.perspective_depth(fov, 1.0, 0.5, 50.0, ClipDepth::ZeroToOne);
+        let h = 0.5 * (fov / 2.0f32).tan();
+        mx.frustum(-h, h, -h, h, 0.5, 50.0, ClipDepth::ZeroToOne);
+        for i in 0..16 {
+            assert!((mx.raw[i] - p.raw[i]).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn look_at() {
+        let eye: Vector3 = Vector3::new(3.0, 4.0, 5.0);
+        let target = Vector3::new(-1.0, 0.0, 2.0);
+        let up = Vector3::new(0.0, 1.0, 0.0);
+
+        let mut mx = Matrix4x4::new();
+        mx.look_at_rh(eye, target, up);
+        let v = target * mx;
+        assert!(v.x.abs() < 1e-5 && v.y.abs() < 1e-5 && v.z < 0.0);
+
+        mx.look_at_lh(eye, target, up);
+        let v = target * mx;
+        assert!(v.x.abs() < 1e-5 && v.y.abs() < 1e-5 && v.z > 0.0);
+
+        let mut camera = PerspectiveCamera::new();
+        camera.position = eye;
+        camera.look_at(target, up);
+        camera.calculate();
+        let v = target * camera.view_matrix;
+        assert!(v.x.abs() < 1e-5 && v.y.abs() < 1e-5 && v.z < 0.0);
+    }
+
+    fn random_trs(r: &mut Random) -> (Vector3, Quaternion, Vector3) {
+        let t = Vector3::new(
+            r.range(-50.0, 50.0),
+            r.range(-50.0, 50.0),
+            r.range(-50.0, 50.0),
+        );
+        let q = Quaternion::from_euler_order(
+            Vector3::new(r.range(-3.0, 3.0), r.range(-1.5, 1.5), r.range(-3.0, 3.0)),
+            EulerOrder::XYZ,
+        );
+        let s = Vector3::new(r.range(0.1, 5.0), r.range(0.1, 5.0), r.range(0.1, 5.0));
+        (t, q, s)
+    }
+
+    fn assert_matrix_eq(a: Matrix4x4, b: Matrix4x4, eps: f32) {
+        assert_approx_eq!(a, b, epsilon = eps);
+    }
+
+    #[test]
+    fn decompose_round_trip() {
+        let mut r = Random::new(7);
+        for _ in 0..200 {
+            let (t, q, s) = random_trs(&mut r);
+            let mx = Matrix4x4::from_trs(t, q, s);
+            let (t2, q2, s2) = mx.decompose();
+
+            assert!((t2 - t).length() < 1e-4);
+            assert!((s2 - s).length() < 1e-3);
+            let d = q.x * q2.x + q.y * q2.y + q.z * q2.z + q.w * q2.w;
+            assert!((d.abs() - 1.0).abs() < 1e-4);
+            assert_matrix_eq(Matrix4x4::from_trs(t2, q2, s2), mx, 1e-3);
+        }
+    }
+
+    #[test]
+    fn decompose_mirrored() {
+        let mut r = Random::new(11);
+        for i in 0..200 {
+            let (t, q, mut s) = random_trs(&mut r);
+            match i % 3 {
+                0 => s.x = -s.x,
+                1 => s.y = -s.y,
+                _ => s.z = -s.z,
+            }
+            let mx = Matrix4x4::from_trs(t, q, s);
+            let (t2, q2, s2) = mx.decompose();
+
+            // Reflection always ends up on the x axis
+            assert!(s2.x < 0.0 && s2.y > 0.0 && s2.z > 0.0);
+            assert!((s2.x.abs() - s.x.abs()).abs() < 1e-3);
+            assert_matrix_eq(Matrix4x4::from_trs(t2, q2, s2), mx, 1e-3);
+            assert!((mx.get_rotation().to_vector4().length() - 1.0).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn decompose_shear() {
+        let mut mx: Matrix4x4 = Matrix4x4::from_trs(
+            Vector3::new(1.0, 2.0, 3.0),
+            Quaternion::from_euler_order(Vector3::new(0.3, 0.2, 0.1), EulerOrder::XYZ),
+            Vector3::new(2.0, 1.0, 1.0),
+        );
+        // Skew the y axis towards x
+        mx.raw[4] += mx.raw[0] * 0.5;
+        mx.raw[5] += mx.raw[1] * 0.5;
+        mx.raw[6] += mx.raw[2] * 0.5;
+
+        let (t, q, s) = mx.decompose();
+        assert!((t - Vector3::new(1.0, 2.0, 3.0)).length() < 1e-5);
+        assert!((q.to_vector4().length() - 1.0).abs() < 1e-5);
+        assert!((s - Vector3::new(2.0, 1.0, 1.0)).length() < 1e-4);
+
+        // The x axis isn't affected by the shear
+        let x = Vector3::new(1.0, 0.0, 0.0) * Matrix4x4::from_trs(Vector3::zero(), q, s);
+        let expected = Vector3::new(mx.raw[0], mx.raw[1], mx.raw[2]);
+        assert!((x - expected).length() < 1e-4);
+    }
+
+    #[test]
+    fn quat_slerp() {
+        let a = Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.2);
+        let b = Quaternion::from_axis_angle(Vector3::new(1.0, 1.0, 0.0), 2.5);
+        let total = a.angle_between(b);
+
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            let q = Quaternion::slerp(a, b, t);
+            assert!((a.angle_between(q) - total * t).abs() < 1e-3);
+            assert!((q.length() - 1.0).abs() < 1e-5);
+        }
+
+        // -b is the same rotation, slerp must take the short way anyway
+        let q = Quaternion::slerp(a, -b, 0.5);
+        assert!((a.angle_between(q) - total * 0.5).abs() < 1e-3);
+        let q = Quaternion::nlerp(a, -b, 0.5);
+        assert!(a.angle_between(q) < total);
+    }
+
+    #[test]
+    fn quat_squad() {
+        let keys: [Quaternion; 4] = [
+            Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.0),
+            Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.5),
+            Quaternion::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), 0.5),
+            Quaternion::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), 1.0),
+        ];
+        let s1 = Quaternion::squad_control(keys[0], keys[1], keys[2]);
+        let s2 = Quaternion::squad_control(keys[1], keys[2], keys[3]);
+
+        let start = Quaternion::squad(keys[1], keys[2], s1, s2, 0.0);
+        let end = Quaternion::squad(keys[1], keys[2], s1, s2, 1.0);
+        assert!(start.angle_between(keys[1]) < 1e-3);
+        assert!(end.angle_between(keys[2]) < 1e-3);
+        let mid = Quaternion::squad(keys[1], keys[2], s1, s2, 0.5);
+        assert!((mid.length() - 1.0).abs() < 1e-4);
+
+        // Opposite signs of the same rotation stay unit length all the way
+        let a = keys[2];
+        let b = a * -1.0;
+        for i in 0..=10 {
+            let t = i as f32 / 10.0;
+            let q = Quaternion::squad(a, b, a, b, t);
+            assert!((q.length() - 1.0).abs() < 1e-4, "{} at {}", q, t);
+        }
+        assert!(Quaternion::squad(a, b, a, b, 1.0).angle_between(b) < 1e-3);
+
+        // Nearly opposite keys end exactly on b, not on -a
+        let b = -(a * Quaternion::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), 0.03));
+        assert_approx_eq!(Quaternion::squad(a, b, a, b, 1.0), b, epsilon = 1e-6);
+        assert_approx_eq!(Quaternion::squad(a, b, a, b, 0.0), a, epsilon = 1e-6);
+        let mid = Quaternion::squad(a, b, a, b, 0.5);
+        assert!((mid.length() - 1.0).abs() < 1e-4);
+        assert!(mid.dot(a).abs() < 1e-4);
+    }
+
+    #[test]
+    fn quat_axis_angle() {
+        let axis: Vector3 = Vector3::new(1.0, 2.0, -2.0).normalize();
+        let q = Quaternion::from_axis_angle(axis, 1.25);
+        let (axis2, angle) = q.to_axis_angle();
+        assert!((axis2 - axis).length() < 1e-5);
+        assert!((angle - 1.25).abs() < 1e-5);
+
+        // Rotating the axis itself does nothing
+        assert!((q * axis - axis).length() < 1e-5);
+
+        let v = Vector3::new(3.0, -1.0, 0.5);
+        assert!((q * v - v * q.to_matrix4x4()).length() < 1e-5);
+        assert!((q.conjugate() * (q * v) - v).length() < 1e-5);
+    }
+
+    #[test]
+    fn quat_rotation_arc() {
+        let a = Vector3::new(1.0, 0.0, 0.0);
+        for b in [
+            Vector3::new(0.0, 1.0, 0.0),
+            Vector3::new(0.3, -0.2, 0.9).normalize(),
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+        ] {
+            let q = Quaternion::from_rotation_arc(a, b);
+            assert!((q * a - b).length() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn quat_look_rotation() {
+        let forward: Vector3 = Vector3::new(1.0, 1.0, -1.0).normalize();
+        let up = Vector3::new(0.0, 1.0, 0.0);
+        let q = Quaternion::look_rotation(forward, up);
+
+        assert!((q * Vector3::new(0.0, 0.0, 1.0) - forward).length() < 1e-5);
+        let y = q * Vector3::new(0.0, 1.0, 0.0);
+        assert!(y.dot(up) > 0.0);
+        assert!(y.dot(forward).abs() < 1e-5);
+        assert!((q * Vector3::new(1.0, 0.0, 0.0)).dot(up).abs() < 1e-5);
+    }
+
+    const EULER_ORDERS: [EulerOrder; 6] = [
+        EulerOrder::XYZ,
+        EulerOrder::XZY,
+        EulerOrder::YXZ,
+        EulerOrder::YZX,
+        EulerOrder::ZXY,
+        EulerOrder::ZYX,
+    ];
+
+    fn axis_rotation(axis: char, angle: f32) -> Quaternion {
+        let v = match axis {
+            'X' => Vector3::new(1.0, 0.0, 0.0),
+            'Y' => Vector3::new(0.0, 1.0, 0.0),
+            _ => Vector3::new(0.0, 0.0, 1.0),
+        };
+        Quaternion::from_axis_angle(v, angle)
+    }
+
+    #[test]
+    fn euler_orders() {
+        let mut r = Random::new(3);
+        for order in EULER_ORDERS {
+            let axes: Vec<char> = format!("{:?}", order).chars().collect();
+            for _ in 0..100 {
+                // Middle axis is limited to -90..90
+                let angle = |a: char, r: &mut Random| {
+                    if a == axes[1] {
+                        r.range(-1.5, 1.5)
+                    } else {
+                        r.range(-3.1, 3.1)
+                    }
+                };
+                let v = Vector3::new(angle('X', &mut r), angle('Y', &mut r), angle('Z', &mut r));
+                let pick = |a: char| match a {
+                    'X' => v.x,
+                    'Y' => v.y,
+                    _ => v.z,
+                };
+
+                // Intrinsic order, first axis is applied first
+                let expected = axis_rotation(axes[0], pick(axes[0]))
+                    * axis_rotation(axes[1], pick(axes[1]))
+                    * axis_rotation(axes[2], pick(axes[2]));
+                let q = Quaternion::from_euler_order(v, order);
+                assert!(q.angle_between(expected) < 1e-3, "{:?}", order);
+
+                let back = q.to_euler_order(order);
+                assert!((back - v).length() < 1e-3, "{:?} {} {}", order, v, back);
+
+                let mx = Matrix4x4::from_euler_order(v, order);
+                assert!((mx.to_euler_order(order) - v).length() < 1e-3);
+                let m3 = Matrix3x3::from_euler_order(v, order);
+                assert!((m3.to_euler_order(order) - v).length() < 1e-3);
+            }
+        }
+
+        // The one argument forms keep their original orders
+        let v = Vector3::new(0.3, -0.2, 0.5);
+        assert_eq!(
+            Quaternion::from_euler(v),
+            Quaternion::from_euler_order(v, EulerOrder::XYZ)
+        );
+        let q = Quaternion::from_euler_order(v, EulerOrder::ZYX);
+        assert!((q.to_euler() - v).length() < 1e-5);
+    }
+
+    #[test]
+    fn euler_gimbal_lock() {
+        for order in EULER_ORDERS {
+            let axes: Vec<char> = format!("{:?}", order).chars().collect();
+            for middle in [std::f32::consts::FRAC_PI_2, -std::f32::consts::FRAC_PI_2] {
+                let mut v = Vector3::new(0.4, 0.4, 0.4);
+                match axes[1] {
+                    'X' => v.x = middle,
+                    'Y' => v.y = middle,
+                    _ => v.z = middle,
+                }
+
+                // Angles can differ at the pole but the rotation must not
+                let q = Quaternion::from_euler_order(v, order);
+                let back = q.to_euler_order(order);
+                let q2 = Quaternion::from_euler_order(back, order);
+                assert!(q.angle_between(q2) < 2e-3, "{:?} {}", order, back);
+            }
+        }
+    }
+
+    #[test]
+    fn dual_quaternion_matrix() {
+        let mut r = Random::new(5);
+        for _ in 0..50 {
+            let (t, q, _) = random_trs(&mut r);
+            let (t2, q2, _) = random_trs(&mut r);
+            let a = DualQuaternion::from_rotation_translation(q, t);
+            let b = DualQuaternion::from_rotation_translation(q2, t2);
+            let ma = Matrix4x4::from_trs(t, q, Vector3::one());
+            let mb = Matrix4x4::from_trs(t2, q2, Vector3::one());
+
+            assert_matrix_eq(a.to_matrix4x4(), ma, 1e-3);
+            assert_matrix_eq((a * b).to_matrix4x4(), ma * mb, 1e-3);
+            assert!((DualQuaternion::from_matrix4x4(ma).get_translation() - t).length() < 1e-3);
+
+            let p = Vector3::new(1.0, -2.0, 3.0);
+            assert!((a.transform_point(p) - p * ma).length() < 1e-3);
+            assert!((a.inverse().transform_point(a.transform_point(p)) - p).length() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn dual_quaternion_sclerp() {
+        let axis: Vector3 = Vector3::new(0.0, 1.0, 0.0);
+        let a = DualQuaternion::from_rotation_translation(
+            Quaternion::from_axis_angle(axis, 0.0),
+            Vector3::new(0.0, 0.0, 0.0),
+        );
+        let b = DualQuaternion::from_rotation_translation(
+            Quaternion::from_axis_angle(axis, 1.0),
+            Vector3::new(0.0, 4.0, 0.0),
+        );
+
+        // Screw along y: half the angle and half the height
+        let mid = DualQuaternion::sclerp(a, b, 0.5);
+        let (mid_axis, angle) = mid.get_rotation().to_axis_angle();
+        assert!((mid_axis - axis).length() < 1e-4);
+        assert!((angle - 0.5).abs() < 1e-4);
+        assert!((mid.get_translation() - Vector3::new(0.0, 2.0, 0.0)).length() < 1e-4);
+
+        assert_matrix_eq(
+            DualQuaternion::sclerp(a, b, 1.0).to_matrix4x4(),
+            b.to_matrix4x4(),
+            1e-4,
+        );
+
+        // Pure translation interpolates linearly
+        let c = DualQuaternion::from_translation(Vector3::new(2.0, 0.0, -6.0));
+        let q = DualQuaternion::sclerp(a, c, 0.25);
+        assert!((q.get_translation() - Vector3::new(0.5, 0.0, -1.5)).length() < 1e-4);
+    }
+
+    #[test]
+    fn dual_quaternion_blend() {
+        let q = Quaternion::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), 0.8);
+        let a = DualQuaternion::from_rotation_translation(q, Vector3::new(1.0, 0.0, 0.0));
+        let b = DualQuaternion::from_rotation_translation(-q, Vector3::new(3.0, 0.0, 0.0));
+
+        // -q is the same rotation, blending must not collapse
+        let r = DualQuaternion::dlb(&[a, b], &[0.5, 0.5]);
+        assert!(r.get_rotation().angle_between(q) < 1e-4);
+        assert!((r.get_translation() - Vector3::new(2.0, 0.0, 0.0)).length() < 1e-4);
+    }
+
+    fn random_matrix(r: &mut Random) -> Matrix4x4 {
+        let (t, q, s) = random_trs(r);
+        let mut mx = Matrix4x4::from_trs(t, q, s);
+        for i in 0..16 {
+            mx.raw[i] += r.range(-0.5, 0.5);
+        }
+        mx
+    }
+
+    #[test]
+    fn simd_mul() {
+        let mut r = Random::new(13);
+        for _ in 0..200 {
+            let a = random_matrix(&mut r);
+            let b = random_matrix(&mut r);
+            assert_eq!((a * b).raw, a.mul_fallback(&b).raw);
+
+            let mut c = a;
+            c *= b;
+            assert_eq!(c.raw, (a * b).raw);
+        }
+    }
+
+    #[test]
+    fn simd_invert() {
+        let mut r = Random::new(17);
+        for _ in 0..200 {
+            let a = random_matrix(&mut r);
+            assert_matrix_eq(a.invert(), a.invert_fallback(), 1e-4);
+        }
+        let zero: Matrix4x4 = Matrix4x4 { raw: [0.0; 16] };
+        assert_eq!(zero.invert().raw, Matrix4x4::<f32>::new().raw);
+    }
+
+    #[test]
+    fn simd_transform_points() {
+        let mut r = Random::new(19);
+        let mut mx = Matrix4x4::new();
+        mx.perspective(1.0, 1.5, 0.1, 100.0);
+        mx *= random_matrix(&mut r);
+
+        let mut points: Vec<Vector3> = (0..101)
+            .map(|_| Vector3::new(r.range(-9.0, 9.0), r.range(-9.0, 9.0), r.range(-9.0, 9.0)))
+            .collect();
+        let expected: Vec<Vector3> = points.iter().map(|p| *p * mx).collect();
+
+        matrix4::transform_points(&mut points, &mx);
+        for (p, e) in points.iter().zip(expected.iter()) {
+            assert_eq!((p.x, p.y, p.z), (e.x, e.y, e.z));
+        }
+    }
+
+    #[test]
+    fn vector_operators() {
+        let a: Vector3 = Vector3::new(1.0, -2.0, 3.0);
+        let b = Vector3::new(4.0, 5.0, -6.0);
+
+        assert_eq!(-a, Vector3::new(-1.0, 2.0, -3.0));
+        assert_eq!(a * b, Vector3::new(4.0, -10.0, -18.0));
+        assert_eq!(b / a, Vector3::new(4.0, -2.5, -2.0));
+        assert_eq!(2.0 * a, a * 2.0);
+        assert_eq!(a / 2.0, Vector3::new(0.5, -1.0, 1.5));
+        assert_eq!(a.min(b), Vector3::new(1.0, -2.0, -6.0));
+        assert_eq!(a.max(b), Vector3::new(4.0, 5.0, 3.0));
+        assert_eq!(a.abs(), Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(
+            b.clamp(Vector3::zero(), Vector3::one()),
+            Vector3::new(1.0, 1.0, 0.0)
+        );
+        assert_eq!(a[0] + a[1] + a[2], 2.0);
+        assert_eq!([a, b].iter().sum::<Vector3>(), a + b);
+
+        let mut c = a;
+        c[2] = 0.0;
+        c *= b;
+        c /= 2.0;
+        c -= Vector3::new(2.0, 0.0, 0.0);
+        assert_eq!(c, Vector3::new(0.0, -5.0, 0.0));
+        // Deprecated inherent mul still forwards to the operator
+        let d = Vector4::new(2.0, 2.0, 2.0, 2.0);
+        #[allow(deprecated)]
+        let product = (a.mul(b), d.mul(Vector4::new(1.0, 2.0, 3.0, 4.0)));
+        assert_eq!(product, (a * b, Vector4::new(2.0, 4.0, 6.0, 8.0)));
+
+        let up = Vector3::new(0.0, 1.0, 0.0);
+        assert_eq!(
+            Vector3::new(1.0, -1.0, 0.0).reflect(up),
+            Vector3::new(1.0, 1.0, 0.0)
+        );
+        assert_eq!(a.project(up), Vector3::new(0.0, -2.0, 0.0));
+
+        let v: Vector2 = Vector2::new(1.0, 0.0);
+        assert_eq!(v.cross(Vector2::new(0.0, 1.0)), 1.0);
+        let mut v2 = v + Vector2::new(0.0, 2.0);
+        v2 += v;
+        assert_eq!(v2, Vector2::new(2.0, 2.0));
+
+        let q = Quaternion::from_axis_angle(up, 0.5);
+        assert_eq!(q[3], q.w);
+        assert_eq!(q + q, 2.0 * q);
+    }
+
+    #[test]
+    fn matrix_operators() {
+        let mut a: Matrix4x4 = Matrix4x4::new();
+        a.translate(1.0, 2.0, 3.0);
+        assert_eq!(a[(3, 0)], 1.0);
+        assert_eq!(a[(3, 2)], a[14]);
+
+        let b = a + a;
+        assert_eq!(b, a * 2.0);
+        assert_eq!(b - a, a);
+        assert_eq!(-b / 2.0, -a);
+
+        let mut m = Matrix3x3::new();
+        m[(1, 0)] = 5.0;
+        m *= 2.0;
+        assert_eq!(m.raw[3], 10.0);
+        assert_eq!(Matrix2x2::new() * 3.0, 3.0 * Matrix2x2::new());
+        assert!(std::panic::catch_unwind(|| m[(3, 0)]).is_err());
+        assert!(std::panic::catch_unwind(|| m[(0, 3)]).is_err());
+    }
+
+    #[test]
+    fn approx_eq() {
+        // 0.1 + 0.2 is one ulp away from 0.3
+        assert_ulps_eq!(0.1f64 + 0.2, 0.3, max_ulps = 1);
+        assert!(!(0.1f64 + 0.2).ulps_eq(&0.3, 0.0, 0));
+        assert!(!1.0f32.ulps_eq(&-1.0, 0.0, 4));
+        assert!(!f32::NAN.approx_eq(&f32::NAN));
+        assert!(f32::INFINITY.approx_eq(&f32::INFINITY));
+
+        assert_relative_eq!(1_000_000.0f32, 1_000_100.0, max_relative = 1e-3);
+        assert!(!1_000_000.0f32.abs_diff_eq(&1_000_100.0, 1e-3));
+        assert!(!1e-6f32.relative_eq(&2e-6, 0.0, 1e-3));
+
+        let a: Vector4 = Vector4::new(1.0, 2.0, 3.0, 4.0);
+        assert_approx_eq!(a, a + Vector4::new(0.0, 0.0, 0.0, 1e-5), epsilon = 1e-4);
+        assert!(!a.abs_diff_eq(&(a + Vector4::new(0.0, 0.0, 0.0, 1e-3)), 1e-4));
+
+        // Opposite quaternions are the same rotation
+        let q = Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), 1.0);
+        assert_approx_eq!(q, -q);
+        assert!(!q.approx_eq(&Quaternion::new(q.x, q.y, q.z, -q.w)));
+
+        let d = DualQuaternion::from_rotation_translation(q, Vector3::new(1.0, 2.0, 3.0));
+        assert_approx_eq!(d, d * -1.0);
+        let mut mixed = d;
+        mixed.real = -mixed.real;
+        assert!(!d.approx_eq(&mixed));
+
+        let bb = BoundingBox {
+            min_x: -1.0,
+            max_x: 1.0,
+            min_y: -1.0,
+            max_y: 1.0,
+            min_z: -1.0,
+            max_z: 1.0,
+        };
+        let mut bb2 = bb;
+        bb2.max_z += 1e-6;
+        assert_approx_eq!(bb, bb2, epsilon = 1e-5);
+
+        let c = ColorRGBA::white();
+        let mut c2 = c;
+        c2.g = 254;
+        assert_approx_eq!(c, c2, epsilon = 1.0 / 255.0);
+        assert!(!c.approx_eq(&c2));
+    }
+
+    #[test]
+    fn byte_round_trip() {
+        let v: Vector3 = Vector3::new(1.5, -2.0, 1e-7);
+        assert_eq!(Vector3::from_bytes(&v.to_bytes()), v);
+        assert_eq!(Vector3::from_be_bytes(&v.to_be_bytes()), v);
+        assert_eq!(&v.to_bytes()[0..4], &1.5f32.to_le_bytes());
+        assert_eq!(&v.to_be_bytes()[0..4], &1.5f32.to_be_bytes());
+
+        let v2 = Vector2::new(3.0, 4.0);
+        assert_eq!(Vector2::from_bytes(&v2.to_bytes()), v2);
+        let v4 = Vector4::new(1.0, 2.0, 3.0, 4.0);
+        assert_eq!(Vector4::from_be_bytes(&v4.to_be_bytes()), v4);
+        let q = Quaternion::from_axis_angle(Vector3::new(1.0, 0.0, 0.0), 0.3);
+        assert_eq!(Quaternion::from_bytes(&q.to_bytes()), q);
+        let m3 = Matrix3x3::from_quaternion(q);
+        assert_eq!(Matrix3x3::from_be_bytes(&m3.to_be_bytes()), m3);
+        let mut m2 = Matrix2x2::new();
+        m2.rotate(0.4);
+        assert_eq!(Matrix2x2::from_bytes(&m2.to_bytes()), m2);
+
+        // write_to appends, so several values can go into one stream
+        let mx = Matrix4x4::from_trs(v, q, Vector3::one());
+        let mut out = Vec::new();
+        mx.write_to(&mut out).unwrap();
+        v.write_be_to(&mut out).unwrap();
+        assert_eq!(out.len(), 64 + 12);
+        assert_eq!(Matrix4x4::from_bytes(&out[0..64]), mx);
+        assert_eq!(Vector3::from_be_bytes(&out[64..]), v);
+
+        let list = vec![v, Vector3::one(), Vector3::zero()];
+        let bytes = Vector3::slice_as_bytes(&list);
+        assert_eq!(bytes.len(), 36);
+        // slice_as_bytes is a view of memory, so native byte order
+        let one: Vec<u8> = [1.0f32; 3].iter().flat_map(|f| f.to_ne_bytes()).collect();
+        assert_eq!(&bytes[12..24], &one[..]);
+        assert_eq!(Vector3::slice_from_bytes(bytes).unwrap(), &list[..]);
+        assert!(Vector3::slice_from_bytes(&bytes[..30]).is_none());
+        assert!(Vector3::slice_from_bytes(&bytes[1..13]).is_none());
+    }
+
+    #[test]
+    fn transform_hierarchy() {
+        let mut r = Random::new(23);
+        for _ in 0..100 {
+            let (t, q, _) = random_trs(&mut r);
+            let s = r.range(0.5, 3.0);
+            let parent = Transform::new(t, q, Vector3::new(s, s, s));
+            let (t, q, s) = random_trs(&mut r);
+            let child = Transform::new(t, q, s);
+
+            // Composition matches the matrix product
+            assert_matrix_eq(
+                (parent * child).to_matrix4x4(),
+                parent.to_matrix4x4() * child.to_matrix4x4(),
+                1e-2,
+            );
+
+            let p = Vector3::new(r.range(-5.0, 5.0), r.range(-5.0, 5.0), r.range(-5.0, 5.0));
+            assert_approx_eq!(parent * p, p * parent.to_matrix4x4(), epsilon = 1e-3);
+            assert_approx_eq!(parent.inverse() * (parent * p), p, epsilon = 1e-3);
+            assert_approx_eq!(
+                parent.transform_vector(p),
+                parent * p - parent.translation,
+                epsilon = 1e-3
+            );
+
+            let back = Transform::from_matrix4x4(child.to_matrix4x4());
+            assert_approx_eq!(back.translation, child.translation, epsilon = 1e-3);
+            assert_approx_eq!(back.rotation, child.rotation, epsilon = 1e-4);
+            assert_approx_eq!(back.scale, child.scale, epsilon = 1e-3);
+        }
+
+        let a: Transform = Transform::from_translation(Vector3::new(2.0, 0.0, 0.0));
+        let b = Transform::new(
+            Vector3::new(4.0, 0.0, 0.0),
+            Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), 1.0),
+            Vector3::new(3.0, 3.0, 3.0),
+        );
+        let mid = Transform::lerp(a, b, 0.5);
+        assert_approx_eq!(mid.translation, Vector3::new(3.0, 0.0, 0.0));
+        assert_approx_eq!(mid.scale, Vector3::new(2.0, 2.0, 2.0));
+        assert!((mid.rotation.angle_between(Quaternion::identity()) - 0.5).abs() < 1e-5);
+
+        let mut instance = MeshInstance::new();
+        instance.position = Vector3::new(1.0, 2.0, 3.0);
+        instance.rotation = Vector3::new(0.0, 0.5, 0.0);
+        let tr = instance.to_transform();
+        assert_approx_eq!(
+            tr.rotation,
+            Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.5)
+        );
+        assert_matrix_eq(instance.to_matrix4x4(), tr.to_matrix4x4(), 1e-6);
+    }
+
+    #[test]
+    fn planes_and_frustum() {
+        let p = Plane::from_points(
+            Vector3::new(0.0, 2.0, 0.0),
+            Vector3::new(0.0, 2.0, 1.0),
+            Vector3::new(1.0, 2.0, 0.0),
+        );
+        assert_approx_eq!(p.normal, Vector3::new(0.0, 1.0, 0.0));
+        assert_eq!(p.signed_distance(Vector3::new(3.0, 5.0, 1.0)), 3.0);
+        assert_eq!(
+            p.project_point(Vector3::new(3.0, 5.0, 1.0)),
+            Vector3::new(3.0, 2.0, 1.0)
+        );
+        assert_eq!(p.flip().signed_distance(Vector3::zero()), 2.0);
+        let q = Plane::new(Vector3::new(0.0, 0.0, 2.0), -4.0);
+        assert_eq!(
+            q,
+            Plane::from_point_normal(Vector3::new(5.0, 1.0, 2.0), Vector3::new(0.0, 0.0, 1.0))
+        );
+        let hit = q
+            .intersect_ray(&Ray::new(Vector3::zero(), Vector3::new(0.0, 0.0, 1.0)))
+            .unwrap();
+        assert_eq!(hit.distance, 2.0);
+
+        for clip_depth in [ClipDepth::NegativeOneToOne, ClipDepth::ZeroToOne] {
+            let mut camera = PerspectiveCamera::new();
+            camera.clip_depth = clip_depth;
+            camera.near = 0.1;
+            camera.far = 100.0;
+            camera.position = Vector3::new(0.0, 0.0, 5.0);
+            camera.look_at(Vector3::zero(), Vector3::new(0.0, 1.0, 0.0));
+            camera.calculate();
+            let f = camera.frustum();
+
+            assert!(f.contains_point(Vector3::zero()));
+            assert!(!f.contains_point(Vector3::new(0.0, 0.0, 6.0)));
+            assert!(f.contains_point(Vector3::new(0.0, 0.0, 4.8)));
+            assert!(!f.contains_point(Vector3::new(0.0, 0.0, 4.95)));
+            assert!(f.contains_point(Vector3::new(0.0, 0.0, -94.0)));
+            assert!(!f.contains_point(Vector3::new(0.0, 0.0, -96.0)));
+            assert!(!f.contains_point(Vector3::new(50.0, 0.0, 0.0)));
+
+            // Points agree with the clip space test
+            let vp = camera.projection_matrix * camera.view_matrix;
+            let mut r = Random::new(23);
+            for _ in 0..500 {
+                let v = Vector3::new(
+                    r.range(-10.0, 10.0),
+                    r.range(-10.0, 10.0),
+                    r.range(-20.0, 6.0),
+                );
+                let m = vp.raw;
+                let clip = |i: usize| m[i] * v.x + m[4 + i] * v.y + m[8 + i] * v.z + m[12 + i];
+                let c = Vector4::new(clip(0), clip(1), clip(2), clip(3));
+                let z_min = if clip_depth == ClipDepth::ZeroToOne {
+                    0.0
+                } else {
+                    -c.w
+                };
+                let inside = c.x.abs() <= c.w && c.y.abs() <= c.w && c.z >= z_min && c.z <= c.w;
+                assert_eq!(f.contains_point(v), inside, "{}", v);
+            }
+
+            let unit = Sphere::new(Vector3::zero(), 1.0);
+            assert_eq!(f.test_sphere(&unit), Intersection::Inside);
+            assert_eq!(
+                f.test_sphere(&Sphere::new(Vector3::new(0.0, 0.0, 5.0), 1.0)),
+                Intersection::Intersecting
+            );
+            assert_eq!(
+                f.test_sphere(&Sphere::new(Vector3::new(0.0, 0.0, 8.0), 1.0)),
+                Intersection::Outside
+            );
+            assert_eq!(
+                f.test_sphere(&Sphere::new(Vector3::new(30.0, 0.0, 0.0), 1.0)),
+                Intersection::Outside
+            );
+
+            let cube = BoundingBox::new(-Vector3::one(), Vector3::one());
+            assert_eq!(f.test_box(&cube), Intersection::Inside);
+            assert_eq!(f.test_box(&cube.expand(10.0)), Intersection::Intersecting);
+            let behind =
+                BoundingBox::from_center_extents(Vector3::new(0.0, 0.0, 10.0), Vector3::one());
+            assert_eq!(f.test_box(&behind), Intersection::Outside);
+            assert!(
+                !f.intersects_box(&behind) && f.intersects_box(&cube) && f.intersects_sphere(&unit)
+            );
+        }
+    }
+
+    #[test]
+    fn ray_intersections() {
+        let down = Ray::new(Vector3::new(0.0, 5.0, 0.0), Vector3::new(0.0, -2.0, 0.0));
+        let up_plane = Vector3::new(0.0, 1.0, 0.0);
+        let hit = down.intersect_plane(up_plane, 0.0).unwrap();
+        assert_eq!(
+            (hit.distance, hit.point, hit.normal),
+            (5.0, Vector3::zero(), up_plane)
+        );
+        // Plane at y = 7 is behind the ray
+        assert!(down.intersect_plane(up_plane, -7.0).is_none());
+        let below = Ray::new(Vector3::new(0.0, -5.0, 0.0), up_plane);
+        assert_eq!(
+            below.intersect_plane(up_plane, 0.0).unwrap().normal,
+            -up_plane
+        );
+        assert!(Ray::new(Vector3::one(), Vector3::new(1.0, 0.0, 0.0))
+            .intersect_plane(up_plane, 0.0)
+            .is_none());
+
+        let x = Vector3::new(1.0, 0.0, 0.0);
+        let sphere = Sphere::new(Vector3::zero(), 1.0);
+        let hit = Ray::new(Vector3::new(-5.0, 0.0, 0.0), x)
+            .intersect_sphere(&sphere)
+            .unwrap();
+        assert_eq!((hit.distance, hit.normal), (4.0, -x));
+        let hit = Ray::new(Vector3::zero(), x)
+            .intersect_sphere(&sphere)
+            .unwrap();
+        assert_eq!((hit.distance, hit.normal), (1.0, x));
+        assert!(Ray::new(Vector3::new(-5.0, 1.1, 0.0), x)
+            .intersect_sphere(&sphere)
+            .is_none());
+        assert!(Ray::new(Vector3::new(5.0, 0.0, 0.0), x)
+            .intersect_sphere(&sphere)
+            .is_none());
+
+        let cube_box = BoundingBox::new(-Vector3::one(), Vector3::one());
+        let hit = Ray::new(Vector3::new(-5.0, 0.5, 0.0), x)
+            .intersect_box(&cube_box)
+            .unwrap();
+        assert_eq!((hit.distance, hit.normal), (4.0, -x));
+        let hit = Ray::new(Vector3::zero(), -x)
+            .intersect_box(&cube_box)
+            .unwrap();
+        assert_eq!((hit.distance, hit.normal), (1.0, -x));
+        assert!(Ray::new(Vector3::new(-5.0, 2.0, 0.0), x)
+            .intersect_box(&cube_box)
+            .is_none());
+        let mut r = Random::new(22);
+        for _ in 0..200 {
+            let origin = Vector3::new(r.range(-4.0, 4.0), r.range(-4.0, 4.0), r.range(-4.0, 4.0));
+            let ray = Ray::from_points(
+                origin,
+                Vector3::new(r.range(-1.0, 1.0), r.range(-1.0, 1.0), r.range(-1.0, 1.0)),
+            );
+            let hit = ray.intersect_box(&cube_box).unwrap();
+            assert!(cube_box.expand(1e-4).contains_point(hit.point));
+            assert!(!cube_box.expand(-1e-4).contains_point(hit.point));
+            if let Some(s) = ray.intersect_sphere(&sphere) {
+                assert!((s.point.length() - 1.0).abs() < 1e-4);
+            }
+        }
+
+        let (a, b, c) = (
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+        );
+        let front = Ray::new(Vector3::new(0.25, 0.5, 1.0), Vector3::new(0.0, 0.0, -1.0));
+        let hit = front.intersect_triangle(a, b, c, true).unwrap();
+        assert_eq!(hit.hit.distance, 1.0);
+        assert_eq!(hit.hit.normal, Vector3::new(0.0, 0.0, 1.0));
+        assert_approx_eq!(hit.barycentric, Vector3::new(0.25, 0.25, 0.5));
+        let back = Ray::new(Vector3::new(0.25, 0.5, -1.0), Vector3::new(0.0, 0.0, 1.0));
+        assert!(back.intersect_triangle(a, b, c, true).is_none());
+        assert!(back.intersect_triangle(a, b, c, false).is_some());
+        assert!(
+            Ray::new(Vector3::new(0.75, 0.5, 1.0), Vector3::new(0.0, 0.0, -1.0))
+                .intersect_triangle(a, b, c, false)
+                .is_none()
+        );
+
+        // Picking through the camera
+        let cube = ml::render::mesh::cube::new(Vector3::one(), 0xFF);
+        let mut camera = PerspectiveCamera::new();
+        camera.position = Vector3::new(0.0, 0.0, 5.0);
+        camera.look_at(Vector3::zero(), Vector3::new(0.0, 1.0, 0.0));
+        camera.calculate();
+        let ray = camera.screen_ray(Vector2::new(0.0, 0.0));
+        assert_approx_eq!(ray.direction, Vector3::new(0.0, 0.0, -1.0), epsilon = 1e-4);
+        let hit = cube.raycast(&ray, true).unwrap();
+        assert_approx_eq!(hit.hit.point, Vector3::new(0.0, 0.0, 1.0), epsilon = 1e-3);
+        assert_eq!(hit.hit.normal, Vector3::new(0.0, 0.0, 1.0));
+        assert!(hit.triangle < cube.index.len() / 3);
+        let uv = hit.uv.unwrap();
+        assert!((0.0..=1.0).contains(&uv.x) && (0.0..=1.0).contains(&uv.y));
+        assert!(cube
+            .raycast(&camera.screen_ray(Vector2::new(0.9, 0.9)), true)
+            .is_none());
+        // From inside only back faces are hit
+        let inside = Ray::new(Vector3::zero(), x);
+        assert!(cube.raycast(&inside, true).is_none());
+        assert_eq!(cube.raycast(&inside, false).unwrap().hit.distance, 1.0);
+
+        // Same rays for every kind of projection
+        let eye = Vector3::new(3.0, 0.0, 10.0);
+        let mut view = Matrix4x4::new();
+        view.look_at(
+            eye,
+            Vector3::new(3.0, 0.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+        );
+        let fov = 60.0f32.to_radians();
+        for depth in [ClipDepth::NegativeOneToOne, ClipDepth::ZeroToOne] {
+            let mut projections = [Matrix4x4::new(); 5];
+            projections[0].perspective_depth(fov, 1.5, 0.1, 100.0, depth);
+            projections[1].perspective_infinite(fov, 1.5, 0.1, depth);
+            projections[2].perspective_reversed(fov, 1.5, 0.1, 100.0, depth);
+            projections[3].perspective_infinite_reversed(fov, 1.5, 0.1, depth);
+            projections[4].orthographic(-3.0, 3.0, -2.0, 2.0, 0.1, 100.0, depth);
+            for (i, p) in projections.iter().enumerate() {
+                let inverse = (*p * view).invert();
+                let center = Ray::from_ndc(Vector2::new(0.0, 0.0), &inverse, depth);
+                assert_approx_eq!(
+                    center.direction,
+                    Vector3::new(0.0, 0.0, -1.0),
+                    epsilon = 1e-4
+                );
+                assert_approx_eq!(
+                    center.origin,
+                    eye - Vector3::new(0.0, 0.0, 0.1),
+                    epsilon = 1e-3
+                );
+
+                let corner = Ray::from_ndc(Vector2::new(0.5, -0.5), &inverse, depth);
+                if i < 4 {
+                    let expected = Ray::from_points(
+                        eye,
+                        eye + Vector3::new(0.5 * 1.5, -0.5, -1.0 / (fov / 2.0).tan()),
+                    );
+                    assert_approx_eq!(corner.direction, expected.direction, epsilon = 1e-4);
+                } else {
+                    assert_approx_eq!(
+                        corner.direction,
+                        Vector3::new(0.0, 0.0, -1.0),
+                        epsilon = 1e-4
+                    );
+                    assert_approx_eq!(corner.origin, Vector3::new(4.5, -1.0, 9.9), epsilon = 1e-3);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bvh_queries() {
+        use ml::math::geom::bvh::{self, Bvh};
+        use ml::render::mesh::mesh::MeshData;
+
+        // Triangle soup, no index
+        let mut r = Random::new(24);
+        let mut mesh = MeshData::new();
+        for _ in 0..400 {
+            let c = Vector3::new(
+                r.range(-10.0, 10.0),
+                r.range(-10.0, 10.0),
+                r.range(-10.0, 10.0),
+            );
+            for _ in 0..3 {
+                mesh.vertex.push(
+                    c + Vector3::new(r.range(-1.0, 1.0), r.range(-1.0, 1.0), r.range(-1.0, 1.0)),
+                );
+            }
+        }
+        let mut tree = mesh.build_bvh();
+        assert_eq!(tree.triangles.len(), 400);
+        assert!(tree.nodes.len() > 1);
+        assert_approx_eq!(tree.bounds(), BoundingBox::from_points(&mesh.vertex));
+
+        let random_ray = |r: &mut Random| {
+            let origin = Vector3::new(
+                r.range(-15.0, 15.0),
+                r.range(-15.0, 15.0),
+                r.range(-15.0, 15.0),
+            );
+            let target = Vector3::new(r.range(-8.0, 8.0), r.range(-8.0, 8.0), r.range(-8.0, 8.0));
+            Ray::from_points(origin, target)
+        };
+        let check = |tree: &Bvh, mesh: &MeshData, r: &mut Random| {
+            for _ in 0..200 {
+                let ray = random_ray(r);
+                let brute = mesh.raycast(&ray, false);
+                let fast = tree.raycast(&ray, false);
+                assert_eq!(brute.map(|h| h.triangle), fast.map(|h| h.triangle));
+                if let (Some(b), Some(f)) = (brute, fast) {
+                    assert_eq!(b.hit.distance, f.hit.distance);
+                    assert!(tree.any_hit(&ray, b.hit.distance + 1e-3, false));
+                    assert!(!tree.any_hit(&ray, b.hit.distance * 0.999, false));
+                } else {
+                    assert!(!tree.any_hit(&ray, f32::INFINITY, false));
+                }
+            }
+
+            for _ in 0..50 {
+                let p = Vector3::new(
+                    r.range(-12.0, 12.0),
+                    r.range(-12.0, 12.0),
+                    r.range(-12.0, 12.0),
+                );
+                let brute = mesh
+                    .vertex
+                    .chunks_exact(3)
+                    .map(|t| (bvh::closest_point_on_triangle(p, t[0], t[1], t[2]) - p).length())
+                    .fold(f32::INFINITY, f32::min);
+                let nearest = tree.nearest_point(p).unwrap();
+                assert_eq!(nearest.distance, brute);
+                assert!(((nearest.point - p).length() - brute).abs() < 1e-5);
+
+                let query = BoundingBox::from_center_extents(p, Vector3::new(2.0, 2.0, 2.0));
+                let mut found = tree.query_box(&query);
+                found.sort();
+                let expected: Vec<usize> = mesh
+                    .vertex
+                    .chunks_exact(3)
+                    .enumerate()
+                    .filter(|(_, t)| BoundingBox::from_points(t).intersects(&query))
+                    .map(|(i, _)| i)
+                    .collect();
+                assert_eq!(found, expected);
+            }
+        };
+        check(&tree, &mesh, &mut r);
+
+        // Deform and refit
+        for v in mesh.vertex.iter_mut() {
+            *v = Vector3::new(v.x * 0.5, v.y + v.x * 0.2, v.z * 1.5);
+        }
+        tree.refit(&mesh.vertex);
+        assert_approx_eq!(tree.bounds(), BoundingBox::from_points(&mesh.vertex));
+        check(&tree, &mesh, &mut r);
+
+        let a = Vector3::zero();
+        let b = Vector3::new(1.0, 0.0, 0.0);
+        let c = Vector3::new(0.0, 1.0, 0.0);
+        let closest = |p| bvh::closest_point_on_triangle(p, a, b, c);
+        assert_eq!(
+            closest(Vector3::new(0.25, 0.25, 3.0)),
+            Vector3::new(0.25, 0.25, 0.0)
+        );
+        assert_eq!(closest(Vector3::new(-1.0, -1.0, 0.0)), a);
+        assert_eq!(
+            closest(Vector3::new(0.5, -1.0, 0.0)),
+            Vector3::new(0.5, 0.0, 0.0)
+        );
+        assert_eq!(
+            closest(Vector3::new(1.0, 1.0, 0.0)),
+            Vector3::new(0.5, 0.5, 0.0)
+        );
+
+        // Axis aligned rays on the split planes of a floor of unit quads.
+        // Shared edges can report either triangle, so compare distances
+        let mut floor = MeshData::new();
+        for z in -4..=4 {
+            for x in -4..=4 {
+                floor.vertex.push(Vector3::new(x as f32, 0.0, z as f32));
+            }
+        }
+        for z in 0..8 {
+            for x in 0..8 {
+                let i = z * 9 + x;
+                floor.index.extend([i, i + 9, i + 1, i + 1, i + 9, i + 10]);
+            }
+        }
+        let tree = floor.build_bvh();
+        let down = Vector3::new(0.0, -1.0, 0.0);
+        for z in -8..=8 {
+            for x in -8..=8 {
+                let origin = Vector3::new(x as f32 * 0.5, 5.0, z as f32 * 0.5);
+                for ray in [Ray::new(origin, down), Ray::new(origin * 0.5, down)] {
+                    let brute = floor.raycast(&ray, false).map(|h| h.hit.distance);
+                    assert!(brute.is_some());
+                    assert_eq!(tree.raycast(&ray, false).map(|h| h.hit.distance), brute);
+                    assert!(tree.any_hit(&ray, f32::INFINITY, false));
+                }
+            }
+        }
+
+        // Indexed mesh keeps uvs through the mesh
+        let cube = ml::render::mesh::cube::new(Vector3::one(), 0xFF);
+        let tree = cube.build_bvh();
+        let ray = Ray::new(Vector3::new(0.2, 0.3, 5.0), Vector3::new(0.0, 0.0, -1.0));
+        let hit = tree.raycast(&ray, true).unwrap();
+        let brute = cube.raycast(&ray, true).unwrap();
+        assert_eq!((hit.triangle, hit.uv), (brute.triangle, None));
+        assert_eq!(cube.triangle_uv(hit.triangle, hit.barycentric), brute.uv);
+        assert!(Bvh::from_mesh(&MeshData::new())
+            .raycast(&ray, false)
+            .is_none());
+        assert!(Bvh::from_mesh(&MeshData::new()).nearest_point(a).is_none());
+    }
+
+    #[test]
+    fn loose_octree() {
+        use ml::math::geom::octree::{Bounds, Octree};
+        use std::collections::HashMap;
+
+        let mut r = Random::new(25);
+        let world = BoundingBox::new(
+            Vector3::new(-100.0, -100.0, -100.0),
+            Vector3::new(100.0, 100.0, 100.0),
+        );
+        let mut tree: Octree = Octree::new(world, 6);
+        let mut all: HashMap<u32, Bounds> = HashMap::new();
+
+        // Mostly small objects, a few large ones and a few outside the world
+        let random_bounds = |r: &mut Random| -> Bounds {
+            let span = if r.int() % 10 == 0 { 130.0 } else { 95.0 };
+            let c = Vector3::new(
+                r.range(-span, span),
+                r.range(-span, span),
+                r.range(-span, span),
+            );
+            let size = if r.int() % 20 == 0 { 30.0 } else { 2.0 };
+            if r.int() % 2 == 0 {
+                Sphere::new(c, r.range(0.1, size)).into()
+            } else {
+                let e = Vector3::new(r.range(0.1, size), r.range(0.1, size), r.range(0.1, size));
+                BoundingBox::from_center_extents(c, e).into()
+            }
+        };
+        for id in 0..1000 {
+            let b = random_bounds(&mut r);
+            assert!(tree.insert(id, b).is_none());
+            all.insert(id, b);
+        }
+        assert_eq!(tree.len(), 1000);
+
+        let mut camera = PerspectiveCamera::new();
+        camera.near = 0.1;
+        camera.far = 80.0;
+        camera.position = Vector3::new(10.0, 20.0, 60.0);
+        camera.look_at(Vector3::zero(), Vector3::new(0.0, 1.0, 0.0));
+        camera.calculate();
+        let frustum = camera.frustum();
+
+        let check = |tree: &Octree, all: &HashMap<u32, Bounds>, r: &mut Random| {
+            let sorted = |mut v: Vec<u32>| {
+                v.sort();
+                v
+            };
+            let brute = |f: &dyn Fn(&Bounds) -> bool| {
+                sorted(all.iter().filter(|(_, b)| f(b)).map(|(k, _)| *k).collect())
+            };
+            for _ in 0..20 {
+                let c = Vector3::new(
+                    r.range(-120.0, 120.0),
+                    r.range(-120.0, 120.0),
+                    r.range(-120.0, 120.0),
+                );
+                let e = Vector3::new(r.range(1.0, 40.0), r.range(1.0, 40.0), r.range(1.0, 40.0));
+                let query = BoundingBox::from_center_extents(c, e);
+                assert_eq!(
+                    sorted(tree.query_box(&query)),
+                    brute(&|b| b.intersects_box(&query))
+                );
+
+                let s = Sphere::new(c, r.range(1.0, 60.0));
+                assert_eq!(
+                    sorted(tree.query_sphere(&s)),
+                    brute(&|b| b.intersects_sphere(&s))
+                );
+
+                let ray = Ray::from_points(
+                    c,
+                    Vector3::new(r.range(-50.0, 50.0), r.range(-50.0, 50.0), 0.0),
+                );
+                let max = r.range(10.0, 300.0);
+                let hits = tree.query_ray(&ray, max);
+                assert!(hits.windows(2).all(|w| w[0].1 <= w[1].1));
+                let mut hits: Vec<(u32, f32)> = hits;
+                hits.sort_by_key(|h| h.0);
+                let mut expected: Vec<(u32, f32)> = all
+                    .iter()
+                    .filter_map(|(k, b)| {
+                        b.ray_distance(&ray).filter(|d| *d <= max).map(|d| (*k, d))
+                    })
+                    .collect();
+                expected.sort_by_key(|h| h.0);
+                assert_eq!(hits, expected);
+            }
+            assert_eq!(
+                sorted(tree.query_frustum(&frustum)),
+                brute(&|b| b.intersects_frustum(&frustum))
+            );
+        };
+        check(&tree, &all, &mut r);
+
+        // Small steps mostly stay in their node, teleports move them
+        for id in 0..500 {
+            let b = if id % 2 == 0 {
+                match all[&id] {
+                    Bounds::Box(b) => BoundingBox::from_center_extents(
+                        b.center() + Vector3::new(0.5, 0.0, -0.5),
+                        b.extents(),
+                    )
+                    .into(),
+                    Bounds::Sphere(s) => {
+                        Sphere::new(s.center + Vector3::new(0.0, 0.5, 0.0), s.radius).into()
+                    }
+                }
+            } else {
+                random_bounds(&mut r)
+            };
+            assert!(tree.update(id, b));
+            all.insert(id, b);
+        }
+        for id in 500..700 {
+            assert_eq!(tree.remove(id), all.remove(&id));
+        }
+        assert!(!tree.update(600, all[&0]));
+        assert!(tree.remove(600).is_none());
+        assert_eq!(tree.insert(0, all[&1]), Some(all[&0]));
+        all.insert(0, all[&1]);
+        assert_eq!(tree.len(), all.len());
+        assert_eq!(tree.get(0), Some(all[&1]));
+        check(&tree, &all, &mut r);
+
+        // Empty children are pruned, a wandering object keeps one branch
+        let ids: Vec<u32> = all.keys().copied().collect();
+        for id in ids {
+            tree.remove(id);
+        }
+        assert_eq!(tree.node_count(), 1);
+        let mut single: Octree = Octree::new(world, 6);
+        single.insert(7, random_bounds(&mut r));
+        for _ in 0..500 {
+            assert!(single.update(7, random_bounds(&mut r)));
+            assert!(single.node_count() <= 1 + 8 * 6);
+        }
+        single.remove(7);
+        assert_eq!(single.node_count(), 1);
+
+        tree.clear();
+        assert!(tree.is_empty() && tree.query_box(&world).is_empty());
+    }
+
+    #[test]
+    fn oriented_boxes() {
+        let mut r = Random::new(21);
+        let q = Quaternion::from_euler_order(Vector3::new(0.4, 1.1, -0.3), EulerOrder::XYZ);
+        let obb = OrientedBox::new(
+            Vector3::new(1.0, 2.0, 3.0),
+            q.to_matrix3x3(),
+            Vector3::new(4.0, 1.0, 0.5),
+        );
+
+        // Corners and a lump of points near one end, vertex PCA gets pulled by
+        // the lump, the hull doesn't see it
+        let mut points = obb.corners().to_vec();
+        for _ in 0..300 {
+            let local = Vector3::new(r.range(2.0, 4.0), r.range(-1.0, 1.0), r.range(-0.5, 0.5));
+            points.push(obb.center + local * obb.axes);
+        }
+        let hull = hull::convex_hull(&points).unwrap();
+        assert_eq!(hull.len(), 12);
+        for f in hull.iter() {
+            let n = (points[f[1]] - points[f[0]]).cross(points[f[2]] - points[f[0]]);
+            assert!(points.iter().all(|p| n.dot(*p - points[f[0]]) <= 1e-3));
+        }
+        assert!(hull::convex_hull(&[
+            Vector3::zero(),
+            Vector3::one(),
+            Vector3::new(2.0, 2.0, 2.0),
+            Vector3::new(3.0, 3.0, 3.0)
+        ])
+        .is_none());
+
+        let fitted = OrientedBox::from_points_hull(&points);
+        assert!((fitted.volume() - obb.volume()).abs() < 1e-2);
+        assert!((fitted.center - obb.center).length() < 1e-3);
+        assert!(points.iter().all(|p| fitted.contains_point(*p)));
+        let pca = OrientedBox::from_points(&points);
+        assert!(points.iter().all(|p| pca.contains_point(*p)));
+        assert!(pca.volume() >= fitted.volume() - 1e-2);
+        assert!(fitted.volume() < fitted.to_bounding_box().volume());
+        assert!(!obb.contains_point(obb.center + obb.axis(2) * 0.6));
+        assert_approx_eq!(
+            obb.closest_point(obb.center + obb.axis(0) * 10.0),
+            obb.center + obb.axis(0) * 4.0,
+            epsilon = 1e-4
+        );
+
+        // Rigid transforms move the box exactly
+        let mx = Matrix4x4::from_trs(
+            Vector3::new(-3.0, 0.0, 2.0),
+            Quaternion::from_axis_angle(Vector3::new(1.0, 1.0, 0.0), 0.7),
+            Vector3::new(2.0, 2.0, 2.0),
+        );
+        let moved = obb.transform(&mx);
+        assert!((moved.volume() - obb.volume() * 8.0).abs() < 1e-2);
+        assert!(obb.corners().iter().all(|c| moved.contains_point(*c * mx)));
+        let squashed = obb.transform(&Matrix4x4::from_trs(
+            Vector3::zero(),
+            Quaternion::identity(),
+            Vector3::new(1.0, 0.2, 3.0),
+        ));
+        assert!(obb
+            .corners()
+            .iter()
+            .all(|c| squashed.contains_point(Vector3::new(c.x, c.y * 0.2, c.z * 3.0))));
+
+        // Separating axis
+        let unit =
+            OrientedBox::from_bounding_box(&BoundingBox::new(-Vector3::one(), Vector3::one()));
+        let shifted = |x: f32| OrientedBox {
+            center: Vector3::new(x, 0.0, 0.0),
+            ..unit
+        };
+        assert!(unit.intersects(&shifted(1.99)));
+        assert!(!unit.intersects(&shifted(2.01)));
+        // Only an edge pair separates these two
+        let diamond = OrientedBox::new(
+            Vector3::new(2.3, 2.3, 0.0),
+            Quaternion::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), std::f32::consts::FRAC_PI_4)
+                .to_matrix3x3()
+                * Quaternion::from_axis_angle(
+                    Vector3::new(1.0, 0.0, 0.0),
+                    std::f32::consts::FRAC_PI_4,
+                )
+                .to_matrix3x3(),
+            Vector3::one(),
+        );
+        assert_eq!(unit.intersects(&diamond), diamond.intersects(&unit));
+        for _ in 0..500 {
+            let (t, q, s) = random_trs(&mut r);
+            let other = OrientedBox::new(t * 0.5, q.to_matrix3x3(), s.abs());
+            let hit = unit.intersects(&other);
+            assert_eq!(hit, other.intersects(&unit));
+            if other.corners().iter().any(|c| unit.contains_point(*c))
+                || unit.contains_point(other.center)
+            {
+                assert!(hit);
+            }
+            if !unit.to_bounding_box().intersects(&other.to_bounding_box()) {
+                assert!(!hit);
+            }
+            assert_eq!(other.intersects_box(&unit.to_bounding_box()), hit);
+        }
+
+        // One box per bone from the vertices it moves
+        let mut mesh = ml::render::mesh::cube::new(Vector3::new(2.0, 1.0, 1.0), 0xFF);
+        mesh.bone_list = vec![Bone::new(), Bone::new(), Bone::new()];
+        mesh.bone_index = mesh
+            .vertex
+            .iter()
+            .map(|v| Vector4::new(if v.x < 0.0 { 0.0 } else { 1.0 }, 0.0, 0.0, 0.0))
+            .collect();
+        mesh.bone_weight = vec![Vector4::new(1.0, 0.0, 0.0, 0.0); mesh.vertex.len()];
+        let boxes = mesh.bone_oriented_boxes(0.5);
+        assert_eq!(boxes.len(), 3);
+        assert!(boxes[2].is_none());
+        let (left, right) = (boxes[0].unwrap(), boxes[1].unwrap());
+        assert!((left.center.x + 2.0).abs() < 1e-4 && (right.center.x - 2.0).abs() < 1e-4);
+        for v in mesh.vertex.iter() {
+            assert_eq!(left.contains_point(*v), v.x < 0.0);
+            assert_eq!(right.contains_point(*v), v.x > 0.0);
+        }
+    }
+
+    #[test]
+    fn bounding_spheres() {
+        let mut r = Random::new(20);
+        for n in [1, 2, 3, 5, 8] {
+            for _ in 0..20 {
+                let points: Vec<Vector3> = (0..n)
+                    .map(|_| {
+                        Vector3::new(r.range(-3.0, 3.0), r.range(-3.0, 3.0), r.range(-3.0, 3.0))
+                    })
+                    .collect();
+                let exact = Sphere::welzl(&points);
+                let fast = Sphere::ritter(&points);
+                let holds = |s: &Sphere| {
+                    points
+                        .iter()
+                        .all(|p| s.center.distance_to(*p) <= s.radius * 1.0001 + 1e-5)
+                };
+                assert!(holds(&exact) && holds(&fast));
+                assert!(exact.radius <= fast.radius * 1.0001);
+
+                // Brute force over every sphere through 2, 3 or 4 points
+                let mut best = f32::INFINITY;
+                for a in 0..n {
+                    for b in a..n {
+                        let mut candidates = vec![Sphere::from_two(points[a], points[b])];
+                        for c in b + 1..n {
+                            candidates.push(Sphere::from_three(points[a], points[b], points[c]));
+                            for d in c + 1..n {
+                                candidates.push(Sphere::from_four(
+                                    points[a], points[b], points[c], points[d],
+                                ));
+                            }
+                        }
+                        for s in candidates.iter().filter(|s| holds(s)) {
+                            best = best.min(s.radius);
+                        }
+                    }
+                }
+                assert!(
+                    (exact.radius - best).abs() < 1e-3,
+                    "{} {}",
+                    exact.radius,
+                    best
+                );
+            }
+        }
+
+        let cube = ml::render::mesh::cube::new(Vector3::one(), 0xFF);
+        let s = cube.bounding_sphere();
+        assert_approx_eq!(s.center, Vector3::zero(), epsilon = 1e-5);
+        assert!((s.radius - 3.0f32.sqrt()).abs() < 1e-4);
+        assert!(Sphere::welzl(&[]).is_empty());
+
+        // Skinned meshes use the posed vertices
+        let mut skinned = cube.clone();
+        let mut bone = Bone::new();
+        bone.matrix.translate(10.0, 0.0, 0.0);
+        skinned.bone_list.push(bone);
+        skinned.bone_weight = vec![Vector4::new(1.0, 0.0, 0.0, 0.0); skinned.vertex.len()];
+        skinned.bone_index = vec![Vector4::zero(); skinned.vertex.len()];
+        let moved = skinned.bounding_sphere();
+        assert_approx_eq!(moved.center, Vector3::new(10.0, 0.0, 0.0), epsilon = 1e-4);
+        assert!((moved.radius - s.radius).abs() < 1e-4);
+
+        let a = Sphere::new(Vector3::zero(), 1.0);
+        let b = Sphere::new(Vector3::new(4.0, 0.0, 0.0), 1.0);
+        let m = a.merge(&b);
+        assert_approx_eq!(m, Sphere::new(Vector3::new(2.0, 0.0, 0.0), 3.0));
+        assert!(m.contains(&a) && m.contains(&b));
+        assert_eq!(a.merge(&Sphere::new(Vector3::zero(), 0.5)), a);
+        assert!(!a.intersects(&b) && a.intersects(&Sphere::new(Vector3::new(2.0, 0.0, 0.0), 1.0)));
+
+        let mx = Matrix4x4::from_trs(
+            Vector3::new(1.0, 2.0, 3.0),
+            Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.5),
+            Vector3::new(1.0, 3.0, 2.0),
+        );
+        assert_approx_eq!(
+            a.transform(&mx),
+            Sphere::new(Vector3::new(1.0, 2.0, 3.0), 3.0),
+            epsilon = 1e-5
+        );
+    }
+
+    #[test]
+    fn bounding_box_ops() {
+        let b = BoundingBox::from_points(&[
+            Vector3::new(1.0, -2.0, 0.0),
+            Vector3::new(-1.0, 2.0, 3.0),
+            Vector3::new(0.0, 0.0, 1.0),
+        ]);
+        assert_eq!(b.min(), Vector3::new(-1.0, -2.0, 0.0));
+        assert_eq!(b.max(), Vector3::new(1.0, 2.0, 3.0));
+        assert_eq!(b.center(), Vector3::new(0.0, 0.0, 1.5));
+        assert_eq!(b.size(), Vector3::new(2.0, 4.0, 3.0));
+        assert_eq!(b.extents(), Vector3::new(1.0, 2.0, 1.5));
+        assert_eq!(b.volume(), 24.0);
+        assert_eq!(b.surface_area(), 2.0 * (8.0 + 12.0 + 6.0));
+        assert!(b.contains_point(Vector3::new(1.0, 0.0, 3.0)));
+        assert!(!b.contains_point(Vector3::new(1.1, 0.0, 0.0)));
+
+        assert!(BoundingBox::from_points(&[]).is_empty());
+        assert_eq!(BoundingBox::empty().union(&b), b);
+        assert_eq!(BoundingBox::empty().volume(), 0.0);
+
+        let other = BoundingBox::new(Vector3::new(0.0, 1.0, 2.0), Vector3::new(5.0, 5.0, 5.0));
+        assert!(b.intersects(&other));
+        assert_eq!(
+            b.intersection(&other),
+            Some(BoundingBox::new(
+                Vector3::new(0.0, 1.0, 2.0),
+                Vector3::new(1.0, 2.0, 3.0)
+            ))
+        );
+        let u = b.union(&other);
+        assert!(u.contains(&b) && u.contains(&other));
+        let far = BoundingBox::from_center_extents(Vector3::new(10.0, 0.0, 0.0), Vector3::one());
+        assert!(!b.intersects(&far));
+        assert_eq!(b.intersection(&far), None);
+        assert_eq!(b.expand(1.0).size(), Vector3::new(4.0, 6.0, 5.0));
+        assert_eq!(
+            b.closest_point(Vector3::new(5.0, 0.0, -1.0)),
+            Vector3::new(1.0, 0.0, 0.0)
+        );
+
+        let cube = ml::render::mesh::cube::new(Vector3::new(2.0, 2.0, 2.0), 0xFF);
+        assert_eq!(
+            BoundingBox::from_mesh(&cube),
+            BoundingBox::new(Vector3::new(-2.0, -2.0, -2.0), Vector3::new(2.0, 2.0, 2.0))
+        );
+
+        // Arvo's method matches the box around the transformed corners
+        let mut r = Random::new(19);
+        for _ in 0..50 {
+            let (t, q, s) = random_trs(&mut r);
+            let mx = Matrix4x4::from_trs(t, q, s);
+            let corners: Vec<Vector3> = b.corners().iter().map(|c| *c * mx).collect();
+            assert_approx_eq!(
+                b.transform(&mx),
+                BoundingBox::from_points(&corners),
+                epsilon = 1e-4
+            );
+        }
+    }
+
+    #[test]
+    fn coordinate_systems() {
+        let mut r = Random::new(18);
+        for _ in 0..200 {
+            let v = Vector3::new(r.range(-5.0, 5.0), r.range(-5.0, 5.0), r.range(-5.0, 5.0));
+            let s = Spherical::from_vector3(v);
+            assert!((0.0..=std::f32::consts::PI).contains(&s.polar));
+            assert_approx_eq!(s.to_vector3(), v, epsilon = 1e-4);
+            assert_approx_eq!(Vector3::from(Cylindrical::from(v)), v, epsilon = 1e-4);
+            let p = Vector2::new(v.x, v.y);
+            assert_approx_eq!(Vector2::from(Polar::from(p)), p, epsilon = 1e-4);
+
+            let dir = v.normalize();
+            let (lat, lon) = coordinates::direction_to_lat_lon(dir);
+            assert_approx_eq!(
+                coordinates::lat_lon_to_direction(lat, lon),
+                dir,
+                epsilon = 1e-5
+            );
+            let uv = coordinates::direction_to_equirect_uv(dir);
+            assert!((0.0..=1.0).contains(&uv.x) && (0.0..=1.0).contains(&uv.y));
+            assert_approx_eq!(
+                coordinates::equirect_uv_to_direction(uv),
+                dir,
+                epsilon = 1e-5
+            );
+        }
+
+        // Axes
+        let s = Spherical::new(
+            2.0f32,
+            std::f32::consts::FRAC_PI_2,
+            std::f32::consts::FRAC_PI_2,
+        );
+        assert_approx_eq!(s.to_vector3(), Vector3::new(2.0, 0.0, 0.0));
+        assert_approx_eq!(
+            Spherical::new(1.0f32, 0.0, 1.0).to_vector3(),
+            Vector3::new(0.0, 1.0, 0.0)
+        );
+        assert!(Spherical::new(1.0f32, 0.0, 0.0).make_safe().polar > 0.0);
+        assert_eq!(
+            Spherical::from_vector3(Vector3::<f32>::zero()),
+            Spherical::default()
+        );
+        assert_approx_eq!(
+            Polar::new(2.0f32, std::f32::consts::FRAC_PI_2).to_vector2(),
+            Vector2::new(0.0, 2.0)
+        );
+        assert_approx_eq!(
+            coordinates::equirect_uv_to_direction(Vector2::new(0.5f32, 0.0)),
+            Vector3::new(0.0, 1.0, 0.0)
+        );
+        assert_approx_eq!(
+            coordinates::direction_to_equirect_uv(Vector3::new(0.0f32, 0.0, 1.0)),
+            Vector2::new(0.5, 0.5)
+        );
+
+        // Even spread, about the same count above and below the equator and in
+        // each half around Y
+        let dirs: Vec<Vector3> = (0..4000)
+            .map(|_| coordinates::uniform_direction(r.range(0.0, 1.0), r.range(0.0, 1.0)))
+            .collect();
+        assert!(dirs.iter().all(|d| (d.length() - 1.0).abs() < 1e-5));
+        let up = dirs.iter().filter(|d| d.y > 0.5).count();
+        let right = dirs.iter().filter(|d| d.x > 0.0).count();
+        assert!((900..1100).contains(&up));
+        assert!((1800..2200).contains(&right));
+    }
+
+    #[test]
+    fn half_float_and_quantize() {
+        assert_eq!(f16::from_f32(1.0), f16::ONE);
+        assert_eq!(f16::from_f32(-2.0), 0xC000);
+        assert_eq!(f16::from_f32(65504.0), f16::MAX);
+        assert_eq!(f16::from_f32(65519.0), f16::MAX);
+        assert_eq!(f16::from_f32(65520.0), f16::INFINITY);
+        assert_eq!(f16::from_f32(f32::NEG_INFINITY), f16::NEG_INFINITY);
+        assert_eq!(f16::from_f32(-0.0), 0x8000);
+        assert!(f16::to_f32(f16::from_f32(f32::NAN)).is_nan());
+        assert_eq!(f16::from_f32(2.0f32.powi(-24)), f16::MIN_POSITIVE_SUBNORMAL);
+        // Ties go to even
+        assert_eq!(f16::from_f32(2.0f32.powi(-25)), 0);
+        assert_eq!(f16::from_f32(3.0 * 2.0f32.powi(-25)), 2);
+        assert_eq!(f16::from_f32(1.0 + 2.0f32.powi(-11)), f16::ONE);
+        assert_eq!(f16::from_f32(1.0 + 3.0 * 2.0f32.powi(-11)), f16::ONE + 2);
+
+        // Every finite half survives the trip through f32
+        for h in 0..=u16::MAX {
+            let v = f16::to_f32(h);
+            if v.is_nan() {
+                assert!(f16::to_f32(f16::from_f32(v)).is_nan());
+            } else {
+                assert_eq!(f16::from_f32(v), h);
+            }
+        }
+        assert_eq!(
+            f16::to_f32_slice(&f16::from_f32_slice(&[0.5, -3.0])),
+            [0.5, -3.0]
+        );
+
+        assert_eq!(quantize::unorm8(1.0), 255);
+        assert_eq!(quantize::unorm8(2.0), 255);
+        assert_eq!(quantize::snorm8(-1.0), -127);
+        assert_eq!(quantize::from_snorm8(-128), -1.0);
+        assert_eq!(quantize::from_snorm16(quantize::snorm16(-1.0)), -1.0);
+        assert_eq!(quantize::snorm16(0.0), 0);
+        let mut r = Random::new(17);
+        for _ in 0..1000 {
+            let v = r.range(-1.0, 1.0);
+            let u = r.range(0.0, 1.0);
+            assert!((quantize::from_snorm16(quantize::snorm16(v)) - v).abs() <= 0.5 / 32767.0);
+            assert!((quantize::from_unorm16(quantize::unorm16(u)) - u).abs() <= 0.5 / 65535.0);
+            assert!((quantize::from_snorm8(quantize::snorm8(v)) - v).abs() <= 0.5 / 127.0);
+            assert!((quantize::from_unorm8(quantize::unorm8(u)) - u).abs() <= 0.5 / 255.0);
+
+            let x = r.range(-20.0, 30.0);
+            let q = quantize::range_unorm16(x, -20.0, 30.0);
+            assert!((quantize::from_range_unorm16(q, -20.0, 30.0) - x).abs() <= 50.0 / 65535.0);
+
+            let n = Vector3::new(r.range(-1.0, 1.0), r.range(-1.0, 1.0), r.range(-1.0, 1.0));
+            if n.length() < 1e-3 {
+                continue;
+            }
+            let n = n.normalize();
+            assert!((quantize::oct_decode(quantize::oct_encode(n)) - n).length() < 1e-5);
+            let back = quantize::oct_decode_snorm16(quantize::oct_encode_snorm16(n));
+            assert!(back.cross(n).length().atan2(back.dot(n)).to_degrees() < 0.005);
+            let back = quantize::oct_decode_snorm8(quantize::oct_encode_snorm8(n));
+            assert!(back.cross(n).length().atan2(back.dot(n)).to_degrees() < 1.0);
+        }
+        let down = Vector3::new(0.0, 0.0, -1.0);
+        assert_approx_eq!(quantize::oct_decode(quantize::oct_encode(down)), down);
+    }
+
+    #[test]
+    fn quaternion_exp_ln() {
+        let mut r = Random::new(16);
+        for _ in 0..100 {
+            let (_, q, _) = random_trs(&mut r);
+            let q = q.cast::<f64>().normalize();
+
+            assert_approx_eq!(q.ln().exp(), q, epsilon = 1e-12);
+            assert!(q.ln().w.abs() < 1e-12);
+            assert_approx_eq!(q.pow(2.0), q * q, epsilon = 1e-12);
+            assert_approx_eq!(q.pow(0.5) * q.pow(0.5), q, epsilon = 1e-12);
+
+            let v = q.to_scaled_axis();
+            let (axis, angle) = q.to_axis_angle();
+            assert_approx_eq!(v, axis * angle, epsilon = 1e-9);
+            assert!(Quaternion::from_scaled_axis(v).angle_between(q) < 1e-6);
+
+            // Integrating the velocity between two orientations gets from one to the other
+            let (_, to, _) = random_trs(&mut r);
+            let to = to.cast::<f64>().normalize();
+            let w = Quaternion::angular_velocity(q, to, 0.5);
+            let mut step = q;
+            for _ in 0..10 {
+                step = step.integrate(w, 0.05);
+            }
+            assert!(step.angle_between(to) < 1e-6);
+
+            // Swing and twist recompose, twist stays on the axis
+            let axis = Vector3::new(0.3, 1.0, -0.2).normalize();
+            let (swing, twist) = q.swing_twist(axis);
+            assert_approx_eq!(swing * twist, q, epsilon = 1e-12);
+            assert!(Vector3::new(twist.x, twist.y, twist.z).cross(axis).length() < 1e-12);
+            assert!(Vector3::new(swing.x, swing.y, swing.z).dot(axis).abs() < 1e-12);
+        }
+
+        let spin = Quaternion::identity().integrate(Vector3::new(0.0, 0.0, 1.0f32), 0.5);
+        assert_approx_eq!(
+            spin,
+            Quaternion::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), 0.5)
+        );
+        let twisted = Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.8f32);
+        let (swing, twist) = twisted.swing_twist(Vector3::new(0.0, 1.0, 0.0));
+        assert_approx_eq!(swing, Quaternion::identity());
+        assert_approx_eq!(twist, twisted);
+    }
+
+    #[test]
+    fn eigen_and_polar() {
+        let mut r = Random::new(15);
+        for _ in 0..50 {
+            let (_, q, _) = random_trs(&mut r);
+            let rot = Matrix3x3::from_quaternion(q.cast::<f64>().normalize());
+            let diag = Matrix3x3::from([3.0, 0.0, 0.0, 0.0, -1.0, 0.0, 0.0, 0.0, 0.5]);
+            let a = rot * diag * rot.transpose();
+
+            let (values, vectors) = a.symmetric_eigen();
+            assert_approx_eq!(values, Vector3::new(3.0, 0.5, -1.0), epsilon = 1e-9);
+            assert!((vectors.determinant() - 1.0).abs() < 1e-9);
+            for i in 0..3 {
+                let v = vectors.column(i);
+                let l = [values.x, values.y, values.z][i];
+                assert_approx_eq!(v * a, v * l, epsilon = 1e-9);
+            }
+        }
+
+        // Points spread along a diagonal have it as first principal axis
+        let dir = Vector3::new(1.0f64, 1.0, 0.0).normalize();
+        let points: Vec<Vector3<f64>> = (0..100)
+            .map(|i| {
+                let t = (i as f64 - 49.5) / 10.0;
+                dir * t + Vector3::new(0.0, 0.0, (i % 3) as f64 * 0.1) + Vector3::new(2.0, 3.0, 4.0)
+            })
+            .collect();
+        assert_approx_eq!(
+            Vector3::centroid(&points),
+            Vector3::new(2.0, 3.0, 4.099),
+            epsilon = 1e-9
+        );
+        let (values, axes) = Matrix3x3::covariance(&points).symmetric_eigen();
+        assert!(values.x > values.y && values.y >= values.z && values.z >= -1e-12);
+        assert!((axes.column(0).dot(dir).abs() - 1.0).abs() < 1e-6);
+
+        for mirror in [1.0, -1.0] {
+            let m = Matrix3x3::from_matrix4x4(random_matrix(&mut r)).cast::<f64>();
+            let m = m * Matrix3x3::from([mirror, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0]);
+            let (rotation, stretch) = m.polar_decomposition();
+            assert!((rotation.determinant() - 1.0).abs() < 1e-9);
+            assert_approx_eq!(
+                rotation * rotation.transpose(),
+                Matrix3x3::new(),
+                epsilon = 1e-9
+            );
+            assert_approx_eq!(stretch, stretch.transpose());
+            assert_approx_eq!(rotation * stretch, m, epsilon = 1e-9);
+            assert_eq!(stretch.determinant() < 0.0, m.determinant() < 0.0);
+        }
+
+        // Flattened to a plane
+        let q = Quaternion::from_axis_angle(Vector3::new(0.0, 0.0, 1.0), 0.7f64);
+        let flat = Matrix3x3::from_quaternion(q)
+            * Matrix3x3::from([2.0, 0.0, 0.0, 0.0, 3.0, 0.0, 0.0, 0.0, 0.0]);
+        let (rotation, stretch) = flat.polar_decomposition();
+        assert_approx_eq!(rotation * stretch, flat, epsilon = 1e-9);
+        assert!((rotation.determinant() - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn matrixn_solvers() {
+        let close = |a: &[f64], b: &[f64], eps: f64| {
+            assert_eq!(a.len(), b.len());
+            for (x, y) in a.iter().zip(b) {
+                assert!((x - y).abs() < eps, "{:?} != {:?}", a, b);
+            }
+        };
+
+        let mut r = Random::new(14);
+        let a = MatrixN::from_fn(5, 5, |_, _| r.range(-2.0, 2.0) as f64);
+        let x = [1.0, -2.0, 0.5, 3.0, -1.5];
+        let b = a.mul_vector(&x);
+
+        // LU
+        close(&a.solve(&b).unwrap(), &x, 1e-9);
+        let inv = a.inverse().unwrap();
+        close(
+            (&a * &inv).as_slice(),
+            MatrixN::identity(5).as_slice(),
+            1e-9,
+        );
+        let m4 = random_matrix(&mut r);
+        let m3 = Matrix3x3::from_matrix4x4(m4);
+        let det = MatrixN::from(m3).cast::<f64>().determinant();
+        assert!((det - m3.determinant() as f64).abs() < 1e-4 * det.abs().max(1.0));
+        assert!((a.determinant() * inv.determinant() - 1.0).abs() < 1e-9);
+        assert!(MatrixN::<f64>::new(3, 3).lu().is_none());
+
+        // QR, Q is orthogonal and Q * R gives back A
+        let tall = MatrixN::from_fn(7, 3, |_, _| r.range(-1.0, 1.0) as f64);
+        let qr = tall.qr();
+        close(
+            (qr.q.transpose() * qr.q.clone()).as_slice(),
+            MatrixN::identity(7).as_slice(),
+            1e-9,
+        );
+        close((&qr.q * &qr.r).as_slice(), tall.as_slice(), 1e-9);
+
+        // Least squares line fit y = 2x + 1 through noisy points
+        let xs = [0.0, 1.0, 2.0, 3.0, 4.0];
+        let ys = [1.1, 2.9, 5.1, 6.9, 9.0];
+        let fit = MatrixN::from_fn(5, 2, |row, c| if c == 0 { xs[row] } else { 1.0 });
+        let line = fit.least_squares(&ys).unwrap();
+        close(&line, &[1.98, 1.04], 1e-9);
+        close(&fit.svd().solve(&ys), &line, 1e-9);
+        let wide = fit.transpose();
+        assert!(wide.qr().solve(&[1.0, 2.0]).is_none());
+        assert!(wide.least_squares(&[1.0, 2.0]).is_none());
+
+        // Cholesky on A^T * A + I
+        let spd = &(a.transpose() * a.clone()) + &MatrixN::identity(5);
+        let ch = spd.cholesky().unwrap();
+        close((&ch.l * &ch.l.transpose()).as_slice(), spd.as_slice(), 1e-9);
+        close(&ch.solve(&spd.mul_vector(&x)), &x, 1e-9);
+        assert!((-spd).cholesky().is_none());
+
+        // SVD reconstructs A and finds the rank
+        for (rows, cols) in [(6, 4), (3, 5)] {
+            let m = MatrixN::from_fn(rows, cols, |_, _| r.range(-1.0, 1.0) as f64);
+            let svd = m.svd();
+            assert!(svd.s.windows(2).all(|w| w[0] >= w[1]));
+            let k = svd.s.len();
+            let sigma = MatrixN::from_fn(k, k, |i, j| if i == j { svd.s[i] } else { 0.0 });
+            close(
+                (&(&svd.u * &sigma) * &svd.v.transpose()).as_slice(),
+                m.as_slice(),
+                1e-9,
+            );
+            assert_eq!(m.rank(), rows.min(cols));
+        }
+        let rank1 = MatrixN::from_rows(3, 3, &[1.0, 2.0, 3.0, 2.0, 4.0, 6.0, 3.0, 6.0, 9.0]);
+        assert_eq!(rank1.rank(), 1);
+        for m in [rank1.clone(), rank1.transpose(), MatrixN::new(4, 2)] {
+            let svd = m.svd();
+            let k = svd.s.len();
+            for basis in [&svd.u, &svd.v] {
+                close(
+                    (basis.transpose() * basis.clone()).as_slice(),
+                    MatrixN::identity(k).as_slice(),
+                    1e-9,
+                );
+            }
+        }
+        close(
+            (&(&rank1 * &rank1.pseudo_inverse()) * &rank1).as_slice(),
+            rank1.as_slice(),
+            1e-9,
+        );
+
+        // Conversions
+        assert_eq!(MatrixN::from(m4).to_matrix4x4(), Some(m4));
+        let v = Vector4::new(1.0f32, 2.0, 3.0, 4.0);
+        assert_eq!(MatrixN::from(v).to_vector4(), Some(v));
+        let v3 = Vector3::new(1.0f32, 2.0, 3.0);
+        assert_eq!(MatrixN::from(v3).transpose().to_vector3(), Some(v3));
+        assert_eq!(MatrixN::from(m3).to_matrix3x3(), Some(m3));
+        assert_eq!(MatrixN::from(v3).to_matrix4x4(), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        use ml::render::mesh::animation::{MeshAnimation, MeshAnimationSequence};
+        use ml::render::mesh::bone::Bone;
+        use ml::render::mesh::cube;
+
+        // Vectors and matrices are flat arrays
+        let v = Vector3::new(1.0f32, -2.5, 3.0);
+        assert_eq!(serde_json::to_string(&v).unwrap(), "[1.0,-2.5,3.0]");
+        assert_eq!(
+            serde_json::from_str::<Vector3>("[1.0,-2.5,3.0]").unwrap(),
+            v
+        );
+
+        let q = Quaternion::from_axis_angle(Vector3::new(0.0, 1.0, 0.0), 0.5);
+        let json = serde_json::to_string(&q).unwrap();
+        assert!(json.starts_with('['));
+        assert_eq!(serde_json::from_str::<Quaternion>(&json).unwrap(), q);
+
+        let mx = Matrix4x4::from_trs(v, q, Vector3::new(2.0, 2.0, 2.0));
+        let json = serde_json::to_string(&mx).unwrap();
+        assert_eq!(serde_json::from_str::<Matrix4x4>(&json).unwrap(), mx);
+        let bytes = bincode::serialize(&mx).unwrap();
+        assert_eq!(bytes.len(), 16 * 4);
+        assert_eq!(bincode::deserialize::<Matrix4x4>(&bytes).unwrap(), mx);
+
+        // MatrixN checks its shape against the data
+        let m = MatrixN::from_rows(2, 3, &[1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0]);
+        let json = serde_json::to_string(&m).unwrap();
+        assert_eq!(serde_json::from_str::<MatrixN>(&json).unwrap(), m);
+        let bad = r#"{"rows":2,"cols":3,"data":[1.0,2.0]}"#;
+        let err = serde_json::from_str::<MatrixN>(bad).unwrap_err();
+        assert!(err.to_string().contains("needs 6 values"));
+        let huge = r#"{"rows":18446744073709551615,"cols":2,"data":[]}"#;
+        assert!(serde_json::from_str::<MatrixN>(huge).is_err());
+
+        let color = ColorRGBA::white();
+        assert_eq!(serde_json::to_string(&color).unwrap(), "[255,255,255,255]");
+        assert!(serde_json::from_str::<Vector3>("[1.0,2.0]").is_err());
+
+        let mut bone = Bone::new();
+        bone.name = String::from("hip");
+        bone.local_rotation = q;
+        bone.matrix = mx;
+        bone.children = vec![1, 2];
+        let back: Bone = serde_json::from_str(&serde_json::to_string(&bone).unwrap()).unwrap();
+        assert_eq!(back.name, "hip");
+        assert_eq!(back.local_rotation, q);
+        assert_eq!(back.matrix, mx);
+        assert_eq!(back.children, vec![1, 2]);
+
+        let mut mesh = cube::new(Vector3::one(), 0xFF);
+        mesh.bone_list.push(bone);
+        let mut seq = MeshAnimationSequence::new();
+        seq.key = String::from("hip");
+        seq.kind = 2;
+        seq.time = vec![0.0, 1.0];
+        seq.value = vec![
+            Vector4::new(0.0, 0.0, 0.0, 1.0),
+            Vector4::new(q.x, q.y, q.z, q.w),
+        ];
+        let mut animation = MeshAnimation::new();
+        animation.name = String::from("walk");
+        animation.duration = 1.0;
+        animation.sequence_list.push(seq);
+        mesh.animation_list.push(animation);
+
+        let bytes = bincode::serialize(&mesh).unwrap();
+        let mut back: ml::render::mesh::mesh::MeshData = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(back.vertex, mesh.vertex);
+        assert_eq!(back.index, mesh.index);
+        assert_eq!(back.bone_list[0].name, "hip");
+        assert_eq!(back.animation_list[0].name, "walk");
+        assert_eq!(
+            back.animation_list[0].sequence_list[0].value,
+            mesh.animation_list[0].sequence_list[0].value
+        );
+
+        // The bone lookup is not serialized and has to come back on its own
+        back.apply_animation("walk", 0.5);
+        assert!(back.bone_list[0].is_changed);
+        assert_ne!(back.bone_list[0].rotation, Quaternion::identity());
+        back.set_bone_rotation("hip", Quaternion::identity());
+        assert_eq!(back.bone_list[0].local_rotation, Quaternion::identity());
+
+        let mut instance = MeshInstance::new();
+        instance.position = v;
+        let back: MeshInstance =
+            serde_json::from_str(&serde_json::to_string(&instance).unwrap()).unwrap();
+        assert_eq!(back.position, v);
+
+        let mut camera = PerspectiveCamera::new();
+        camera.position = v;
+        camera.clip_depth = ClipDepth::ZeroToOne;
+        let back: PerspectiveCamera =
+            serde_json::from_str(&serde_json::to_string(&camera).unwrap()).unwrap();
+        assert_eq!(back.position, v);
+        assert_eq!(back.clip_depth, ClipDepth::ZeroToOne);
+
+        // Built trees can be cached
+        let tree = mesh.build_bvh();
+        let bytes = bincode::serialize(&tree).unwrap();
+        assert_eq!(
+            bincode::deserialize::<ml::math::geom::bvh::Bvh>(&bytes).unwrap(),
+            tree
+        );
+        let json = serde_json::to_string(&tree).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ml::math::geom::bvh::Bvh>(&json).unwrap(),
+            tree
+        );
+
+        // Broken caches are rejected instead of panicking in the queries
+        let mut bad = tree.clone();
+        bad.nodes[0].first = 1000;
+        bad.nodes[0].count = 1;
+        let json = serde_json::to_string(&bad).unwrap();
+        assert!(serde_json::from_str::<ml::math::geom::bvh::Bvh>(&json).is_err());
+        let mut bad = tree.clone();
+        bad.nodes[0].count = 0;
+        bad.nodes[0].first = 0;
+        let json = serde_json::to_string(&bad).unwrap();
+        assert!(serde_json::from_str::<ml::math::geom::bvh::Bvh>(&json).is_err());
+        let mut bad = tree.clone();
+        let t = bad.triangles[0] as usize;
+        bad.corners[t][1] = bad.positions.len() as u32;
+        let json = serde_json::to_string(&bad).unwrap();
+        assert!(serde_json::from_str::<ml::math::geom::bvh::Bvh>(&json).is_err());
+        let mut bad = tree.clone();
+        bad.triangles[0] = bad.corners.len() as u32;
+        let bytes = bincode::serialize(&bad).unwrap();
+        assert!(bincode::deserialize::<ml::math::geom::bvh::Bvh>(&bytes).is_err());
+    }
 }